@@ -0,0 +1,326 @@
+//! ALTO reader and writer.
+//!
+//! `parse_alto` reads ALTO v2-v4 documents (and page fragments returned by `image_to_alto`)
+//! into the `model` types, `write_alto` writes pages as an ALTO v4 document.
+//! ALTO has no paragraphs, so like Tesseract a block is written as a `ComposedBlock` and every
+//! paragraph as a `TextBlock`. A `TextBlock` outside of a `ComposedBlock` is read as a block
+//! with one paragraph. Only the `pixel` measurement unit is supported.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::alto::{parse_alto, write_alto};
+//!
+//! let alto = std::fs::read_to_string("archive/page.xml").unwrap();
+//! let pages = parse_alto(&alto).unwrap();
+//! std::fs::write("page_v4.xml", write_alto(&pages)).unwrap();
+//! ```
+
+use crate::errors::TesseractError;
+use crate::model::{get_union, Block, BoundingBox, Line, Page, Paragraph, Word};
+use crate::xml::{escape_xml, parse_xml, Element};
+use tesseract_plumbing as pl;
+
+pub fn parse_alto(alto: &str) -> Result<Vec<Page>, TesseractError> {
+    let root = parse_xml(alto)?;
+    let (layout, image) = match root.get_child("alto") {
+        Some(document) => {
+            let description = document.get_child("Description");
+            let unit = description
+                .and_then(|description| description.get_child("MeasurementUnit"))
+                .map(|unit| unit.get_text());
+            if unit.is_some_and(|unit| unit.trim() != "pixel") {
+                return Err(TesseractError::ParseError);
+            }
+            let image = description
+                .and_then(|description| description.get_child("sourceImageInformation"))
+                .and_then(|information| information.get_child("fileName"))
+                .map(|file_name| file_name.get_text().trim().to_string())
+                .filter(|file_name| !file_name.is_empty());
+            (
+                document
+                    .get_child("Layout")
+                    .ok_or(TesseractError::ParseError)?,
+                image,
+            )
+        }
+        // Page fragments.
+        None => (&root, None),
+    };
+    let page_elements: Vec<&Element> = layout
+        .get_children()
+        .filter(|element| element.get_local_name() == "Page")
+        .collect();
+    if page_elements.is_empty() {
+        return Err(TesseractError::ParseError);
+    }
+    page_elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            let mut page = parse_page(element, index)?;
+            if index == 0 {
+                page.image = image.clone();
+            }
+            Ok(page)
+        })
+        .collect()
+}
+
+pub fn write_alto(pages: &[Page]) -> String {
+    let mut alto = get_alto_header(pages.first().and_then(|page| page.image.as_deref()));
+    for (index, page) in pages.iter().enumerate() {
+        alto.push_str(&write_page(page, index));
+    }
+    alto.push_str("\t</Layout>\n</alto>\n");
+    alto
+}
+
+pub(crate) fn get_alto_header(image: Option<&str>) -> String {
+    // ALTO v4 header up to the opening Layout tag, shared with `assembly::assemble_alto`.
+    let mut alto = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <alto xmlns=\"http://www.loc.gov/standards/alto/ns-v4#\" \
+         xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.loc.gov/standards/alto/ns-v4# http://www.loc.gov/standards/alto/v4/alto-4-2.xsd\">\n\
+         \t<Description>\n\
+         \t\t<MeasurementUnit>pixel</MeasurementUnit>\n",
+    );
+    if let Some(image) = image {
+        alto.push_str(&format!(
+            "\t\t<sourceImageInformation>\n\
+             \t\t\t<fileName>{}</fileName>\n\
+             \t\t</sourceImageInformation>\n",
+            escape_xml(image)
+        ));
+    }
+    alto.push_str(&format!(
+        "\t\t<OCRProcessing ID=\"OCR_0\">\n\
+         \t\t\t<ocrProcessingStep>\n\
+         \t\t\t\t<processingSoftware>\n\
+         \t\t\t\t\t<softwareName>tesseract {}</softwareName>\n\
+         \t\t\t\t</processingSoftware>\n\
+         \t\t\t</ocrProcessingStep>\n\
+         \t\t</OCRProcessing>\n\
+         \t</Description>\n\
+         \t<Layout>\n",
+        pl::version().to_string_lossy()
+    ));
+    alto
+}
+
+fn write_position(bbox: &BoundingBox) -> String {
+    format!(
+        "HPOS=\"{}\" VPOS=\"{}\" WIDTH=\"{}\" HEIGHT=\"{}\"",
+        bbox.left,
+        bbox.top,
+        bbox.get_width(),
+        bbox.get_height()
+    )
+}
+
+fn write_page(page: &Page, index: usize) -> String {
+    let mut alto = format!(
+        "\t\t<Page WIDTH=\"{}\" HEIGHT=\"{}\" PHYSICAL_IMG_NR=\"{}\" ID=\"page_{}\">\n\
+         \t\t\t<PrintSpace {}>\n",
+        page.bbox.right,
+        page.bbox.bottom,
+        index,
+        index,
+        write_position(&page.bbox)
+    );
+    let (mut par_id, mut line_id, mut word_id) = (0, 0, 0);
+    for (block_index, block) in page.blocks.iter().enumerate() {
+        alto.push_str(&format!(
+            "\t\t\t\t<ComposedBlock ID=\"cblock_{}_{}\" {}>\n",
+            index,
+            block_index,
+            write_position(&block.bbox)
+        ));
+        for paragraph in block.paragraphs.iter() {
+            alto.push_str(&format!(
+                "\t\t\t\t\t<TextBlock ID=\"block_{}_{}\" {}>\n",
+                index,
+                par_id,
+                write_position(&paragraph.bbox)
+            ));
+            par_id += 1;
+            for line in paragraph.lines.iter() {
+                alto.push_str(&format!(
+                    "\t\t\t\t\t\t<TextLine ID=\"line_{}_{}\" {}>\n",
+                    index,
+                    line_id,
+                    write_position(&line.bbox)
+                ));
+                line_id += 1;
+                for (word_index, word) in line.words.iter().enumerate() {
+                    if word_index > 0 {
+                        let previous = &line.words[word_index - 1].bbox;
+                        alto.push_str(&format!(
+                            "\t\t\t\t\t\t\t<SP WIDTH=\"{}\" VPOS=\"{}\" HPOS=\"{}\"/>\n",
+                            word.bbox.left.saturating_sub(previous.right),
+                            previous.top,
+                            previous.right
+                        ));
+                    }
+                    let confidence = match word.confidence {
+                        Some(confidence) => format!(" WC=\"{:.4}\"", confidence / 100.0),
+                        None => String::new(),
+                    };
+                    alto.push_str(&format!(
+                        "\t\t\t\t\t\t\t<String ID=\"string_{}_{}\" {}{} CONTENT=\"{}\"/>\n",
+                        index,
+                        word_id,
+                        write_position(&word.bbox),
+                        confidence,
+                        escape_xml(&word.text)
+                    ));
+                    word_id += 1;
+                }
+                alto.push_str("\t\t\t\t\t\t</TextLine>\n");
+            }
+            alto.push_str("\t\t\t\t\t</TextBlock>\n");
+        }
+        alto.push_str("\t\t\t\t</ComposedBlock>\n");
+    }
+    alto.push_str("\t\t\t</PrintSpace>\n\t\t</Page>\n");
+    alto
+}
+
+fn parse_number(element: &Element, name: &str) -> Result<Option<f32>, TesseractError> {
+    match element.get_attribute(name) {
+        Some(value) => value
+            .trim()
+            .parse::<f32>()
+            .map(Some)
+            .map_err(|_| TesseractError::ParseError),
+        None => Ok(None),
+    }
+}
+
+fn parse_position(element: &Element) -> Result<Option<BoundingBox>, TesseractError> {
+    let position = (
+        parse_number(element, "HPOS")?,
+        parse_number(element, "VPOS")?,
+        parse_number(element, "WIDTH")?,
+        parse_number(element, "HEIGHT")?,
+    );
+    match position {
+        (Some(left), Some(top), Some(width), Some(height)) => {
+            let (left, top) = (left.max(0.0).round() as u32, top.max(0.0).round() as u32);
+            Ok(Some(BoundingBox::new(
+                left,
+                top,
+                left + width.max(0.0).round() as u32,
+                top + height.max(0.0).round() as u32,
+            )))
+        }
+        (None, None, None, None) => Ok(None),
+        _ => Err(TesseractError::ParseError),
+    }
+}
+
+fn get_elements<'a>(element: &'a Element, names: &[&str]) -> Vec<&'a Element> {
+    element.find_elements(&|child: &Element| names.contains(&child.get_local_name()))
+}
+
+fn parse_page(element: &Element, index: usize) -> Result<Page, TesseractError> {
+    let blocks = get_elements(element, &["ComposedBlock", "TextBlock"])
+        .into_iter()
+        .map(|block_element| match block_element.get_local_name() {
+            "ComposedBlock" => parse_block(block_element),
+            _ => {
+                let paragraph = parse_paragraph(block_element)?;
+                Ok(Block {
+                    bbox: paragraph.bbox,
+                    paragraphs: vec![paragraph],
+                    order: None,
+                })
+            }
+        })
+        .collect::<Result<Vec<Block>, TesseractError>>()?;
+    let bbox = match (
+        parse_number(element, "WIDTH")?,
+        parse_number(element, "HEIGHT")?,
+    ) {
+        (Some(width), Some(height)) => {
+            BoundingBox::new(0, 0, width.round() as u32, height.round() as u32)
+        }
+        _ => get_union(blocks.iter().map(|block| &block.bbox)),
+    };
+    let page_number = match element.get_attribute("PHYSICAL_IMG_NR") {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| TesseractError::ParseError)?,
+        None => index,
+    };
+    Ok(Page {
+        bbox,
+        page_number,
+        image: None,
+        blocks,
+        elapsed_ms: None,
+    })
+}
+
+fn parse_block(element: &Element) -> Result<Block, TesseractError> {
+    let paragraphs = get_elements(element, &["TextBlock"])
+        .into_iter()
+        .map(parse_paragraph)
+        .collect::<Result<Vec<Paragraph>, TesseractError>>()?;
+    Ok(Block {
+        bbox: match parse_position(element)? {
+            Some(bbox) => bbox,
+            None => get_union(paragraphs.iter().map(|paragraph| &paragraph.bbox)),
+        },
+        paragraphs,
+        order: None,
+    })
+}
+
+fn parse_paragraph(element: &Element) -> Result<Paragraph, TesseractError> {
+    let lines = element
+        .get_children()
+        .filter(|child| child.get_local_name() == "TextLine")
+        .map(parse_line)
+        .collect::<Result<Vec<Line>, TesseractError>>()?;
+    Ok(Paragraph {
+        bbox: match parse_position(element)? {
+            Some(bbox) => bbox,
+            None => get_union(lines.iter().map(|line| &line.bbox)),
+        },
+        lines,
+    })
+}
+
+fn parse_line(element: &Element) -> Result<Line, TesseractError> {
+    let words = element
+        .get_children()
+        .filter(|child| child.get_local_name() == "String")
+        .map(parse_word)
+        .collect::<Result<Vec<Word>, TesseractError>>()?;
+    Ok(Line {
+        bbox: match parse_position(element)? {
+            Some(bbox) => bbox,
+            None => get_union(words.iter().map(|word| &word.bbox)),
+        },
+        words,
+        ..Default::default()
+    })
+}
+
+fn parse_word(element: &Element) -> Result<Word, TesseractError> {
+    // WC is a confidence 0-1.
+    Ok(Word {
+        text: element
+            .get_attribute("CONTENT")
+            .ok_or(TesseractError::ParseError)?
+            .to_string(),
+        bbox: parse_position(element)?.ok_or(TesseractError::ParseError)?,
+        confidence: parse_number(element, "WC")?
+            .map(|confidence| (confidence * 10000.0).round() / 100.0),
+        symbols: Vec::new(),
+    })
+}
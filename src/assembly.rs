@@ -0,0 +1,142 @@
+//! Format-aware assembly of recognized pages into a single document.
+//!
+//! Tesseract returns every page as a separate fragment: hOCR and ALTO pages without
+//! the document header and TSV rows whose `page_num` always starts at 1.
+//! Functions of this module merge such pages (or complete single-page documents)
+//! into one well-formed hOCR/ALTO document or a single TSV with one header
+//! and consecutive page numbers.
+
+use crate::alto::get_alto_header;
+use crate::file_types::OutputFileFormat;
+#[cfg(feature = "serde")]
+use crate::json::assemble_json;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+use tesseract_plumbing as pl;
+
+pub const TSV_HEADER: &str =
+    "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext";
+
+pub fn assemble_doc(output_type: &OutputFileFormat, doc_vec: &[String]) -> String {
+    // Merge recognized pages into one document of the given output type.
+    // Formats without a document structure are joined with \n sep.
+    match output_type {
+        OutputFileFormat::HOCR => assemble_hocr(doc_vec),
+        OutputFileFormat::ALTO => assemble_alto(doc_vec),
+        OutputFileFormat::TSV => assemble_tsv(doc_vec),
+        #[cfg(feature = "serde")]
+        OutputFileFormat::JSON => assemble_json(doc_vec),
+        OutputFileFormat::LAYOUT => doc_vec.join("\n\u{c}\n"),
+        OutputFileFormat::MARKDOWN => doc_vec.join("\n\n---\n\n"),
+        _ => doc_vec.join("\n"),
+    }
+}
+
+pub fn assemble_hocr(doc_vec: &[String]) -> String {
+    let mut doc = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\"\n    \
+         \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xml:lang=\"en\" lang=\"en\">\n \
+         <head>\n  \
+         <title></title>\n  \
+         <meta http-equiv=\"Content-Type\" content=\"text/html;charset=utf-8\"/>\n",
+    );
+    doc.push_str(&format!(
+        "  <meta name='ocr-system' content='tesseract {}' />\n",
+        pl::version().to_string_lossy()
+    ));
+    doc.push_str(
+        "  <meta name='ocr-capabilities' content='ocr_page ocr_carea ocr_par ocr_line ocrx_word ocrp_wconf'/>\n \
+         </head>\n \
+         <body>\n",
+    );
+    for (index, page) in doc_vec.iter().enumerate() {
+        let page = extract_between(page, "<body>", "</body>");
+        doc.push_str(&renumber_hocr_page(page, index));
+        if !page.ends_with('\n') {
+            doc.push('\n');
+        }
+    }
+    doc.push_str(" </body>\n</html>\n");
+    doc
+}
+
+pub fn assemble_alto(doc_vec: &[String]) -> String {
+    // The header is the same as of `alto::write_alto`, so assembled and written documents are ALTO v4.
+    let mut doc = get_alto_header(None);
+    for (index, page) in doc_vec.iter().enumerate() {
+        let page = extract_between(page, "<Layout>", "</Layout>");
+        doc.push_str(&renumber_alto_page(page, index));
+        if !page.ends_with('\n') {
+            doc.push('\n');
+        }
+    }
+    doc.push_str("\t</Layout>\n</alto>\n");
+    doc
+}
+
+pub fn assemble_tsv(doc_vec: &[String]) -> String {
+    let mut doc = String::from(TSV_HEADER);
+    doc.push('\n');
+    for (index, page) in doc_vec.iter().enumerate() {
+        for row in page.lines() {
+            if row.is_empty() || row.starts_with("level\t") {
+                continue;
+            }
+            let mut columns: Vec<&str> = row.split('\t').collect();
+            let page_num = (index + 1).to_string();
+            if columns.len() > 1 {
+                columns[1] = &page_num;
+            }
+            doc.push_str(&columns.join("\t"));
+            doc.push('\n');
+        }
+    }
+    doc
+}
+
+pub(crate) fn extract_between<'a>(page: &'a str, start: &str, end: &str) -> &'a str {
+    // Complete documents (e.g. produced by the tesseract cli) are reduced to their page content.
+    match (page.find(start), page.rfind(end)) {
+        (Some(start_index), Some(end_index)) if start_index + start.len() <= end_index => {
+            page[start_index + start.len()..end_index].trim_start_matches('\n')
+        }
+        _ => page,
+    }
+}
+
+fn renumber_hocr_page(page: &str, index: usize) -> String {
+    // hOCR ids look like page_1, block_1_2, word_1_15 where the first number is a one-based page number.
+    static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+    static PAGE_NO_REGEX: OnceLock<Regex> = OnceLock::new();
+    let id_regex =
+        ID_REGEX.get_or_init(|| Regex::new(r#"(\bid=['"][A-Za-z_]*?[A-Za-z])_\d+"#).unwrap());
+    let page_no_regex = PAGE_NO_REGEX.get_or_init(|| Regex::new(r"\bppageno \d+").unwrap());
+    let page = id_regex.replace_all(page, |caps: &Captures| {
+        format!("{}_{}", &caps[1], index + 1)
+    });
+    page_no_regex
+        .replace_all(&page, format!("ppageno {}", index).as_str())
+        .into_owned()
+}
+
+fn renumber_alto_page(page: &str, index: usize) -> String {
+    // Tesseract numbers ALTO ids per element type: page_0, block_3, line_7, string_15.
+    // The zero-based page index is inserted before the counter, string_15 becomes string_1_15.
+    // Ids which already carry a page index (page_0, string_0_15 of `write_alto`) get the new index.
+    static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+    static IMAGE_NR_REGEX: OnceLock<Regex> = OnceLock::new();
+    let id_regex = ID_REGEX
+        .get_or_init(|| Regex::new(r#"(\bID="([A-Za-z_]*?[A-Za-z]))_(\d+)(_\d+)?""#).unwrap());
+    let image_nr_regex =
+        IMAGE_NR_REGEX.get_or_init(|| Regex::new(r#"\bPHYSICAL_IMG_NR="\d+""#).unwrap());
+    let page = id_regex.replace_all(page, |caps: &Captures| match (&caps[2], caps.get(4)) {
+        ("page", _) => format!("{}_{}\"", &caps[1], index),
+        (_, Some(counter)) => format!("{}_{}{}\"", &caps[1], index, counter.as_str()),
+        (_, None) => format!("{}_{}_{}\"", &caps[1], index, &caps[3]),
+    });
+    image_nr_regex
+        .replace_all(&page, format!("PHYSICAL_IMG_NR=\"{}\"", index).as_str())
+        .into_owned()
+}
@@ -0,0 +1,65 @@
+//! Local HTTP OCR service.
+//!
+//! Starts `rusttesserast::server` on the given address, see its documentation for endpoints.
+
+use clap::Parser;
+use rusttesserast::constanst::TesseractDefaultConstants;
+use rusttesserast::pool::{EngineConfig, EnginePool};
+use rusttesserast::server::{serve, DEFAULT_MAX_BODY_BYTES};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "rusttesserast-server",
+    version,
+    about = "Local HTTP OCR service"
+)]
+struct Args {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: String,
+    /// Path to the tessdata directory.
+    #[arg(long)]
+    tessdata: Option<String>,
+    /// Default tesseract languages, e.g. eng+rus.
+    #[arg(short, long, default_value = "eng")]
+    lang: String,
+    /// Default Tesseract Page Segmentation Mode.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_PSM)]
+    psm: u32,
+    /// Tesseract Engine Mode.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_OEM)]
+    oem: u32,
+    /// Source image resolution.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_DPI)]
+    dpi: i32,
+    /// Timeout for a single request in seconds.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_TIMEOUT)]
+    timeout: i32,
+    /// Number of tesseract engines working in parallel.
+    #[arg(short = 'j', long, default_value_t = 2)]
+    engines: usize,
+    /// Maximum size of an uploaded image in bytes.
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY_BYTES)]
+    max_body_bytes: usize,
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let pool = Arc::new(EnginePool::new(
+        EngineConfig {
+            dpi: args.dpi,
+            psm: args.psm,
+            oem: args.oem,
+            timeout: args.timeout,
+            datapath: args.tessdata,
+            lang: args.lang,
+        },
+        args.engines,
+    ));
+    let listener = TcpListener::bind(&args.listen).await?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+    serve(listener, pool, args.max_body_bytes).await
+}
@@ -0,0 +1,398 @@
+//! Command-line interface for rusttesserast.
+//!
+//! Recognizes images given as files, directories or glob patterns (`-` reads an image from stdin),
+//! prints recognized documents or saves them to an output directory, and optionally prints a JSON summary.
+//! Exit code is 0 if every image was recognized, otherwise it is mapped from the first `TesseractError`.
+//! With `--worker` it runs `rusttesserast::worker` over stdin and stdout instead.
+
+use clap::Parser;
+use glob::glob;
+use rusttesserast::assembly::assemble_doc;
+use rusttesserast::constanst::TesseractDefaultConstants;
+use rusttesserast::errors::TesseractError;
+use rusttesserast::file_types::OutputFileFormat;
+use rusttesserast::pool::{EngineConfig, EnginePool};
+use rusttesserast::tess_lib::TesseractApi;
+use rusttesserast::utils::{save_doc, save_indexed_pages};
+use rusttesserast::worker::run_worker;
+use serde::Serialize;
+use std::fs::{create_dir_all, read, read_dir};
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+const IMAGE_EXTENSIONS: [&str; 12] = [
+    "png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "pnm", "pbm", "pgm", "ppm", "webp",
+];
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "rusttesserast",
+    version,
+    about = "Recognize images with tesseract"
+)]
+struct Args {
+    /// Image files, directories or glob patterns. Use `-` to read an image from stdin.
+    #[arg(required_unless_present = "worker")]
+    inputs: Vec<String>,
+    /// Path to the tessdata directory.
+    #[arg(long)]
+    tessdata: Option<String>,
+    /// Tesseract languages, e.g. eng+rus.
+    #[arg(short, long, default_value = "eng")]
+    lang: String,
+    /// Tesseract Page Segmentation Mode.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_PSM)]
+    psm: u32,
+    /// Tesseract Engine Mode.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_OEM)]
+    oem: u32,
+    /// Source image resolution.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_DPI)]
+    dpi: i32,
+    /// Timeout for a single image in seconds.
+    #[arg(long, default_value_t = TesseractDefaultConstants::DEFAULT_TIMEOUT)]
+    timeout: i32,
+    /// Output formats separated by comma: txt, tsv, hocr, alto, layout, md, json.
+    #[arg(short, long = "format", value_delimiter = ',', default_value = "txt")]
+    formats: Vec<String>,
+    /// Directory for saving recognized documents. If it is not set, documents are printed to stdout.
+    #[arg(short, long)]
+    output_dir: Option<String>,
+    /// Name of the saved document without extension.
+    #[arg(long, default_value = "data")]
+    doc_name: String,
+    /// Save one file per image using a naming template, e.g. {stem}.{ext} or {index:04}.{ext}.
+    #[arg(long)]
+    page_template: Option<String>,
+    /// Number of tesseract engines working in parallel.
+    #[arg(short = 'j', long, default_value_t = 1)]
+    concurrency: usize,
+    /// Print a JSON summary instead of recognized documents.
+    #[arg(long)]
+    json: bool,
+    /// Run as a long-living worker reading JSON requests from stdin line by line
+    /// and writing JSON results to stdout.
+    #[arg(long, conflicts_with = "inputs")]
+    worker: bool,
+}
+
+enum Input {
+    File(String),
+    Stdin(Vec<u8>),
+}
+
+impl Input {
+    fn name(&self) -> &str {
+        match self {
+            Input::File(path) => path,
+            Input::Stdin(_) => "stdin",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PageSummary {
+    input: String,
+    format: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    elapsed_ms: u128,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    inputs: usize,
+    /// Inputs recognized and saved in every format.
+    succeeded: usize,
+    /// Inputs which failed in at least one format, results has a line per input and format.
+    failed: usize,
+    elapsed_ms: u128,
+    results: Vec<PageSummary>,
+}
+
+type PageResult = Result<Vec<String>, TesseractError>;
+
+fn exit_code(error: &TesseractError) -> u8 {
+    match error {
+        TesseractError::TesseractInitError => 3,
+        TesseractError::NoSuchFileException => 4,
+        TesseractError::TesseracRuntimeError => 5,
+        TesseractError::TesseractTimeoutError => 6,
+        TesseractError::SaveDocError => 7,
+        TesseractError::InvalidImageError => 8,
+        TesseractError::CacheError => 9,
+        TesseractError::JournalError => 10,
+        TesseractError::ParseError => 11,
+        TesseractError::PdfError => 12,
+        TesseractError::RedactionError => 13,
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    match path.extension() {
+        Some(ext) => IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+fn collect_inputs(inputs: &[String]) -> Result<Vec<Input>, TesseractError> {
+    // Expand files, directories, glob patterns and stdin into a flat list of images.
+    let mut images = Vec::new();
+    for input in inputs {
+        if input == "-" {
+            let mut image_data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut image_data)
+                .map_err(|_| TesseractError::NoSuchFileException)?;
+            images.push(Input::Stdin(image_data));
+        } else if Path::new(input).is_dir() {
+            let mut dir_images: Vec<String> = read_dir(input)
+                .map_err(|_| TesseractError::NoSuchFileException)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && is_image(path))
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            dir_images.sort();
+            images.extend(dir_images.into_iter().map(Input::File));
+        } else if input.contains(['*', '?', '[']) {
+            let matched: Vec<String> = glob(input)
+                .map_err(|_| TesseractError::NoSuchFileException)?
+                .filter_map(Result::ok)
+                .filter(|path| path.is_file())
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            if matched.is_empty() {
+                return Err(TesseractError::NoSuchFileException);
+            }
+            images.extend(matched.into_iter().map(Input::File));
+        } else if Path::new(input).is_file() {
+            images.push(Input::File(input.clone()));
+        } else {
+            return Err(TesseractError::NoSuchFileException);
+        }
+    }
+    Ok(images)
+}
+
+fn recognize_input(
+    engine: &mut TesseractApi,
+    input: &Input,
+    formats: &[OutputFileFormat],
+) -> PageResult {
+    match input {
+        Input::File(path) => {
+            let image_data = read(path).map_err(|_| TesseractError::NoSuchFileException)?;
+            engine.set_image_from_mem(&image_data)?;
+        }
+        Input::Stdin(image_data) => engine.set_image_from_mem(image_data)?,
+    }
+    formats
+        .iter()
+        .map(|output_type| engine.get_output(output_type))
+        .collect()
+}
+
+fn save_outputs(
+    output_dir: &str,
+    doc_name: &str,
+    page_template: Option<&str>,
+    names: &[(usize, String)],
+    docs: &[String],
+    output_type: &OutputFileFormat,
+) -> Result<Vec<String>, TesseractError> {
+    // Save recognized pages of one format and return the path of a file for every page.
+    // names - input positions and names of the recognized pages, {index} is the input position.
+    create_dir_all(output_dir).map_err(|_| TesseractError::SaveDocError)?;
+    match page_template {
+        Some(template) => {
+            let pages: Vec<(usize, &str, &str)> = names
+                .iter()
+                .zip(docs.iter())
+                .map(|((index, name), doc)| (*index, name.as_str(), doc.as_str()))
+                .collect();
+            Ok(
+                save_indexed_pages(Some(output_dir), template, &pages, output_type)?
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect(),
+            )
+        }
+        None => {
+            let doc_name = format!("{}.{}", doc_name, output_type.get_type());
+            save_doc(Some(output_dir), Some(&doc_name), docs, output_type)?;
+            Ok(vec![
+                Path::new(output_dir)
+                    .join(doc_name)
+                    .to_string_lossy()
+                    .into_owned();
+                docs.len()
+            ])
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let started = Instant::now();
+
+    let pool = EnginePool::new(
+        EngineConfig {
+            dpi: args.dpi,
+            psm: args.psm,
+            oem: args.oem,
+            timeout: args.timeout,
+            datapath: args.tessdata.clone(),
+            lang: args.lang.clone(),
+        },
+        args.concurrency,
+    );
+
+    if args.worker {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        return match run_worker(&pool, stdin, tokio::io::stdout()).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("{}", error);
+                ExitCode::from(1)
+            }
+        };
+    }
+
+    let mut formats = Vec::with_capacity(args.formats.len());
+    for format in &args.formats {
+        match OutputFileFormat::from_type(format) {
+            Some(
+                output_type @ (OutputFileFormat::TXT
+                | OutputFileFormat::TSV
+                | OutputFileFormat::HOCR
+                | OutputFileFormat::ALTO
+                | OutputFileFormat::JSON
+                | OutputFileFormat::LAYOUT
+                | OutputFileFormat::MARKDOWN),
+            ) => formats.push(output_type),
+            _ => {
+                eprintln!("None existing format {}", format);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let inputs = match collect_inputs(&args.inputs) {
+        Ok(inputs) => inputs,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::from(exit_code(&error));
+        }
+    };
+
+    let formats = Arc::new(formats);
+    let inputs: Vec<Arc<Input>> = inputs.into_iter().map(Arc::new).collect();
+
+    let results: Vec<(PageResult, u128)> = futures::future::join_all(inputs.iter().map(|input| {
+        let input = input.clone();
+        let formats = formats.clone();
+        let started = Instant::now();
+        let pool = &pool;
+        async move {
+            let result = pool
+                .run(None, None, move |engine| {
+                    recognize_input(engine, &input, &formats)
+                })
+                .await;
+            (result, started.elapsed().as_millis())
+        }
+    }))
+    .await;
+
+    let mut first_exit_code: Option<u8> = None;
+    let mut summaries = Vec::new();
+    for (format_index, output_type) in formats.iter().enumerate() {
+        let mut names = Vec::new();
+        let mut docs = Vec::new();
+        for (index, (input, (result, _))) in inputs.iter().zip(results.iter()).enumerate() {
+            if let Ok(pages) = result {
+                names.push((index, input.name().to_string()));
+                docs.push(pages[format_index].clone());
+            }
+        }
+
+        if args.output_dir.is_none() && !args.json && !docs.is_empty() {
+            println!("{}", assemble_doc(output_type, &docs));
+        }
+        let saved = match &args.output_dir {
+            // Saving doesn't need an engine, so it doesn't take a slot of the pool.
+            Some(output_dir) if !docs.is_empty() => save_outputs(
+                output_dir,
+                &args.doc_name,
+                args.page_template.as_deref(),
+                &names,
+                &docs,
+                output_type,
+            ),
+            _ => Ok(Vec::new()),
+        };
+
+        let mut saved_index = 0;
+        for (input, (result, elapsed_ms)) in inputs.iter().zip(results.iter()) {
+            let (status, output, error) = match (result, &saved) {
+                (Ok(_), Ok(paths)) => {
+                    saved_index += 1;
+                    ("ok", paths.get(saved_index - 1).cloned(), None)
+                }
+                (Ok(_), Err(error)) | (Err(error), _) => {
+                    first_exit_code = first_exit_code.or(Some(exit_code(error)));
+                    ("error", None, Some(error.to_string()))
+                }
+            };
+            summaries.push(PageSummary {
+                input: input.name().to_string(),
+                format: output_type.get_type().to_string(),
+                status,
+                output,
+                error,
+                elapsed_ms: *elapsed_ms,
+            });
+        }
+    }
+
+    for summary in summaries.iter().filter(|summary| summary.status == "error") {
+        eprintln!(
+            "{} ({}): {}",
+            summary.input,
+            summary.format,
+            summary.error.as_deref().unwrap_or_default()
+        );
+    }
+    if args.json {
+        // Results are grouped by format, the results of an input are every inputs.len()-th.
+        let failed = (0..inputs.len())
+            .filter(|index| {
+                summaries
+                    .iter()
+                    .skip(*index)
+                    .step_by(inputs.len())
+                    .any(|summary| summary.status == "error")
+            })
+            .count();
+        let summary = Summary {
+            inputs: inputs.len(),
+            succeeded: inputs.len() - failed,
+            failed,
+            elapsed_ms: started.elapsed().as_millis(),
+            results: summaries,
+        };
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+    }
+
+    match first_exit_code {
+        Some(code) => ExitCode::from(code),
+        None => ExitCode::SUCCESS,
+    }
+}
@@ -0,0 +1,334 @@
+//! Content-hash cache of recognition results.
+//!
+//! Results are keyed by a SHA-256 hash of the image bytes and the effective configuration
+//! (output format, languages, psm, oem, dpi, variables, tesseract and tessdata versions),
+//! so a changed option or an updated `.traineddata` file never returns a stale result.
+//! The cache is opt-in: set `TesseractApi::cache` to a `ResultCache` with a memory or disk backend.
+
+use crate::errors::TesseractError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// CacheBackend stores recognized documents by key.
+pub trait CacheBackend: Send {
+    /// Return a cached document.
+    fn get(&mut self, key: &str) -> Option<String>;
+    /// Store a document and return the number of evicted entries.
+    fn put(&mut self, key: &str, value: &str) -> usize;
+    /// Number of cached entries.
+    fn len(&self) -> usize;
+    /// Size of cached documents in bytes.
+    fn size(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// CacheStats contains hit/miss statistics of a `ResultCache`.
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// ResultCache wraps a backend and counts hits and misses.
+pub struct ResultCache {
+    backend: Box<dyn CacheBackend>,
+    stats: CacheStats,
+}
+
+impl fmt::Debug for ResultCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultCache")
+            .field("stats", &self.get_stats())
+            .finish()
+    }
+}
+
+impl ResultCache {
+    pub fn new(backend: Box<dyn CacheBackend>) -> ResultCache {
+        ResultCache {
+            backend,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn memory(max_entries: usize, max_bytes: usize) -> ResultCache {
+        // In-memory LRU cache limited by the number of entries and the size of documents.
+        ResultCache::new(Box::new(MemoryCache::new(max_entries, max_bytes)))
+    }
+
+    pub fn disk(dir: &str, max_bytes: usize) -> Result<ResultCache, TesseractError> {
+        // On-disk cache in dir limited by the size of documents.
+        Ok(ResultCache::new(Box::new(DiskCache::new(dir, max_bytes)?)))
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<String> {
+        let value = self.backend.get(key);
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        value
+    }
+
+    pub fn put(&mut self, key: &str, value: &str) {
+        self.stats.insertions += 1;
+        self.stats.evictions += self.backend.put(key, value) as u64;
+    }
+
+    pub fn get_stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.backend.len(),
+            bytes: self.backend.size(),
+            ..self.stats
+        }
+    }
+}
+
+pub fn get_cache_key(image_data: &[u8], config: &[(&str, String)]) -> String {
+    // SHA-256 of the image bytes followed by name=value pairs of the configuration.
+    let mut hasher = Sha256::new();
+    hasher.update((image_data.len() as u64).to_le_bytes());
+    hasher.update(image_data);
+    for (name, value) in config {
+        hasher.update(name.as_bytes());
+        hasher.update(b"=");
+        hasher.update(value.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Tessdata directories searched by tesseract if the api has no datapath.
+const DEFAULT_TESSDATA_DIRS: [&str; 5] = [
+    "/usr/local/share/tessdata",
+    "/usr/share/tesseract-ocr/5/tessdata",
+    "/usr/share/tesseract-ocr/4.00/tessdata",
+    "/usr/share/tessdata",
+    "/opt/homebrew/share/tessdata",
+];
+
+pub fn get_tessdata_dirs(datapath: Option<&str>) -> Vec<PathBuf> {
+    // Directories where tesseract looks for traineddata files: datapath if it is set,
+    // otherwise TESSDATA_PREFIX (the tessdata dir itself or its parent) and the default install paths.
+    if let Some(datapath) = datapath {
+        return vec![PathBuf::from(datapath)];
+    }
+    let mut dirs = Vec::new();
+    if let Some(prefix) = std::env::var_os("TESSDATA_PREFIX") {
+        let prefix = PathBuf::from(prefix);
+        dirs.push(prefix.join("tessdata"));
+        dirs.push(prefix);
+    }
+    dirs.extend(DEFAULT_TESSDATA_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+pub fn get_tessdata_version(datapath: Option<&str>, lang: Option<&str>) -> String {
+    // Size and modification time of every traineddata file of the languages.
+    // Missing files are recorded as well, so installing them invalidates the cache.
+    let dirs = get_tessdata_dirs(datapath);
+    lang.unwrap_or("eng")
+        .split('+')
+        .map(|lang| {
+            let file_name = format!("{}.traineddata", lang);
+            match dirs
+                .iter()
+                .find_map(|dir| fs::metadata(dir.join(&file_name)).ok())
+            {
+                Some(metadata) => {
+                    let modified = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|modified| modified.as_secs())
+                        .unwrap_or_default();
+                    format!("{}:{}:{}", lang, metadata.len(), modified)
+                }
+                None => format!("{}:missing", lang),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// MemoryCache is an in-memory least recently used cache.
+pub struct MemoryCache {
+    max_entries: usize,
+    max_bytes: usize,
+    bytes: usize,
+    tick: u64,
+    entries: HashMap<String, (String, u64)>,
+    order: BTreeMap<u64, String>,
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize, max_bytes: usize) -> MemoryCache {
+        MemoryCache {
+            max_entries,
+            max_bytes,
+            bytes: 0,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        if let Some((_, tick)) = self.entries.get_mut(key) {
+            self.order.remove(tick);
+            *tick = self.tick;
+            self.order.insert(self.tick, key.to_string());
+        }
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&mut self, key: &str) -> Option<String> {
+        self.touch(key);
+        self.entries.get(key).map(|(value, _)| value.clone())
+    }
+
+    fn put(&mut self, key: &str, value: &str) -> usize {
+        if value.len() > self.max_bytes || self.max_entries == 0 {
+            return 0;
+        }
+        self.tick += 1;
+        if let Some((old_value, old_tick)) = self
+            .entries
+            .insert(key.to_string(), (value.to_string(), self.tick))
+        {
+            self.bytes -= old_value.len();
+            self.order.remove(&old_tick);
+        }
+        self.bytes += value.len();
+        self.order.insert(self.tick, key.to_string());
+
+        let mut evicted = 0;
+        while self.entries.len() > self.max_entries || self.bytes > self.max_bytes {
+            match self.order.pop_first() {
+                Some((_, old_key)) => {
+                    if let Some((old_value, _)) = self.entries.remove(&old_key) {
+                        self.bytes -= old_value.len();
+                        evicted += 1;
+                    }
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn size(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// DiskCache stores every document in a separate file, the least recently used files are evicted first.
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: usize,
+}
+
+impl DiskCache {
+    pub fn new(dir: &str, max_bytes: usize) -> Result<DiskCache, TesseractError> {
+        fs::create_dir_all(dir).map_err(|_| TesseractError::CacheError)?;
+        Ok(DiskCache {
+            dir: PathBuf::from(dir),
+            max_bytes,
+        })
+    }
+
+    fn write_entry(&self, key: &str, value: &str) -> bool {
+        // Write to a temporary file first, so a reader never sees a partial document.
+        let tmp_path = self.dir.join(format!("{}.tmp", key));
+        if fs::write(&tmp_path, value).is_err()
+            || fs::rename(&tmp_path, self.dir.join(format!("{}.cache", key))).is_err()
+        {
+            let _ = fs::remove_file(tmp_path);
+            return false;
+        }
+        true
+    }
+
+    fn get_entries(&self) -> Vec<(PathBuf, u64, std::time::SystemTime)> {
+        match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "cache"))
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    Some((entry.path(), metadata.len(), metadata.modified().ok()?))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl CacheBackend for DiskCache {
+    fn get(&mut self, key: &str) -> Option<String> {
+        // A hit rewrites the entry to refresh its modification time, which is the eviction order.
+        let value = fs::read_to_string(self.dir.join(format!("{}.cache", key))).ok()?;
+        self.write_entry(key, &value);
+        Some(value)
+    }
+
+    fn put(&mut self, key: &str, value: &str) -> usize {
+        if value.len() > self.max_bytes {
+            return 0;
+        }
+        if !self.write_entry(key, value) {
+            return 0;
+        }
+
+        let mut entries = self.get_entries();
+        let mut bytes: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        let mut evicted = 0;
+        for (path, len, _) in entries {
+            if bytes <= self.max_bytes as u64 {
+                break;
+            }
+            if path.file_stem().is_some_and(|stem| stem == key) {
+                continue;
+            }
+            if fs::remove_file(path).is_ok() {
+                bytes -= len;
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    fn len(&self) -> usize {
+        self.get_entries().len()
+    }
+
+    fn size(&self) -> usize {
+        self.get_entries()
+            .iter()
+            .map(|(_, len, _)| *len as usize)
+            .sum()
+    }
+}
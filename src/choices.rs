@@ -0,0 +1,180 @@
+//! Alternative characters.
+//!
+//! The LSTM engine of Tesseract keeps alternatives of every recognized symbol with their confidences.
+//! `recognize_choices` recognizes an image with the hocr_char_boxes variable and the `lstm_choice_mode` of the api
+//! and returns every word with:
+//! 1. symbols - the recognized characters with their boxes, confidences and top-N alternatives.
+//! 2. alternatives - top-N texts of the word combined from the alternatives of its symbols,
+//!    the score of a text is the product of the confidences of its symbols.
+//!
+//! Alternatives can be used to repair words failing a validation, e.g. a checksum of an account number.
+//! Confidences are 0-100, alternatives are ordered by confidence. Only the LSTM engine reports alternatives,
+//! with the legacy engine or lstm_choice_mode 0 a symbol has no alternatives.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let words = tesseract_base.image_to_choices("scans/invoice.png", 3).await.unwrap();
+//!     for word in words.iter() {
+//!         println!("{} {:?}", word.text, word.alternatives);
+//!     }
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::hocr::parse_hocr;
+use crate::model::{BoundingBox, Choice, Page, Symbol};
+use crate::tess_lib::TesseractApi;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::path::Path;
+
+const CHAR_BOXES: &str = "hocr_char_boxes";
+
+const LSTM_CHOICE_MODE: &str = "lstm_choice_mode";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// WordChoices is a recognized word with its alternatives.
+pub struct WordChoices {
+    /// Zero-based index of the page.
+    pub page: usize,
+    pub text: String,
+    pub bbox: BoundingBox,
+    /// Recognition confidence, 0-100.
+    pub confidence: Option<f32>,
+    /// Texts of the word combined from the alternatives of its symbols, the best first.
+    pub alternatives: Vec<Choice>,
+    pub symbols: Vec<Symbol>,
+}
+
+pub fn get_word_choices(pages: &[Page], top_n: usize) -> Vec<WordChoices> {
+    // Words of pages with at most top_n alternatives of every word and symbol.
+    let mut words = Vec::new();
+    for (index, page) in pages.iter().enumerate() {
+        for word in page.get_words() {
+            let mut symbols = word.symbols.clone();
+            for symbol in symbols.iter_mut() {
+                symbol.choices.truncate(top_n);
+            }
+            words.push(WordChoices {
+                page: index,
+                text: word.text.clone(),
+                bbox: word.bbox,
+                confidence: word.confidence,
+                alternatives: match symbols.is_empty() {
+                    true => vec![Choice {
+                        text: word.text.clone(),
+                        confidence: word.confidence.unwrap_or_default(),
+                    }],
+                    false => get_alternatives(&symbols, top_n),
+                },
+                symbols,
+            });
+        }
+    }
+    words
+}
+
+pub fn get_alternatives(symbols: &[Symbol], top_n: usize) -> Vec<Choice> {
+    // Top-N texts of symbols (beam search), a symbol without alternatives is taken as recognized.
+    let mut texts = vec![(String::new(), 1.0f32)];
+    for symbol in symbols {
+        let choices = match symbol.choices.is_empty() {
+            true => vec![Choice {
+                text: symbol.text.clone(),
+                confidence: symbol.confidence.unwrap_or(100.0),
+            }],
+            false => symbol.choices.clone(),
+        };
+        let mut next: Vec<(String, f32)> = Vec::new();
+        for (text, score) in texts.iter() {
+            for choice in choices.iter() {
+                let text = format!("{}{}", text, choice.text);
+                let score = score * choice.confidence.clamp(0.0, 100.0) / 100.0;
+                match next.iter_mut().find(|(other, _)| *other == text) {
+                    Some((_, other)) => *other = other.max(score),
+                    None => next.push((text, score)),
+                }
+            }
+        }
+        next.sort_by(|a, b| b.1.total_cmp(&a.1));
+        next.truncate(top_n);
+        texts = next;
+    }
+    texts
+        .into_iter()
+        .map(|(text, score)| Choice {
+            text,
+            confidence: score * 100.0,
+        })
+        .collect()
+}
+
+pub fn recognize_choices(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    top_n: usize,
+) -> Result<Vec<WordChoices>, TesseractError> {
+    let hocr = get_choice_hocr(tesseract, filename)?;
+    Ok(get_word_choices(&parse_hocr(&hocr)?, top_n))
+}
+
+pub(crate) fn get_choice_hocr(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+) -> Result<String, TesseractError> {
+    // Raw hOCR of an image with symbols and their alternatives, see `hocr::parse_hocr`.
+    // hOCR written by normalization doesn't have the symbols, so the output is not normalized.
+    if !Path::new(filename).exists() {
+        return Err(TesseractError::NoSuchFileException);
+    }
+    tesseract.set_image(filename)?;
+    // The variables of the api are restored when the guard is dropped, also after errors.
+    let guard = ChoiceVariables(tesseract);
+    set_variable(guard.0, CHAR_BOXES, "1")?;
+    let lstm_choice_mode = guard.0.lstm_choice_mode.to_string();
+    set_variable(guard.0, LSTM_CHOICE_MODE, &lstm_choice_mode)?;
+    let normalize = guard.0.normalize.take();
+    let hocr = guard.0.get_output(&OutputFileFormat::HOCR);
+    guard.0.normalize = normalize;
+    hocr
+}
+
+/// ChoiceVariables restores the variables set by `get_choice_hocr` to the values of the api.
+struct ChoiceVariables<'a>(&'a mut TesseractApi);
+
+impl Drop for ChoiceVariables<'_> {
+    fn drop(&mut self) {
+        for name in [CHAR_BOXES, LSTM_CHOICE_MODE] {
+            let value = self
+                .0
+                .variables
+                .iter()
+                .find(|(variable, _)| variable == name)
+                .map_or_else(|| String::from("0"), |(_, value)| value.clone());
+            let _ = set_variable(self.0, name, &value);
+        }
+    }
+}
+
+fn set_variable(
+    tesseract: &mut TesseractApi,
+    name: &str,
+    value: &str,
+) -> Result<(), TesseractError> {
+    // The variable is set on the base api, so the variables of the api are not changed.
+    let name = CString::new(name).unwrap();
+    let value = CString::new(value).map_err(|_| TesseractError::TesseracRuntimeError)?;
+    tesseract
+        .tess_pl
+        .set_variable(&name, &value)
+        .map_err(|_| TesseractError::TesseracRuntimeError)
+}
@@ -8,4 +8,5 @@ impl TesseractDefaultConstants {
     pub const DEFAULT_DPI: i32 = 300;
     pub const DEFAULT_PSM: u32 = 4;
     pub const DEFAULT_OEM: u32 = 3;
+    pub const DEFAULT_LSTM_CHOICE_MODE: u32 = 2;
 }
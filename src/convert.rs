@@ -0,0 +1,92 @@
+//! Conversion between layout formats without re-running OCR.
+//!
+//! Documents are read into the `model` types and written in another format:
+//! 1. hOCR - `hocr`, read and write.
+//! 2. ALTO - `alto`, read v2-v4, write v4.
+//! 3. PAGE XML - `page_xml`, read and write a single page.
+//! 4. TSV - `tsv`, read and write.
+//! 5. JSON - `json`, read and write with the serde feature.
+//! 6. TXT - write only.
+//! 7. Layout-preserving text (`layout`) and Markdown (`markdown`) - write only.
+//!
+//! Other formats return `TesseracRuntimeError`, the same as `TesseractApi::get_output`.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::convert::convert;
+//! use rusttesserast::file_types::OutputFileFormat;
+//!
+//! let hocr = std::fs::read_to_string("archive/page.hocr").unwrap();
+//! let alto = convert(&hocr, &OutputFileFormat::HOCR, &OutputFileFormat::ALTO).unwrap();
+//! ```
+
+use crate::alto::{parse_alto, write_alto};
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::hocr::{parse_hocr, write_hocr};
+#[cfg(feature = "serde")]
+use crate::json::{parse_json, write_json};
+use crate::layout::write_layout_text;
+use crate::markdown::write_markdown;
+use crate::model::Page;
+use crate::page_xml::{parse_page_xml, write_page_xml};
+use crate::tsv::{get_pages, get_rows, parse_tsv, write_tsv};
+use std::time::Duration;
+
+pub fn parse_doc(doc: &str, input_type: &OutputFileFormat) -> Result<Vec<Page>, TesseractError> {
+    match input_type {
+        OutputFileFormat::HOCR => parse_hocr(doc),
+        OutputFileFormat::ALTO => parse_alto(doc),
+        OutputFileFormat::PAGE => Ok(vec![parse_page_xml(doc)?]),
+        OutputFileFormat::TSV => Ok(get_pages(&parse_tsv(doc)?)),
+        #[cfg(feature = "serde")]
+        OutputFileFormat::JSON => parse_json(doc),
+        _ => Err(TesseractError::TesseracRuntimeError),
+    }
+}
+
+pub fn write_doc(pages: &[Page], output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+    // PAGE XML can contain only one page, use `write_page_xml` for every page of a longer document.
+    match output_type {
+        OutputFileFormat::HOCR => Ok(write_hocr(pages)),
+        OutputFileFormat::ALTO => Ok(write_alto(pages)),
+        OutputFileFormat::PAGE => match pages {
+            [page] => Ok(write_page_xml(page)),
+            _ => Err(TesseractError::TesseracRuntimeError),
+        },
+        OutputFileFormat::TSV => Ok(write_tsv(&get_rows(pages), true)),
+        #[cfg(feature = "serde")]
+        OutputFileFormat::JSON => Ok(write_json(pages)),
+        OutputFileFormat::TXT => Ok(pages
+            .iter()
+            .map(Page::get_text)
+            .collect::<Vec<String>>()
+            .join("\n")),
+        OutputFileFormat::LAYOUT => Ok(write_layout_text(pages)),
+        OutputFileFormat::MARKDOWN => Ok(write_markdown(pages)),
+        _ => Err(TesseractError::TesseracRuntimeError),
+    }
+}
+
+pub(crate) fn convert_recognized(
+    hocr: &str,
+    output_type: &OutputFileFormat,
+    elapsed: Duration,
+) -> Result<String, TesseractError> {
+    // Convert the hOCR output of an engine, every page carries the time of the recognition.
+    // Only the JSON output contains the time, see `model::Page::elapsed_ms`.
+    let mut pages = parse_hocr(hocr)?;
+    for page in pages.iter_mut() {
+        page.elapsed_ms = Some(elapsed.as_millis());
+    }
+    write_doc(&pages, output_type)
+}
+
+pub fn convert(
+    doc: &str,
+    input_type: &OutputFileFormat,
+    output_type: &OutputFileFormat,
+) -> Result<String, TesseractError> {
+    write_doc(&parse_doc(doc, input_type)?, output_type)
+}
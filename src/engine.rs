@@ -0,0 +1,262 @@
+//! OCR engine abstraction.
+//!
+//! `OcrEngine` describes the operations the batch machinery needs from an engine, so `recognize_doc`
+//! can run on `TesseractApi`, on a `MockOcrEngine` generated by mockall or on a `FixtureEngine`
+//! returning prepared outputs. Application tests can use the fakes without Tesseract installed.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::engine::{recognize_doc, DocOptions, FixtureEngine};
+//! use rusttesserast::file_types::OutputFileFormat;
+//!
+//! fn main() {
+//!     let mut engine = FixtureEngine::new();
+//!     engine.add_page("page.png", OutputFileFormat::TXT, "Hello");
+//!     let doc = recognize_doc(&mut engine, vec!["page.png"], "txt", &DocOptions::default(), None);
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::job::JobJournal;
+#[cfg(feature = "pdf")]
+use crate::pdf::{bind_pdf_library, PdfOptions, PdfPages};
+use crate::utils::{save_doc, save_indexed_pages};
+use mockall::automock;
+use std::collections::HashMap;
+
+/// OcrEngine recognizes one image at a time.
+#[automock]
+pub trait OcrEngine {
+    /// Load an image from a file.
+    fn set_image(&mut self, filename: &str) -> Result<(), TesseractError>;
+    /// Load an encoded image (png, jpeg, tiff, ...) from memory.
+    fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError>;
+    /// Set the resolution of the loaded image, e.g. the dpi a PDF page was rendered at.
+    fn set_source_resolution(&mut self, _dpi: i32) {}
+    fn set_page_seg_mode(&mut self, psm: u32);
+    /// Recognize the loaded image.
+    fn recognize(&mut self) -> Result<(), TesseractError>;
+    /// Return the recognized image in the given format.
+    fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError>;
+    /// Mean confidence of the recognized words, 0-100.
+    fn mean_text_conf(&mut self) -> i32;
+    /// Load, recognize and return an image file. Engines can override it, e.g. to use a cache.
+    fn recognize_image(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+    ) -> Result<String, TesseractError> {
+        self.set_image(filename)?;
+        self.recognize()?;
+        self.get_output(output_type)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+/// DocOptions describe how `recognize_doc` saves a document.
+pub struct DocOptions<'a> {
+    /// Path for saving a doc. If path is None, then file will be saved to a project dir.
+    pub save_path: Option<&'a str>,
+    /// Name of a doc. Default value - data.txt
+    pub doc_name: Option<&'a str>,
+    /// Save the recognized doc. Default value - false
+    pub save_doc: bool,
+    /// Naming template of per page files. If it is set, every page is saved to its own file
+    /// and save_path is used as an output root.
+    pub page_template: Option<&'a str>,
+    #[cfg(feature = "pdf")]
+    /// Rasterization of PDF files, see `pdf::PdfOptions`.
+    pub pdf_options: PdfOptions,
+}
+
+pub fn recognize_images<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    image_array: &[&str],
+    output_type: &OutputFileFormat,
+) -> Vec<Result<String, TesseractError>> {
+    image_array
+        .iter()
+        .map(|image| engine.recognize_image(image, output_type))
+        .collect()
+}
+
+pub fn recognize_job<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    journal: &mut JobJournal,
+    image_array: &[&str],
+    output_type: &OutputFileFormat,
+) -> Result<Vec<Result<String, TesseractError>>, TesseractError> {
+    // Recognize pages which are not recorded in the journal and record them one by one.
+    journal.start(image_array, output_type)?;
+    let mut doc = Vec::with_capacity(image_array.len());
+    for (index, image) in image_array.iter().enumerate() {
+        if let Some(page) = journal.get_page(index, image) {
+            doc.push(Ok(page));
+            continue;
+        }
+        let page = engine.recognize_image(image, output_type);
+        journal.record(index, image, &page)?;
+        doc.push(page);
+    }
+    journal.finish()?;
+    Ok(doc)
+}
+
+pub fn get_doc_format(output_type: &str) -> Option<OutputFileFormat> {
+    // Output type of `recognize_doc`, None if the type is not supported.
+    match output_type {
+        "txt" => Some(OutputFileFormat::TXT),
+        "tsv" => Some(OutputFileFormat::TSV),
+        "hocr" => Some(OutputFileFormat::HOCR),
+        "alto" => Some(OutputFileFormat::ALTO),
+        #[cfg(feature = "serde")]
+        "json" => Some(OutputFileFormat::JSON),
+        "layout" => Some(OutputFileFormat::LAYOUT),
+        "md" => Some(OutputFileFormat::MARKDOWN),
+        _ => None,
+    }
+}
+
+pub fn recognize_doc<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    image_array: Vec<&str>,
+    output_type: &str,
+    options: &DocOptions<'_>,
+    journal: Option<&mut JobJournal>,
+) -> Result<Vec<String>, TesseractError> {
+    // Recognize images on any engine and save the doc.
+    // image_array - vector which contains a paths to available images.
+    // output_type - a str which contains output type value (txt, tsv, hocr, alto, layout, md, json).
+    // options - how the doc is saved.
+    // journal - optional journal of the job, pages recognized by previous runs are not recognized again.
+    // An unsupported output type is a TesseracRuntimeError.
+    // Images which could not be recognized (e.g. missing files) are skipped.
+    // With the pdf feature PDF files are recognized page by page, see `pdf::PdfPages`.
+    let output_type = get_doc_format(output_type).ok_or(TesseractError::TesseracRuntimeError)?;
+    #[cfg(feature = "pdf")]
+    let pdfium = bind_pdf_library(&image_array, &options.pdf_options);
+    #[cfg(feature = "pdf")]
+    let (mut engine, pdf_images) =
+        PdfPages::new(engine, pdfium.as_ref(), &image_array, &options.pdf_options);
+    #[cfg(feature = "pdf")]
+    let (engine, image_array) = (
+        &mut engine,
+        pdf_images.iter().map(String::as_str).collect::<Vec<&str>>(),
+    );
+    let doc = match journal {
+        Some(journal) => recognize_job(engine, journal, &image_array, &output_type)?,
+        None => recognize_images(engine, &image_array, &output_type),
+    };
+
+    let (recognized_images, filtered_doc): (Vec<(usize, &str)>, Vec<String>) = image_array
+        .iter()
+        .zip(doc)
+        .enumerate()
+        .filter_map(|(index, (image, s))| s.ok().map(|s| ((index, *image), s)))
+        .unzip();
+    if options.save_doc {
+        match options.page_template {
+            Some(template) => {
+                let pages: Vec<(usize, &str, &str)> = recognized_images
+                    .iter()
+                    .zip(filtered_doc.iter())
+                    .map(|((index, image), page)| (*index, *image, page.as_str()))
+                    .collect();
+                save_indexed_pages(options.save_path, template, &pages, &output_type)?;
+            }
+            None => save_doc(
+                options.save_path,
+                options.doc_name,
+                &filtered_doc,
+                &output_type,
+            )?,
+        }
+    }
+    Ok(filtered_doc)
+}
+
+#[derive(Debug, Default, Clone)]
+/// FixtureEngine returns prepared outputs instead of recognizing images.
+pub struct FixtureEngine {
+    /// Mean confidence returned for every image. Default value - 0
+    pub confidence: i32,
+    /// Page segmentation mode set by `set_page_seg_mode`.
+    pub psm: u32,
+    pages: HashMap<(String, OutputFileFormat), String>,
+    images: HashMap<Vec<u8>, String>,
+    current: Option<String>,
+    recognized: Vec<String>,
+}
+
+impl FixtureEngine {
+    pub fn new() -> FixtureEngine {
+        FixtureEngine::default()
+    }
+
+    pub fn add_page(&mut self, image: &str, output_type: OutputFileFormat, output: &str) {
+        // Output returned for an image file in the given format.
+        self.pages
+            .insert((image.to_string(), output_type), output.to_string());
+    }
+
+    pub fn add_image_data(&mut self, image: &str, image_data: &[u8]) {
+        // Image data loaded by `set_image_from_mem` is recognized as the image file.
+        self.images.insert(image_data.to_vec(), image.to_string());
+    }
+
+    pub fn get_recognized(&self) -> &[String] {
+        // Images recognized so far, in order.
+        &self.recognized
+    }
+}
+
+impl OcrEngine for FixtureEngine {
+    fn set_image(&mut self, filename: &str) -> Result<(), TesseractError> {
+        if !self.pages.keys().any(|(image, _)| image == filename) {
+            return Err(TesseractError::NoSuchFileException);
+        }
+        self.current = Some(filename.to_string());
+        Ok(())
+    }
+
+    fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError> {
+        match self.images.get(image_data) {
+            Some(image) => {
+                self.current = Some(image.clone());
+                Ok(())
+            }
+            None => Err(TesseractError::InvalidImageError),
+        }
+    }
+
+    fn set_page_seg_mode(&mut self, psm: u32) {
+        self.psm = psm;
+    }
+
+    fn recognize(&mut self) -> Result<(), TesseractError> {
+        match &self.current {
+            Some(image) => {
+                self.recognized.push(image.clone());
+                Ok(())
+            }
+            None => Err(TesseractError::TesseracRuntimeError),
+        }
+    }
+
+    fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        let image = self
+            .current
+            .clone()
+            .ok_or(TesseractError::TesseracRuntimeError)?;
+        self.pages
+            .get(&(image, *output_type))
+            .cloned()
+            .ok_or(TesseractError::TesseracRuntimeError)
+    }
+
+    fn mean_text_conf(&mut self) -> i32 {
+        self.confidence
+    }
+}
@@ -1,12 +1,22 @@
 use std::fmt::{self, Display};
 use std::error::Error;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TesseractError{
     TesseractInitError,
     NoSuchFileException,
     TesseracRuntimeError,
-    TesseractTimeoutError
+    TesseractTimeoutError,
+    SaveDocError,
+    InvalidImageError,
+    CacheError,
+    JournalError,
+    ParseError,
+    PdfError,
+    RedactionError
 }
 
 impl Display for TesseractError{
@@ -15,7 +25,14 @@ impl Display for TesseractError{
             TesseractError::TesseractInitError => write!(f, "Tesseract Init Error"),
             TesseractError::NoSuchFileException => write!(f, "No such file"),
             TesseractError::TesseracRuntimeError => write!(f, "Tesseract Runtime Error"),
-            TesseractError::TesseractTimeoutError => write!(f, "Tesseract Runtime Error")
+            TesseractError::TesseractTimeoutError => write!(f, "Tesseract Runtime Error"),
+            TesseractError::SaveDocError => write!(f, "Save Doc Error"),
+            TesseractError::InvalidImageError => write!(f, "Invalid Image Error"),
+            TesseractError::CacheError => write!(f, "Cache Error"),
+            TesseractError::JournalError => write!(f, "Journal Error"),
+            TesseractError::ParseError => write!(f, "Parse Error"),
+            TesseractError::PdfError => write!(f, "Pdf Error"),
+            TesseractError::RedactionError => write!(f, "Redaction Error")
         }
     }
 }
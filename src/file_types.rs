@@ -1,3 +1,8 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OutputFileFormat {
     PDF,
     ALTO,
@@ -5,6 +10,11 @@ pub enum OutputFileFormat {
     TSV,
     TXT,
     OSD,
+    PAGE,
+    #[cfg(feature = "serde")]
+    JSON,
+    LAYOUT,
+    MARKDOWN,
 }
 
 impl OutputFileFormat {
@@ -16,6 +26,38 @@ impl OutputFileFormat {
             OutputFileFormat::TSV => "tsv",
             OutputFileFormat::TXT => "txt",
             OutputFileFormat::OSD => "osd",
+            OutputFileFormat::PAGE => "page",
+            #[cfg(feature = "serde")]
+            OutputFileFormat::JSON => "json",
+            OutputFileFormat::LAYOUT => "layout",
+            OutputFileFormat::MARKDOWN => "md",
         }
     }
-}
\ No newline at end of file
+
+    pub fn from_type(output_type: &str) -> Option<OutputFileFormat> {
+        match output_type {
+            "pdf" => Some(OutputFileFormat::PDF),
+            "alto" => Some(OutputFileFormat::ALTO),
+            "hocr" => Some(OutputFileFormat::HOCR),
+            "tsv" => Some(OutputFileFormat::TSV),
+            "txt" => Some(OutputFileFormat::TXT),
+            "osd" => Some(OutputFileFormat::OSD),
+            "page" => Some(OutputFileFormat::PAGE),
+            #[cfg(feature = "serde")]
+            "json" => Some(OutputFileFormat::JSON),
+            "layout" => Some(OutputFileFormat::LAYOUT),
+            "md" => Some(OutputFileFormat::MARKDOWN),
+            _ => None,
+        }
+    }
+
+    pub fn is_converted(&self) -> bool {
+        // Layout text, Markdown and JSON are converted from the hOCR output, see `convert`.
+        match self {
+            #[cfg(feature = "serde")]
+            OutputFileFormat::JSON => true,
+            OutputFileFormat::LAYOUT | OutputFileFormat::MARKDOWN => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,664 @@
+//! Template-based form extraction.
+//!
+//! A `FormTemplate` describes the fields of a form in pixels of a template page. Every field is a rectangle
+//! relative to an anchor (a word or phrase printed on every copy of the form) or to a corner of the page,
+//! with its own character whitelist, page segmentation mode and validation regex.
+//! With the serde feature templates are loaded from JSON config files, see `FormTemplate::load`.
+//!
+//! `TesseractApi::image_to_form` recognizes only the regions of a template:
+//! 1. Anchors are searched around their template positions (scaled to the scan size).
+//! 2. The template is aligned to the scan by the found anchors - scale and offset of every axis.
+//!    Without found anchors the template is scaled to the scan size.
+//! 3. Every field region is recognized and its text is parsed to the type of the field and validated.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::form::FormTemplate;
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let template = FormTemplate::load("templates/invoice.json").unwrap();
+//!     let form = tesseract_base.image_to_form("scans/invoice.png", &template).await.unwrap();
+//!     for field in form.fields.iter() {
+//!         println!("{}: {:?} ({}, valid - {})", field.name, field.value, field.confidence, field.valid);
+//!     }
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::hocr::parse_hocr;
+use crate::model::{BoundingBox, Page};
+use crate::tess_lib::TesseractApi;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ffi::CString;
+use std::path::Path;
+use std::sync::OnceLock;
+use tesseract_plumbing as pl;
+
+/// Margin around the expected position of an anchor searched on a scan, a fraction of the page size.
+const ANCHOR_MARGIN: f32 = 0.1;
+
+/// Minimal distance of anchors used to estimate the scale of an axis, a fraction of the template size.
+const MIN_ANCHOR_SPREAD: f32 = 0.25;
+
+/// Sparse text - anchors are searched in regions with arbitrary text.
+const ANCHOR_PSM: u32 = 11;
+
+/// Single text line - default page segmentation mode of fields.
+const FIELD_PSM: u32 = 7;
+
+const WHITELIST: &str = "tessedit_char_whitelist";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+/// FormCorner is a corner of the page a field without an anchor is placed from.
+pub enum FormCorner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+/// FieldType is the type of a field value.
+pub enum FieldType {
+    #[default]
+    Text,
+    /// Integer number, spaces are ignored.
+    Integer,
+    /// Decimal number with a point or a comma. If both are used, the last one is the decimal separator,
+    /// a separator used more than once groups thousands.
+    Decimal,
+    /// Date as YYYY-MM-DD or DD.MM.YYYY, the separator is `-`, `.` or `/`.
+    Date,
+}
+
+impl FieldType {
+    pub fn get_whitelist(&self) -> Option<&'static str> {
+        // Characters recognized in fields of the type without their own whitelist.
+        match self {
+            FieldType::Text => None,
+            FieldType::Integer => Some("0123456789-"),
+            FieldType::Decimal => Some("0123456789.,-"),
+            FieldType::Date => Some("0123456789.-/"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FieldValue is a recognized value parsed to the type of its field.
+pub enum FieldValue {
+    Text(String),
+    Integer(i64),
+    Decimal(f64),
+    Date { year: i32, month: u32, day: u32 },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FormAnchor is a text printed on every copy of a form.
+pub struct FormAnchor {
+    pub name: String,
+    /// Words of the anchor, compared case-insensitively without punctuation.
+    pub text: String,
+    /// Position of the anchor on the template, or on the scan for found anchors.
+    pub bbox: BoundingBox,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FormField is a rectangle of a form recognized as one value.
+pub struct FormField {
+    pub name: String,
+    /// Name of the anchor the field is placed from. If it is None, the field is placed from the corner.
+    pub anchor: Option<String>,
+    /// Corner of the page used without an anchor. Default value - top_left
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub corner: FormCorner,
+    /// Offset of the field from the top left corner of the anchor or from the page corner
+    /// in pixels of the template, negative values go left and up.
+    pub left: i32,
+    pub top: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Default value - text
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub field_type: FieldType,
+    /// Recognized characters. If it is None, the whitelist of the field type is used.
+    pub whitelist: Option<String>,
+    /// Page segmentation mode of the field. Default value - 7 (single line)
+    pub psm: Option<u32>,
+    /// Regex the recognized text has to match to be valid.
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FormTemplate describes a form in pixels of a template page.
+pub struct FormTemplate {
+    pub name: String,
+    /// Size of the template page.
+    pub width: u32,
+    pub height: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anchors: Vec<FormAnchor>,
+    pub fields: Vec<FormField>,
+}
+
+impl FormTemplate {
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<FormTemplate, TesseractError> {
+        let template: FormTemplate =
+            serde_json::from_str(json).map_err(|_| TesseractError::ParseError)?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load(path: &str) -> Result<FormTemplate, TesseractError> {
+        // Load a template from a JSON config file.
+        let json =
+            std::fs::read_to_string(path).map_err(|_| TesseractError::NoSuchFileException)?;
+        FormTemplate::from_json(&json)
+    }
+
+    pub fn validate(&self) -> Result<(), TesseractError> {
+        self.compile_patterns().map(|_| ())
+    }
+
+    fn compile_patterns(&self) -> Result<Vec<Option<Regex>>, TesseractError> {
+        // Validate the template and compile the pattern of every field once.
+        // The page has a size, names are unique, field anchors exist and patterns are valid regexes.
+        if self.width == 0 || self.height == 0 {
+            return Err(TesseractError::ParseError);
+        }
+        let mut anchors = HashSet::new();
+        if !self
+            .anchors
+            .iter()
+            .all(|anchor| anchors.insert(anchor.name.as_str()))
+        {
+            return Err(TesseractError::ParseError);
+        }
+        let mut fields = HashSet::new();
+        let mut patterns = Vec::with_capacity(self.fields.len());
+        for field in self.fields.iter() {
+            let is_valid = fields.insert(field.name.as_str())
+                && field
+                    .anchor
+                    .as_deref()
+                    .map_or(true, |anchor| anchors.contains(anchor));
+            let pattern = field.pattern.as_deref().map(Regex::new).transpose();
+            match pattern {
+                Ok(pattern) if is_valid => patterns.push(pattern),
+                _ => return Err(TesseractError::ParseError),
+            }
+        }
+        Ok(patterns)
+    }
+
+    pub fn get_anchor(&self, name: &str) -> Option<&FormAnchor> {
+        self.anchors.iter().find(|anchor| anchor.name == name)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FormAlignment maps template positions to a scan.
+pub struct FormAlignment {
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    /// Size of the scan.
+    pub width: u32,
+    pub height: u32,
+    /// Anchors found on the scan.
+    pub anchors: Vec<FormAnchor>,
+}
+
+impl FormAlignment {
+    pub fn new(
+        template: &FormTemplate,
+        width: u32,
+        height: u32,
+        anchors: Vec<FormAnchor>,
+    ) -> FormAlignment {
+        // Scale and offset of every axis are fitted to the centers of the found anchors.
+        let centers: Vec<((f32, f32), (f32, f32))> = anchors
+            .iter()
+            .filter_map(|found| {
+                let anchor = template.get_anchor(&found.name)?;
+                Some((get_center(&anchor.bbox), get_center(&found.bbox)))
+            })
+            .collect();
+        let (scale_x, offset_x) = fit_axis(
+            centers
+                .iter()
+                .map(|((x, _), (found_x, _))| (*x, *found_x))
+                .collect(),
+            template.width,
+            width,
+        );
+        let (scale_y, offset_y) = fit_axis(
+            centers
+                .iter()
+                .map(|((_, y), (_, found_y))| (*y, *found_y))
+                .collect(),
+            template.height,
+            height,
+        );
+        FormAlignment {
+            scale_x,
+            scale_y,
+            offset_x,
+            offset_y,
+            width,
+            height,
+            anchors,
+        }
+    }
+
+    pub fn get_point(&self, x: f32, y: f32) -> (f32, f32) {
+        // Position of a template point on the scan.
+        (
+            x * self.scale_x + self.offset_x,
+            y * self.scale_y + self.offset_y,
+        )
+    }
+
+    pub fn get_region(&self, template: &FormTemplate, field: &FormField) -> BoundingBox {
+        // Region of a field on the scan. Fields of anchors which were not found are placed
+        // from the aligned template position of the anchor.
+        let found = field
+            .anchor
+            .as_deref()
+            .and_then(|name| self.anchors.iter().find(|anchor| anchor.name == name));
+        let template_anchor = field
+            .anchor
+            .as_deref()
+            .and_then(|name| template.get_anchor(name));
+        let (x, y) = match (found, template_anchor, field.corner) {
+            (Some(found), _, _) => (found.bbox.left as f32, found.bbox.top as f32),
+            (None, Some(anchor), _) => {
+                self.get_point(anchor.bbox.left as f32, anchor.bbox.top as f32)
+            }
+            (None, None, FormCorner::TopLeft) => (0.0, 0.0),
+            (None, None, FormCorner::TopRight) => (self.width as f32, 0.0),
+            (None, None, FormCorner::BottomLeft) => (0.0, self.height as f32),
+            (None, None, FormCorner::BottomRight) => (self.width as f32, self.height as f32),
+        };
+        let left = x + field.left as f32 * self.scale_x;
+        let top = y + field.top as f32 * self.scale_y;
+        self.get_box(
+            left,
+            top,
+            left + field.width as f32 * self.scale_x,
+            top + field.height as f32 * self.scale_y,
+        )
+    }
+
+    fn get_search_region(&self, anchor: &FormAnchor) -> BoundingBox {
+        let (left, top) = self.get_point(anchor.bbox.left as f32, anchor.bbox.top as f32);
+        let (right, bottom) = self.get_point(anchor.bbox.right as f32, anchor.bbox.bottom as f32);
+        let margin_x = self.width as f32 * ANCHOR_MARGIN;
+        let margin_y = self.height as f32 * ANCHOR_MARGIN;
+        self.get_box(
+            left - margin_x,
+            top - margin_y,
+            right + margin_x,
+            bottom + margin_y,
+        )
+    }
+
+    fn get_box(&self, left: f32, top: f32, right: f32, bottom: f32) -> BoundingBox {
+        // Box clipped to the scan.
+        let clip = |value: f32, max: u32| value.round().clamp(0.0, max as f32) as u32;
+        BoundingBox::new(
+            clip(left, self.width),
+            clip(top, self.height),
+            clip(right, self.width),
+            clip(bottom, self.height),
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FieldResult is a recognized field.
+pub struct FieldResult {
+    pub name: String,
+    /// Recognized text without surrounding whitespace.
+    pub text: String,
+    /// Text parsed to the type of the field, None for empty fields and texts of another type.
+    pub value: Option<FieldValue>,
+    /// Mean confidence of the field words, 0-100.
+    pub confidence: i32,
+    /// Recognized region of the scan.
+    pub bbox: BoundingBox,
+    /// The text is parsed and matches the pattern of the field.
+    pub valid: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// FormResult is a recognized form.
+pub struct FormResult {
+    /// Name of the template.
+    pub name: String,
+    pub alignment: FormAlignment,
+    pub fields: Vec<FieldResult>,
+}
+
+impl FormResult {
+    pub fn get_field(&self, name: &str) -> Option<&FieldResult> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.fields.iter().all(|field| field.valid)
+    }
+}
+
+pub fn find_anchor(anchor: &FormAnchor, page: &Page) -> Option<BoundingBox> {
+    // Box of the first run of page words equal to the words of the anchor.
+    let normalize = |text: &str| -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let expected: Vec<String> = anchor
+        .text
+        .split_whitespace()
+        .map(normalize)
+        .filter(|word| !word.is_empty())
+        .collect();
+    let words: Vec<(String, BoundingBox)> = page
+        .get_words()
+        .map(|word| (normalize(&word.text), word.bbox))
+        .filter(|(text, _)| !text.is_empty())
+        .collect();
+    if expected.is_empty() {
+        return None;
+    }
+    words
+        .windows(expected.len())
+        .find(|run| {
+            run.iter()
+                .zip(expected.iter())
+                .all(|((text, _), word)| text == word)
+        })
+        .map(|run| {
+            run.iter()
+                .skip(1)
+                .fold(run[0].1, |bbox, (_, word_bbox)| bbox.union(word_bbox))
+        })
+}
+
+pub fn parse_value(text: &str, field_type: &FieldType) -> Option<FieldValue> {
+    // Parse a recognized text to a field type, None if the text is empty or is not of the type.
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    match field_type {
+        FieldType::Text => Some(FieldValue::Text(text.to_string())),
+        FieldType::Integer => compact.parse().ok().map(FieldValue::Integer),
+        FieldType::Decimal => {
+            // The last of different separators is the decimal one, a repeated separator groups thousands.
+            let point = match (compact.rfind('.'), compact.rfind(',')) {
+                (Some(point), Some(comma)) => Some(point.max(comma)),
+                (Some(point), None) if compact.matches('.').count() == 1 => Some(point),
+                (None, Some(comma)) if compact.matches(',').count() == 1 => Some(comma),
+                _ => None,
+            };
+            let number: String = compact
+                .char_indices()
+                .filter_map(|(index, c)| match c {
+                    '.' | ',' if Some(index) == point => Some('.'),
+                    '.' | ',' => None,
+                    c => Some(c),
+                })
+                .collect();
+            number
+                .parse()
+                .ok()
+                .filter(|value: &f64| value.is_finite())
+                .map(FieldValue::Decimal)
+        }
+        FieldType::Date => parse_date(&compact),
+    }
+}
+
+fn parse_date(text: &str) -> Option<FieldValue> {
+    static ISO_REGEX: OnceLock<Regex> = OnceLock::new();
+    static DAY_FIRST_REGEX: OnceLock<Regex> = OnceLock::new();
+    let iso =
+        ISO_REGEX.get_or_init(|| Regex::new(r"^(\d{4})[-./](\d{1,2})[-./](\d{1,2})$").unwrap());
+    let day_first = DAY_FIRST_REGEX
+        .get_or_init(|| Regex::new(r"^(\d{1,2})[-./](\d{1,2})[-./](\d{4})$").unwrap());
+    let (year, month, day) = match (iso.captures(text), day_first.captures(text)) {
+        (Some(captures), _) => (
+            captures[1].parse().ok()?,
+            captures[2].parse().ok()?,
+            captures[3].parse().ok()?,
+        ),
+        (None, Some(captures)) => (
+            captures[3].parse().ok()?,
+            captures[2].parse().ok()?,
+            captures[1].parse().ok()?,
+        ),
+        (None, None) => return None,
+    };
+    let is_leap = |year: i32| year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap(year) => 29,
+        2 => 28,
+        _ => return None,
+    };
+    match day {
+        1.. if day <= days => Some(FieldValue::Date { year, month, day }),
+        _ => None,
+    }
+}
+
+fn get_center(bbox: &BoundingBox) -> (f32, f32) {
+    (
+        (bbox.left + bbox.right) as f32 / 2.0,
+        (bbox.top + bbox.bottom) as f32 / 2.0,
+    )
+}
+
+fn fit_axis(points: Vec<(f32, f32)>, template_size: u32, size: u32) -> (f32, f32) {
+    // Least squares scale and offset of template positions to scan positions.
+    // The scale is the ratio of the page sizes unless the anchors are far enough from each other.
+    let ratio = size as f32 / template_size as f32;
+    if points.is_empty() {
+        return (ratio, 0.0);
+    }
+    let count = points.len() as f32;
+    let mean = points.iter().fold((0.0, 0.0), |(x, y), (template, scan)| {
+        (x + template / count, y + scan / count)
+    });
+    let (min, max) = points
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), (template, _)| {
+            (min.min(*template), max.max(*template))
+        });
+    let variance: f32 = points
+        .iter()
+        .map(|(template, _)| (template - mean.0).powi(2))
+        .sum();
+    let covariance: f32 = points
+        .iter()
+        .map(|(template, scan)| (template - mean.0) * (scan - mean.1))
+        .sum();
+    let scale = match covariance / variance {
+        scale if max - min >= template_size as f32 * MIN_ANCHOR_SPREAD && scale > 0.0 => scale,
+        _ => ratio,
+    };
+    (scale, mean.1 - mean.0 * scale)
+}
+
+fn set_whitelist(tesseract: &mut TesseractApi, whitelist: &str) -> Result<(), TesseractError> {
+    // The whitelist is set on the base api, so the variables of the api are not changed.
+    let name = CString::new(WHITELIST).unwrap();
+    let value = CString::new(whitelist).map_err(|_| TesseractError::ParseError)?;
+    tesseract
+        .tess_pl
+        .set_variable(&name, &value)
+        .map_err(|_| TesseractError::TesseracRuntimeError)
+}
+
+fn recognize_region(
+    tesseract: &mut TesseractApi,
+    region: &BoundingBox,
+    output_type: &OutputFileFormat,
+) -> Result<String, TesseractError> {
+    tesseract.tess_pl.set_rectangle(
+        region.left as i32,
+        region.top as i32,
+        region.get_width() as i32,
+        region.get_height() as i32,
+    );
+    tesseract.get_output(output_type)
+}
+
+fn recognize_field(
+    tesseract: &mut TesseractApi,
+    template: &FormTemplate,
+    alignment: &FormAlignment,
+    field: &FormField,
+    pattern: Option<&Regex>,
+    whitelist: &str,
+) -> Result<FieldResult, TesseractError> {
+    let bbox = alignment.get_region(template, field);
+    let mut result = FieldResult {
+        name: field.name.clone(),
+        bbox,
+        ..Default::default()
+    };
+    if bbox.get_width() == 0 || bbox.get_height() == 0 {
+        // The field is outside of the scan.
+        return Ok(result);
+    }
+    let field_whitelist = field
+        .whitelist
+        .as_deref()
+        .or(field.field_type.get_whitelist())
+        .unwrap_or(whitelist);
+    set_whitelist(tesseract, field_whitelist)?;
+    tesseract
+        .tess_pl
+        .set_page_seg_mode(field.psm.unwrap_or(FIELD_PSM));
+    result.text = recognize_region(tesseract, &bbox, &OutputFileFormat::TXT)?
+        .trim()
+        .to_string();
+    result.confidence = tesseract.tess_pl.mean_text_conf();
+    result.value = parse_value(&result.text, &field.field_type);
+    result.valid =
+        result.value.is_some() && pattern.map_or(true, |pattern| pattern.is_match(&result.text));
+    Ok(result)
+}
+
+fn recognize_fields(
+    tesseract: &mut TesseractApi,
+    template: &FormTemplate,
+    patterns: &[Option<Regex>],
+    width: u32,
+    height: u32,
+    whitelist: &str,
+) -> Result<FormResult, TesseractError> {
+    let expected = FormAlignment::new(template, width, height, Vec::new());
+    let mut anchors = Vec::new();
+    set_whitelist(tesseract, whitelist)?;
+    tesseract.tess_pl.set_page_seg_mode(ANCHOR_PSM);
+    for anchor in template.anchors.iter() {
+        // Boxes of the hOCR words of a rectangle are in pixels of the whole image.
+        let region = expected.get_search_region(anchor);
+        let hocr = recognize_region(tesseract, &region, &OutputFileFormat::HOCR)?;
+        let found = parse_hocr(&hocr)?
+            .iter()
+            .find_map(|page| find_anchor(anchor, page));
+        if let Some(bbox) = found {
+            anchors.push(FormAnchor {
+                bbox,
+                ..anchor.clone()
+            });
+        }
+    }
+    let alignment = FormAlignment::new(template, width, height, anchors);
+    let fields = template
+        .fields
+        .iter()
+        .zip(patterns.iter())
+        .map(|(field, pattern)| {
+            recognize_field(
+                tesseract,
+                template,
+                &alignment,
+                field,
+                pattern.as_ref(),
+                whitelist,
+            )
+        })
+        .collect::<Result<Vec<FieldResult>, TesseractError>>()?;
+    Ok(FormResult {
+        name: template.name.clone(),
+        alignment,
+        fields,
+    })
+}
+
+pub fn recognize_form(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    template: &FormTemplate,
+) -> Result<FormResult, TesseractError> {
+    // Recognize the fields of a template on an image file.
+    let patterns = template.compile_patterns()?;
+    if !Path::new(filename).exists() {
+        return Err(TesseractError::NoSuchFileException);
+    }
+    let filename = CString::new(filename).map_err(|_| TesseractError::NoSuchFileException)?;
+    match pl::leptonica_plumbing::Pix::read(&filename) {
+        Ok(pix) => tesseract.tess_pl.set_image_2(&pix),
+        Err(_) => return Err(TesseractError::InvalidImageError),
+    };
+    tesseract.tess_pl.set_source_resolution(tesseract.dpi);
+    let (width, height) = match tesseract.tess_pl.get_input_image() {
+        Some(pix) => (pix.get_width() as u32, pix.get_height() as u32),
+        None => return Err(TesseractError::InvalidImageError),
+    };
+    let whitelist = tesseract
+        .variables
+        .iter()
+        .find(|(name, _)| name == WHITELIST)
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default();
+    let form = recognize_fields(tesseract, template, &patterns, width, height, &whitelist);
+
+    // The whole image, the page segmentation mode and the whitelist of the api are restored, also after errors.
+    tesseract
+        .tess_pl
+        .set_rectangle(0, 0, width as i32, height as i32);
+    tesseract.tess_pl.set_page_seg_mode(tesseract.psm);
+    set_whitelist(tesseract, &whitelist)?;
+    form
+}
@@ -0,0 +1,381 @@
+//! hOCR parser.
+//!
+//! `parse_hocr` turns hOCR (a complete document or page fragments returned by `image_to_hocr`)
+//! into the `model` types. Supported elements: `ocr_page`, `ocr_carea`, `ocr_par`, `ocr_line`
+//! (and the `ocr_caption`, `ocr_header`, `ocr_textfloat` line types), `ocrx_word`, `ocrx_cinfo`
+//! (symbols of the hocr_char_boxes variable and their alternatives with lstm_choice_mode 2).
+//! Supported properties: `bbox`, `x_wconf`, `baseline`, `x_size`, `image`, `ppageno`, `x_bboxes`,
+//! `x_conf`, `x_confs`.
+//! Missing levels (e.g. lines without paragraphs in older files) are replaced by implicit elements,
+//! malformed markup or properties are reported as `ParseError`.
+//! `write_hocr` writes pages back as one hOCR document in the format produced by Tesseract.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::hocr::parse_hocr;
+//!
+//! let hocr = std::fs::read_to_string("archive/page.hocr").unwrap();
+//! for page in parse_hocr(&hocr).unwrap() {
+//!     println!("{}", page.get_text());
+//! }
+//! ```
+
+use crate::assembly::assemble_hocr;
+use crate::errors::TesseractError;
+use crate::model::{get_union, Block, BoundingBox, Choice, Line, Page, Paragraph, Symbol, Word};
+use crate::xml::{escape_xml, parse_xml, Element};
+
+const LINE_CLASSES: [&str; 4] = ["ocr_line", "ocr_caption", "ocr_header", "ocr_textfloat"];
+
+pub fn parse_hocr(hocr: &str) -> Result<Vec<Page>, TesseractError> {
+    let root = parse_xml(hocr)?;
+    let page_elements = get_elements(&root, &["ocr_page"]);
+    if page_elements.is_empty() {
+        return Err(TesseractError::ParseError);
+    }
+    page_elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| parse_page(element, index))
+        .collect()
+}
+
+pub fn write_hocr(pages: &[Page]) -> String {
+    // Pages are written as fragments returned by `image_to_hocr` and assembled into one document.
+    let fragments: Vec<String> = pages
+        .iter()
+        .enumerate()
+        .map(|(index, page)| write_page(page, index + 1))
+        .collect();
+    assemble_hocr(&fragments)
+}
+
+fn write_bbox(bbox: &BoundingBox) -> String {
+    format!(
+        "bbox {} {} {} {}",
+        bbox.left, bbox.top, bbox.right, bbox.bottom
+    )
+}
+
+fn write_page(page: &Page, page_id: usize) -> String {
+    // Pages of a written document are numbered consecutively.
+    let title = escape_xml(&format!(
+        "image \"{}\"; {}; ppageno {}",
+        page.image.as_deref().unwrap_or_default(),
+        write_bbox(&page.bbox),
+        page_id - 1
+    ));
+    let mut hocr = format!(
+        "  <div class='ocr_page' id='page_{}' title='{}'>\n",
+        page_id, title
+    );
+    let (mut par_id, mut line_id, mut word_id) = (0, 0, 0);
+    for (block_index, block) in page.blocks.iter().enumerate() {
+        hocr.push_str(&format!(
+            "   <div class='ocr_carea' id='block_{}_{}' title=\"{}\">\n",
+            page_id,
+            block_index + 1,
+            write_bbox(&block.bbox)
+        ));
+        for paragraph in block.paragraphs.iter() {
+            par_id += 1;
+            hocr.push_str(&format!(
+                "    <p class='ocr_par' id='par_{}_{}' title=\"{}\">\n",
+                page_id,
+                par_id,
+                write_bbox(&paragraph.bbox)
+            ));
+            for line in paragraph.lines.iter() {
+                line_id += 1;
+                let mut title = write_bbox(&line.bbox);
+                if let Some((slope, offset)) = line.baseline {
+                    title.push_str(&format!("; baseline {} {}", slope, offset));
+                }
+                if let Some(x_size) = line.x_size {
+                    title.push_str(&format!("; x_size {}", x_size));
+                }
+                hocr.push_str(&format!(
+                    "     <span class='ocr_line' id='line_{}_{}' title=\"{}\">\n",
+                    page_id, line_id, title
+                ));
+                for word in line.words.iter() {
+                    word_id += 1;
+                    let mut title = write_bbox(&word.bbox);
+                    if let Some(confidence) = word.confidence {
+                        title.push_str(&format!("; x_wconf {}", confidence));
+                    }
+                    hocr.push_str(&format!(
+                        "      <span class='ocrx_word' id='word_{}_{}' title='{}'>{}</span>\n",
+                        page_id,
+                        word_id,
+                        title,
+                        escape_xml(&word.text)
+                    ));
+                }
+                hocr.push_str("     </span>\n");
+            }
+            hocr.push_str("    </p>\n");
+        }
+        hocr.push_str("   </div>\n");
+    }
+    hocr.push_str("  </div>\n");
+    hocr
+}
+
+fn get_elements<'a>(element: &'a Element, classes: &[&str]) -> Vec<&'a Element> {
+    element.find_elements(&|child: &Element| classes.iter().any(|class| child.has_class(class)))
+}
+
+fn get_properties(element: &Element) -> Vec<(&str, &str)> {
+    // Properties of the title attribute, e.g. bbox 0 0 10 10; x_wconf 95
+    element
+        .get_attribute("title")
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|property| {
+            let property = property.trim();
+            match property.split_once(char::is_whitespace) {
+                Some((name, value)) => Some((name, value.trim())),
+                None if !property.is_empty() => Some((property, "")),
+                None => None,
+            }
+        })
+        .collect()
+}
+
+fn get_property<'a>(properties: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    properties
+        .iter()
+        .find(|(property, _)| *property == name)
+        .map(|(_, value)| *value)
+}
+
+fn parse_numbers<T: std::str::FromStr>(
+    value: &str,
+    count: usize,
+) -> Result<Vec<T>, TesseractError> {
+    let numbers = value
+        .split_whitespace()
+        .map(|number| number.parse::<T>().map_err(|_| TesseractError::ParseError))
+        .collect::<Result<Vec<T>, TesseractError>>()?;
+    if numbers.len() != count {
+        return Err(TesseractError::ParseError);
+    }
+    Ok(numbers)
+}
+
+fn parse_bbox(properties: &[(&str, &str)]) -> Result<Option<BoundingBox>, TesseractError> {
+    match get_property(properties, "bbox") {
+        Some(value) => {
+            let numbers = parse_numbers::<u32>(value, 4)?;
+            Ok(Some(BoundingBox::new(
+                numbers[0], numbers[1], numbers[2], numbers[3],
+            )))
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_float(properties: &[(&str, &str)], name: &str) -> Result<Option<f32>, TesseractError> {
+    match get_property(properties, name) {
+        Some(value) => Ok(Some(parse_numbers::<f32>(value, 1)?[0])),
+        None => Ok(None),
+    }
+}
+
+fn parse_page(element: &Element, index: usize) -> Result<Page, TesseractError> {
+    let properties = get_properties(element);
+    let mut blocks = get_elements(element, &["ocr_carea"])
+        .into_iter()
+        .map(parse_block)
+        .collect::<Result<Vec<Block>, TesseractError>>()?;
+    if blocks.is_empty() {
+        let block = parse_block(element)?;
+        if !block.paragraphs.is_empty() {
+            blocks.push(Block {
+                bbox: get_union(block.paragraphs.iter().map(|paragraph| &paragraph.bbox)),
+                ..block
+            });
+        }
+    }
+    let page_number = match get_property(&properties, "ppageno") {
+        Some(value) => parse_numbers::<usize>(value, 1)?[0],
+        None => index,
+    };
+    let image = get_property(&properties, "image")
+        .map(|image| image.trim_matches('"').to_string())
+        .filter(|image| !image.is_empty());
+    Ok(Page {
+        bbox: match parse_bbox(&properties)? {
+            Some(bbox) => bbox,
+            None => get_union(blocks.iter().map(|block| &block.bbox)),
+        },
+        page_number,
+        image,
+        blocks,
+        elapsed_ms: None,
+    })
+}
+
+fn parse_block(element: &Element) -> Result<Block, TesseractError> {
+    let mut paragraphs = get_elements(element, &["ocr_par"])
+        .into_iter()
+        .map(parse_paragraph)
+        .collect::<Result<Vec<Paragraph>, TesseractError>>()?;
+    if paragraphs.is_empty() {
+        let paragraph = parse_paragraph(element)?;
+        if !paragraph.lines.is_empty() {
+            paragraphs.push(Paragraph {
+                bbox: get_union(paragraph.lines.iter().map(|line| &line.bbox)),
+                ..paragraph
+            });
+        }
+    }
+    Ok(Block {
+        bbox: match parse_bbox(&get_properties(element))? {
+            Some(bbox) => bbox,
+            None => get_union(paragraphs.iter().map(|paragraph| &paragraph.bbox)),
+        },
+        paragraphs,
+        order: None,
+    })
+}
+
+fn parse_paragraph(element: &Element) -> Result<Paragraph, TesseractError> {
+    let mut lines = get_elements(element, &LINE_CLASSES)
+        .into_iter()
+        .map(parse_line)
+        .collect::<Result<Vec<Line>, TesseractError>>()?;
+    if lines.is_empty() {
+        let words = get_elements(element, &["ocrx_word"])
+            .into_iter()
+            .map(parse_word)
+            .collect::<Result<Vec<Word>, TesseractError>>()?;
+        if !words.is_empty() {
+            lines.push(Line {
+                bbox: get_union(words.iter().map(|word| &word.bbox)),
+                words,
+                ..Default::default()
+            });
+        }
+    }
+    Ok(Paragraph {
+        bbox: match parse_bbox(&get_properties(element))? {
+            Some(bbox) => bbox,
+            None => get_union(lines.iter().map(|line| &line.bbox)),
+        },
+        lines,
+    })
+}
+
+fn parse_line(element: &Element) -> Result<Line, TesseractError> {
+    let properties = get_properties(element);
+    let words = get_elements(element, &["ocrx_word"])
+        .into_iter()
+        .map(parse_word)
+        .collect::<Result<Vec<Word>, TesseractError>>()?;
+    let baseline = match get_property(&properties, "baseline") {
+        Some(value) => {
+            let numbers = parse_numbers::<f32>(value, 2)?;
+            Some((numbers[0], numbers[1]))
+        }
+        None => None,
+    };
+    Ok(Line {
+        bbox: match parse_bbox(&properties)? {
+            Some(bbox) => bbox,
+            None => get_union(words.iter().map(|word| &word.bbox)),
+        },
+        baseline,
+        x_size: parse_float(&properties, "x_size")?,
+        words,
+    })
+}
+
+fn parse_word(element: &Element) -> Result<Word, TesseractError> {
+    // A word without a bounding box can not be placed on the page.
+    let properties = get_properties(element);
+    let symbols = parse_symbols(element)?;
+    // Alternatives of the symbols are inside the word, so the text is joined from the symbols.
+    let text = match symbols.is_empty() {
+        true => element.get_text().trim().to_string(),
+        false => symbols.iter().map(|symbol| symbol.text.as_str()).collect(),
+    };
+    Ok(Word {
+        text,
+        bbox: parse_bbox(&properties)?.ok_or(TesseractError::ParseError)?,
+        confidence: parse_float(&properties, "x_wconf")?,
+        symbols,
+    })
+}
+
+fn parse_symbols(element: &Element) -> Result<Vec<Symbol>, TesseractError> {
+    // Symbols of hocr_char_boxes, e.g. <span class='ocrx_cinfo' title='x_bboxes 0 0 10 10; x_conf 95'>a</span>.
+    // With lstm_choice_mode 2 a symbol is followed by a span with the lstm_choices id holding its alternatives,
+    // with lstm_choice_mode 1 by an ocr_symbol span holding the alternatives of every timestep.
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for child in get_elements(element, &["ocrx_cinfo", "ocr_symbol"]) {
+        let id = child.get_attribute("id").unwrap_or_default();
+        if id.starts_with("lstm_choices") || child.has_class("ocr_symbol") {
+            let choices = match child.has_class("ocr_symbol") {
+                true => get_elements(child, &["ocrx_cinfo"])
+                    .into_iter()
+                    .flat_map(|timestep| get_elements(timestep, &["ocrx_cinfo"]))
+                    .collect(),
+                false => get_elements(child, &["ocrx_cinfo"]),
+            };
+            if let Some(symbol) = symbols.last_mut() {
+                symbol.choices = merge_choices(
+                    choices
+                        .into_iter()
+                        .map(parse_choice)
+                        .collect::<Result<Vec<Choice>, TesseractError>>()?,
+                );
+            }
+        } else if child.has_class("ocrx_cinfo") {
+            let properties = get_properties(child);
+            let bbox = match get_property(&properties, "x_bboxes") {
+                Some(value) => {
+                    let numbers = parse_numbers::<u32>(value, 4)?;
+                    Some(BoundingBox::new(
+                        numbers[0], numbers[1], numbers[2], numbers[3],
+                    ))
+                }
+                None => None,
+            };
+            symbols.push(Symbol {
+                text: child.get_text(),
+                bbox,
+                confidence: parse_float(&properties, "x_conf")?,
+                choices: Vec::new(),
+            });
+        }
+    }
+    Ok(symbols)
+}
+
+fn merge_choices(choices: Vec<Choice>) -> Vec<Choice> {
+    // Alternatives ordered by confidence, an alternative of several timesteps keeps its best confidence.
+    // Empty alternatives (blanks of timesteps) are dropped.
+    let mut merged: Vec<Choice> = Vec::new();
+    for choice in choices {
+        if choice.text.trim().is_empty() {
+            continue;
+        }
+        match merged.iter_mut().find(|merged| merged.text == choice.text) {
+            Some(merged) => merged.confidence = merged.confidence.max(choice.confidence),
+            None => merged.push(choice),
+        }
+    }
+    merged.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    merged
+}
+
+fn parse_choice(element: &Element) -> Result<Choice, TesseractError> {
+    let properties = get_properties(element);
+    Ok(Choice {
+        text: element.get_text(),
+        confidence: parse_float(&properties, "x_confs")?.ok_or(TesseractError::ParseError)?,
+    })
+}
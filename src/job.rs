@@ -0,0 +1,347 @@
+//! Checkpoint and resume of batch jobs.
+//!
+//! A `JobJournal` records every recognized page of a `recognize_doc` run in a local journal file
+//! `{dir}/{job_id}.journal`. A rerun with the same job id reuses the recorded pages and recognizes
+//! only the pages which were not finished, failed pages are retried.
+//!
+//! The journal is a text file with one tab separated record per line:
+//! 1. `job <format> <total>` - written when a run starts.
+//! 2. `page <index> <image> <output>` - a recognized page, tabs and new lines of the output are escaped.
+//! 3. `error <index> <image> <error>` - a failed page.
+//! 4. `done` - written when a run finishes.
+//!
+//! Every record is flushed to disk before the next page is recognized, so the status of a running job
+//! can be read with `JobJournal::read_status` or with `get_status_handle` from another task.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::job::JobJournal;
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let journal = JobJournal::open("jobs", "scans-2024").unwrap();
+//!     let mut tesseract_base = TesseractApi::new(Some(TesseractApi{journal: Some(journal), ..Default::default()}), Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let image_array = vec!["/workspaces/rusttesserast/tests/test_img.png", "/workspaces/rusttesserast/tests/test_img.png"];
+//!     let test = tesseract_base.recognize_doc(None, None, image_array, "txt", None).await;
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// JobStatus describes the progress of a job.
+pub struct JobStatus {
+    pub job_id: String,
+    /// Number of pages of the last run.
+    pub total: usize,
+    /// Number of recognized pages, including pages recognized by previous runs.
+    pub completed: usize,
+    /// Number of failed pages.
+    pub failed: usize,
+    /// Number of pages reused from previous runs by the current run.
+    pub resumed: usize,
+    /// The last run recognized all pages.
+    pub finished: bool,
+}
+
+#[derive(Debug)]
+/// JobJournal records recognized pages of a job.
+pub struct JobJournal {
+    job_id: String,
+    path: PathBuf,
+    format: Option<String>,
+    pages: HashMap<(usize, String), String>,
+    failed: HashSet<(usize, String)>,
+    file: File,
+    status: Arc<Mutex<JobStatus>>,
+}
+
+impl JobJournal {
+    pub fn open(dir: &str, job_id: &str) -> Result<JobJournal, TesseractError> {
+        // Open the journal of a job or create a new one.
+        // dir - directory of journals, it is created if it does not exist.
+        // job_id - name of the job, only ASCII letters, digits, '-', '_' and '.' are allowed.
+        let is_valid = !job_id.is_empty()
+            && !job_id.starts_with('.')
+            && job_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+        if !is_valid {
+            return Err(TesseractError::JournalError);
+        }
+        fs::create_dir_all(dir).map_err(|_| TesseractError::JournalError)?;
+        let path = get_journal_path(dir, job_id);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(_) => return Err(TesseractError::JournalError),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|_| TesseractError::JournalError)?;
+        // A record interrupted by a crash is dropped, so the next record starts on a new line.
+        let content = match content.rfind('\n') {
+            Some(end) if end + 1 < content.len() => {
+                file.set_len(end as u64 + 1)
+                    .map_err(|_| TesseractError::JournalError)?;
+                &content[..=end]
+            }
+            Some(_) => content.as_str(),
+            None => {
+                file.set_len(0).map_err(|_| TesseractError::JournalError)?;
+                ""
+            }
+        };
+
+        let records = parse_journal(content);
+        let status = get_status(job_id, &records);
+        let mut journal = JobJournal {
+            job_id: job_id.to_string(),
+            path,
+            format: None,
+            pages: HashMap::new(),
+            failed: HashSet::new(),
+            file,
+            status: Arc::new(Mutex::new(status)),
+        };
+        for record in records {
+            match record {
+                Record::Job { format, .. } => journal.format = Some(format),
+                Record::Page {
+                    index,
+                    image,
+                    output,
+                } => {
+                    journal.failed.remove(&(index, image.clone()));
+                    journal.pages.insert((index, image), output);
+                }
+                Record::Error { index, image } => {
+                    if !journal.pages.contains_key(&(index, image.clone())) {
+                        journal.failed.insert((index, image));
+                    }
+                }
+                Record::Done => {}
+            }
+        }
+        Ok(journal)
+    }
+
+    pub fn read_status(dir: &str, job_id: &str) -> Result<JobStatus, TesseractError> {
+        // Read the status of a job from its journal, e.g. from another process while the job is running.
+        let content = fs::read_to_string(get_journal_path(dir, job_id))
+            .map_err(|_| TesseractError::JournalError)?;
+        Ok(get_status(job_id, &parse_journal(&content)))
+    }
+
+    pub fn get_job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_status(&self) -> JobStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn get_status_handle(&self) -> Arc<Mutex<JobStatus>> {
+        // Shared status updated after every page, it can be polled by another task.
+        self.status.clone()
+    }
+
+    pub(crate) fn start(
+        &mut self,
+        image_array: &[&str],
+        output_type: &OutputFileFormat,
+    ) -> Result<(), TesseractError> {
+        // Start a run. A job can not be resumed with another output format.
+        let format = output_type.get_type();
+        if self.format.as_deref().is_some_and(|old| old != format) {
+            return Err(TesseractError::JournalError);
+        }
+        self.format = Some(format.to_string());
+        self.write_record(&format!("job\t{}\t{}", format, image_array.len()))?;
+        let mut status = self.status.lock().unwrap();
+        status.total = image_array.len();
+        status.completed = self.pages.len();
+        status.failed = self.failed.len();
+        status.resumed = 0;
+        status.finished = false;
+        Ok(())
+    }
+
+    pub(crate) fn get_page(&mut self, index: usize, image: &str) -> Option<String> {
+        // Return a page recognized by a previous run.
+        let page = self.pages.get(&(index, image.to_string())).cloned();
+        if page.is_some() {
+            self.status.lock().unwrap().resumed += 1;
+        }
+        page
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        index: usize,
+        image: &str,
+        page: &Result<String, TesseractError>,
+    ) -> Result<(), TesseractError> {
+        let key = (index, image.to_string());
+        match page {
+            Ok(output) => {
+                self.write_record(&format!(
+                    "page\t{}\t{}\t{}",
+                    index,
+                    escape(image),
+                    escape(output)
+                ))?;
+                self.failed.remove(&key);
+                self.pages.insert(key, output.clone());
+            }
+            Err(error) => {
+                self.write_record(&format!(
+                    "error\t{}\t{}\t{}",
+                    index,
+                    escape(image),
+                    escape(&error.to_string())
+                ))?;
+                self.failed.insert(key);
+            }
+        }
+        let mut status = self.status.lock().unwrap();
+        status.completed = self.pages.len();
+        status.failed = self.failed.len();
+        Ok(())
+    }
+
+    pub(crate) fn finish(&mut self) -> Result<(), TesseractError> {
+        self.write_record("done")?;
+        self.status.lock().unwrap().finished = true;
+        Ok(())
+    }
+
+    fn write_record(&mut self, record: &str) -> Result<(), TesseractError> {
+        self.file
+            .write_all(format!("{}\n", record).as_bytes())
+            .and_then(|_| self.file.sync_data())
+            .map_err(|_| TesseractError::JournalError)
+    }
+}
+
+enum Record {
+    Job {
+        format: String,
+        total: usize,
+    },
+    Page {
+        index: usize,
+        image: String,
+        output: String,
+    },
+    Error {
+        index: usize,
+        image: String,
+    },
+    Done,
+}
+
+fn get_journal_path(dir: &str, job_id: &str) -> PathBuf {
+    Path::new(dir).join(format!("{}.journal", job_id))
+}
+
+fn parse_journal(content: &str) -> Vec<Record> {
+    // Malformed lines are skipped.
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["job", format, total] => Some(Record::Job {
+                    format: format.to_string(),
+                    total: total.parse().ok()?,
+                }),
+                ["page", index, image, output] => Some(Record::Page {
+                    index: index.parse().ok()?,
+                    image: unescape(image),
+                    output: unescape(output),
+                }),
+                ["error", index, image, _] => Some(Record::Error {
+                    index: index.parse().ok()?,
+                    image: unescape(image),
+                }),
+                ["done"] => Some(Record::Done),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn get_status(job_id: &str, records: &[Record]) -> JobStatus {
+    let mut status = JobStatus {
+        job_id: job_id.to_string(),
+        ..Default::default()
+    };
+    let mut completed = HashSet::new();
+    let mut failed = HashSet::new();
+    for record in records {
+        match record {
+            Record::Job { total, .. } => {
+                status.total = *total;
+                status.finished = false;
+            }
+            Record::Page { index, image, .. } => {
+                failed.remove(&(index, image));
+                completed.insert((index, image));
+            }
+            Record::Error { index, image } => {
+                if !completed.contains(&(index, image)) {
+                    failed.insert((index, image));
+                }
+            }
+            Record::Done => status.finished = true,
+        }
+    }
+    status.completed = completed.len();
+    status.failed = failed.len();
+    status
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
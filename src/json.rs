@@ -0,0 +1,53 @@
+//! JSON output.
+//!
+//! `OutputFileFormat::JSON` is a JSON array of `model::Page` with the serde representation of the model:
+//! every page contains its bounding box, page number, source image and the blocks, paragraphs, lines
+//! and words with their boxes and confidences. Recognized pages also contain `elapsed_ms`, the time
+//! of the recognition. `TesseractApi` returns a single page as an array with one page, `assemble_json`
+//! merges such arrays into one document with consecutive page numbers.
+//!
+//! The format is available only with the serde feature, without it `OutputFileFormat::JSON` does not exist.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::json::parse_json;
+//!
+//! let json = std::fs::read_to_string("archive/doc.json").unwrap();
+//! for page in parse_json(&json).unwrap() {
+//!     println!("{}", page.get_text());
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::model::Page;
+
+pub fn parse_json(json: &str) -> Result<Vec<Page>, TesseractError> {
+    // A document is an array of pages, a single page object is read as a document with one page.
+    let json = json.trim_start();
+    if json.starts_with('{') {
+        return serde_json::from_str::<Page>(json)
+            .map(|page| vec![page])
+            .map_err(|_| TesseractError::ParseError);
+    }
+    serde_json::from_str(json).map_err(|_| TesseractError::ParseError)
+}
+
+pub fn write_json(pages: &[Page]) -> String {
+    serde_json::to_string_pretty(pages).unwrap_or_default()
+}
+
+pub fn assemble_json(doc_vec: &[String]) -> String {
+    // Merge recognized pages into one array, pages which are not valid JSON are skipped.
+    let pages: Vec<Page> = doc_vec
+        .iter()
+        .filter_map(|doc| parse_json(doc).ok())
+        .flatten()
+        .enumerate()
+        .map(|(index, page)| Page {
+            page_number: index,
+            ..page
+        })
+        .collect();
+    write_json(&pages)
+}
@@ -0,0 +1,125 @@
+//! Layout-preserving plain text.
+//!
+//! `write_layout_text` places the words of a page on a character grid approximating their positions:
+//! a column is the median width of a character and a row is the median height of a line, so columns,
+//! indentation and tables stay aligned in a monospaced font. Lines of different columns at the same
+//! height share a row, words which would overlap are moved right behind the previous word.
+//! Empty margins are removed and runs of empty rows are reduced to one.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::hocr::parse_hocr;
+//! use rusttesserast::layout::write_layout_text;
+//!
+//! let hocr = std::fs::read_to_string("archive/page.hocr").unwrap();
+//! println!("{}", write_layout_text(&parse_hocr(&hocr).unwrap()));
+//! ```
+
+use crate::model::{Line, Page};
+use std::collections::BTreeMap;
+
+pub fn write_layout_text(pages: &[Page]) -> String {
+    // Pages are separated with a form feed, like the text output of Tesseract.
+    pages
+        .iter()
+        .map(write_page)
+        .collect::<Vec<String>>()
+        .join("\n\u{c}\n")
+}
+
+pub(crate) fn get_median(mut values: Vec<f32>) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f32::total_cmp);
+    Some(values[values.len() / 2])
+}
+
+pub(crate) fn get_char_width(page: &Page) -> f32 {
+    // Median width of a character of the page words, 1 pixel for pages without words.
+    let widths = page
+        .get_words()
+        .filter_map(|word| match word.text.chars().count() {
+            0 => None,
+            count => Some(word.bbox.get_width() as f32 / count as f32),
+        })
+        .collect();
+    get_median(widths).unwrap_or(1.0).max(1.0)
+}
+
+pub(crate) fn get_line_height(page: &Page) -> f32 {
+    // Median height of the page lines, 1 pixel for pages without lines.
+    let heights = page
+        .get_lines()
+        .filter(|line| !line.words.is_empty())
+        .map(|line| line.bbox.get_height() as f32)
+        .collect();
+    get_median(heights).unwrap_or(1.0).max(1.0)
+}
+
+fn write_page(page: &Page) -> String {
+    let mut lines: Vec<&Line> = page
+        .get_lines()
+        .filter(|line| line.words.iter().any(|word| !word.text.trim().is_empty()))
+        .collect();
+    let left = match lines.iter().map(|line| line.bbox.left).min() {
+        Some(left) => left as f32,
+        None => return String::new(),
+    };
+    let char_width = get_char_width(page);
+    let line_height = get_line_height(page);
+
+    // Lines are grouped into rows by their middles, lines of other columns at the same height
+    // share a row. The next row is placed by the distance of the middles in whole line heights.
+    let middle = |line: &Line| (line.bbox.top + line.bbox.bottom) as f32 / 2.0;
+    lines.sort_by(|a, b| middle(a).total_cmp(&middle(b)));
+    let mut rows: BTreeMap<usize, Vec<(usize, &str)>> = BTreeMap::new();
+    let mut current: Option<(usize, &Line)> = None;
+    for line in lines {
+        let row = match current {
+            Some((row, first)) if middle(line) < first.bbox.bottom as f32 => row,
+            Some((row, first)) => {
+                let distance = (middle(line) - middle(first)) / line_height;
+                row + (distance as usize).max(1)
+            }
+            None => 0,
+        };
+        if current.map_or(true, |(current_row, _)| current_row != row) {
+            current = Some((row, line));
+        }
+        let words = line
+            .words
+            .iter()
+            .filter(|word| !word.text.trim().is_empty())
+            .map(|word| {
+                let column = ((word.bbox.left as f32 - left) / char_width).round() as usize;
+                (column, word.text.trim())
+            });
+        rows.entry(row).or_default().extend(words);
+    }
+
+    let mut text = String::new();
+    let mut previous_row = None;
+    for (row, mut words) in rows {
+        if let Some(previous_row) = previous_row {
+            text.push('\n');
+            if row > previous_row + 1 {
+                text.push('\n');
+            }
+        }
+        previous_row = Some(row);
+        words.sort_by_key(|(column, _)| *column);
+        let mut length = 0;
+        for (column, word) in words {
+            let column = match length {
+                0 => column,
+                _ => column.max(length + 1),
+            };
+            text.extend(std::iter::repeat(' ').take(column - length));
+            text.push_str(word);
+            length = column + word.chars().count();
+        }
+    }
+    text
+}
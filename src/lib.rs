@@ -4,8 +4,9 @@
 //! 1. save_path: Option<&str> - path for saving a doc. If path is None, then file will be saved to a project dir.
 //! 2. doc_name: Option<&str> - name of a doc where recognized information will be saved. Default value - data.txt
 //! 3. image_array: Vec<&str> - vector which contains a paths to available images.
-//! 4. output_type: &str - a str which contains output type value. So far, only 4 types available (txt, tsv, hocr)
-//! 5. save_doc: Option<bool> - bool flag to save document.
+//! 4. output_type: &str - a str which contains output type value. So far, only 7 types available (txt, tsv, hocr, alto, layout, md, json - with the serde feature)
+//! 5. save_doc: Option<bool> - bool flag to save document. hOCR and ALTO pages are saved as one well-formed document,
+//!    TSV pages get a single header and consecutive page numbers.
 //! # Examples
 //!
 //! ```rust, no_run
@@ -35,8 +36,44 @@
     html_root_url = "https://docs.rs/rusttesserast/0.0.2"
 
 )]
+pub mod alto;
+pub mod assembly;
+pub mod cache;
+pub mod choices;
 pub mod constanst;
+pub mod convert;
+pub mod engine;
 pub mod file_types;
+pub mod form;
+pub mod hocr;
+pub mod job;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod layout;
+pub mod markdown;
+pub mod model;
+pub mod normalize;
+pub mod page_xml;
+mod pix;
 pub mod errors;
 pub mod tess_lib;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod pool;
+pub mod reading_order;
+pub mod redact;
+pub mod search;
+pub mod spell;
+pub mod subprocess;
+pub mod table;
+pub mod tsv;
+mod xml;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "pdf")]
+pub mod pdf_renderer;
+#[cfg(feature = "pdf")]
+pub mod searchable_pdf;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "worker")]
+pub mod worker;
\ No newline at end of file
@@ -0,0 +1,218 @@
+//! Markdown output.
+//!
+//! `write_markdown` writes the paragraphs of recognized pages as Markdown:
+//! 1. Headings - short paragraphs whose letters are larger than the median line of the page,
+//!    `#` for twice the median size, `##` for 1.5 times and `###` for 1.2 times.
+//! 2. Lists - paragraphs whose lines start with a bullet (`•`, `-`, `*`, ...) or a number (`1.`, `2)`),
+//!    lines without a marker continue the previous item. A change between bullets and numbers starts a new list.
+//! 3. Tables - aligned tables found by `table::detect_tables` with the default options.
+//!    The first row is the header.
+//! 4. Paragraphs - lines are joined with spaces, words hyphenated at the end of a line are joined.
+//!
+//! Pages are separated with a thematic break (`---`).
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::hocr::parse_hocr;
+//! use rusttesserast::markdown::write_markdown;
+//!
+//! let hocr = std::fs::read_to_string("archive/page.hocr").unwrap();
+//! println!("{}", write_markdown(&parse_hocr(&hocr).unwrap()));
+//! ```
+
+use crate::layout::get_median;
+use crate::model::{Line, Page};
+use crate::table::{detect_tables, get_center, is_inside, Table, TableCell, TableOptions};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+const BULLETS: [char; 10] = ['•', '●', '▪', '■', '◦', '‣', '·', '-', '–', '*'];
+
+pub fn write_markdown(pages: &[Page]) -> String {
+    pages
+        .iter()
+        .map(write_page)
+        .collect::<Vec<String>>()
+        .join("\n\n---\n\n")
+}
+
+fn write_page(page: &Page) -> String {
+    let body_size = get_median(page.get_lines().map(get_size).collect()).unwrap_or(1.0);
+    let tables = detect_tables(page, &TableOptions::default());
+    let mut written_tables = HashSet::new();
+    let mut elements = Vec::new();
+    for paragraph in page.blocks.iter().flat_map(|block| block.paragraphs.iter()) {
+        // Lines of a table are replaced by the table at the position of its first line.
+        let mut lines = Vec::new();
+        for line in paragraph.lines.iter() {
+            let table = tables.iter().position(|table| is_in_table(line, table));
+            match table {
+                Some(table) => {
+                    elements.extend(write_paragraph(&lines, body_size));
+                    lines.clear();
+                    if written_tables.insert(table) {
+                        elements.push(write_table(&tables[table]));
+                    }
+                }
+                None => lines.push(line),
+            }
+        }
+        elements.extend(write_paragraph(&lines, body_size));
+    }
+    elements.join("\n\n")
+}
+
+fn is_in_table(line: &Line, table: &Table) -> bool {
+    // A line is a part of a table if the centers of all its words are inside the table.
+    let mut words = line
+        .words
+        .iter()
+        .filter(|word| !word.text.trim().is_empty())
+        .peekable();
+    words.peek().is_some() && words.all(|word| is_inside(get_center(&word.bbox), &table.bbox))
+}
+
+fn get_size(line: &Line) -> f32 {
+    line.x_size.unwrap_or(line.bbox.get_height() as f32)
+}
+
+fn get_words(line: &Line) -> impl Iterator<Item = &str> {
+    line.words
+        .iter()
+        .map(|word| word.text.trim())
+        .filter(|text| !text.is_empty())
+}
+
+fn get_list_marker(line: &Line) -> Option<(Option<u32>, String)> {
+    // Number of a numbered item (None for bullets) and the text of the line without the marker.
+    static NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
+    let number = NUMBER_REGEX.get_or_init(|| Regex::new(r"^(\d{1,3})[.)]$").unwrap());
+    let mut words = get_words(line);
+    let first = words.next()?;
+    let rest = words.collect::<Vec<&str>>().join(" ");
+    if let Some(captures) = number.captures(first) {
+        return Some((captures[1].parse().ok(), rest));
+    }
+    let mut chars = first.chars();
+    match chars.next() {
+        Some(bullet) if BULLETS.contains(&bullet) => {
+            let text = chars.as_str().trim_start();
+            match (text.is_empty(), bullet) {
+                (true, _) => Some((None, rest)),
+                // Dashes and asterisks followed by a word are a part of the text.
+                (false, '-' | '–' | '*') => None,
+                (false, _) => Some((None, format!("{} {}", text, rest).trim_end().to_string())),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn join_lines<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    // Words hyphenated at the end of a line are joined if the next line starts with a lowercase letter.
+    let mut text = String::new();
+    for line in lines {
+        let is_hyphenated = text.ends_with('-')
+            && text[..text.len() - 1].ends_with(char::is_alphabetic)
+            && line.starts_with(char::is_lowercase);
+        if is_hyphenated {
+            text.pop();
+        } else if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn write_paragraph(lines: &[&Line], body_size: f32) -> Vec<String> {
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let texts: Vec<String> = lines
+        .iter()
+        .map(|line| get_words(line).collect::<Vec<&str>>().join(" "))
+        .collect();
+    let size = lines.iter().map(|line| get_size(line)).sum::<f32>() / lines.len() as f32;
+    let level = match size / body_size {
+        ratio if ratio >= 2.0 => 1,
+        ratio if ratio >= 1.5 => 2,
+        ratio if ratio >= 1.2 => 3,
+        _ => 0,
+    };
+    if level > 0 && lines.len() <= 2 {
+        let text = escape_markdown(&join_lines(texts.iter().map(String::as_str)));
+        return vec![format!("{} {}", "#".repeat(level), text)];
+    }
+
+    if lines.iter().any(|line| get_list_marker(line).is_some()) {
+        // Lines before the first item are a paragraph, other lines without a marker continue an item.
+        let mut elements = Vec::new();
+        let mut text_lines: Vec<&str> = Vec::new();
+        let mut items: Vec<(Option<u32>, Vec<String>)> = Vec::new();
+        for (line, text) in lines.iter().zip(texts.iter()) {
+            match (get_list_marker(line), items.last_mut()) {
+                (Some((number, text)), _) => items.push((number, vec![text])),
+                (None, Some((_, item))) => item.push(text.clone()),
+                (None, None) => text_lines.push(text),
+            }
+        }
+        if !text_lines.is_empty() {
+            elements.push(write_text(&join_lines(text_lines.into_iter())));
+        }
+        // An item of another type starts a new list, bullets do not continue a numbered list.
+        let mut lists: Vec<(bool, Vec<String>)> = Vec::new();
+        for (number, item) in items.iter() {
+            let text = escape_markdown(&join_lines(item.iter().map(String::as_str)));
+            let item = match number {
+                Some(number) => format!("{}. {}", number, text),
+                None => format!("- {}", text),
+            };
+            match lists.last_mut() {
+                Some((is_numbered, list)) if *is_numbered == number.is_some() => list.push(item),
+                _ => lists.push((number.is_some(), vec![item])),
+            }
+        }
+        elements.extend(lists.into_iter().map(|(_, list)| list.join("\n")));
+        return elements;
+    }
+    vec![write_text(&join_lines(texts.iter().map(String::as_str)))]
+}
+
+fn write_text(text: &str) -> String {
+    // A paragraph starting with a Markdown marker is escaped, so it is not read as a heading, list or quote.
+    static MARKER_REGEX: OnceLock<Regex> = OnceLock::new();
+    let marker = MARKER_REGEX.get_or_init(|| Regex::new(r"^(#|>|[-+=]|\d+[.)])").unwrap());
+    let text = escape_markdown(text);
+    match marker.find(&text) {
+        Some(found) => format!("{}\\{}", &text[..found.end() - 1], &text[found.end() - 1..]),
+        None => text,
+    }
+}
+
+fn write_table(table: &Table) -> String {
+    let write_row = |row: &Vec<TableCell>| {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| escape_markdown(&cell.text).replace('|', "\\|"))
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut rows = vec![write_row(&table.rows[0])];
+    rows.push(format!("|{}", " --- |".repeat(table.get_column_count())));
+    rows.extend(table.rows[1..].iter().map(write_row));
+    rows.join("\n")
+}
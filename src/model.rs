@@ -0,0 +1,201 @@
+//! Structured recognition result.
+//!
+//! A document is a list of pages, every page is a hierarchy of blocks, paragraphs, lines and words
+//! with bounding boxes in pixels of the source image. Parsers of OCR outputs (see `hocr`)
+//! return this model, so results can be post-processed without re-running OCR.
+//! With the serde feature the model implements `Serialize` and `Deserialize`, see `json`.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// BoundingBox of an element, right and bottom are exclusive.
+pub struct BoundingBox {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl BoundingBox {
+    pub fn new(left: u32, top: u32, right: u32, bottom: u32) -> BoundingBox {
+        BoundingBox {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.right.saturating_sub(self.left)
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.bottom.saturating_sub(self.top)
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        // Smallest box containing both boxes.
+        BoundingBox {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+}
+
+pub(crate) fn get_union<'a>(boxes: impl Iterator<Item = &'a BoundingBox>) -> BoundingBox {
+    // Smallest box containing all boxes, an empty box if there are none.
+    boxes
+        .copied()
+        .reduce(|union, bbox| union.union(&bbox))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Word is a recognized word.
+pub struct Word {
+    pub text: String,
+    pub bbox: BoundingBox,
+    /// Recognition confidence, 0-100.
+    pub confidence: Option<f32>,
+    /// Recognized characters, only hOCR with the hocr_char_boxes variable has them.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Symbol is a recognized character of a word.
+pub struct Symbol {
+    pub text: String,
+    pub bbox: Option<BoundingBox>,
+    /// Recognition confidence, 0-100.
+    pub confidence: Option<f32>,
+    /// Alternatives of the character with the lstm_choice_mode variable, the best first.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Choice is an alternative text of a symbol.
+pub struct Choice {
+    pub text: String,
+    /// Confidence of the alternative, 0-100.
+    pub confidence: f32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Line is a text line.
+pub struct Line {
+    pub bbox: BoundingBox,
+    /// Baseline slope and offset from the bottom of the line box, as in hOCR.
+    pub baseline: Option<(f32, f32)>,
+    /// Height of the line letters in pixels.
+    pub x_size: Option<f32>,
+    pub words: Vec<Word>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Paragraph is a group of lines.
+pub struct Paragraph {
+    pub bbox: BoundingBox,
+    pub lines: Vec<Line>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Block is a text area of a page.
+pub struct Block {
+    pub bbox: BoundingBox,
+    pub paragraphs: Vec<Paragraph>,
+    /// Zero-based position of the block in reading order, see `reading_order`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub order: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Page is a recognized image.
+pub struct Page {
+    pub bbox: BoundingBox,
+    /// Zero-based page number.
+    pub page_number: usize,
+    /// Path of the source image, if it is known.
+    pub image: Option<String>,
+    pub blocks: Vec<Block>,
+    /// Time of the recognition in milliseconds, it is set only for recognized JSON output.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub elapsed_ms: Option<u128>,
+}
+
+impl Line {
+    pub fn get_text(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
+
+impl Paragraph {
+    pub fn get_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(Line::get_text)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl Block {
+    pub fn get_text(&self) -> String {
+        self.paragraphs
+            .iter()
+            .map(Paragraph::get_text)
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+impl Page {
+    pub fn get_text(&self) -> String {
+        // Plain text of the page: words are joined with spaces, lines with \n, paragraphs with an empty line.
+        self.blocks
+            .iter()
+            .map(Block::get_text)
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    pub fn get_words(&self) -> impl Iterator<Item = &Word> {
+        self.get_lines().flat_map(|line| line.words.iter())
+    }
+
+    pub fn get_lines(&self) -> impl Iterator<Item = &Line> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.paragraphs.iter())
+            .flat_map(|paragraph| paragraph.lines.iter())
+    }
+}
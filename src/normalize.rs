@@ -0,0 +1,347 @@
+//! Post-OCR text normalization.
+//!
+//! `NormalizeOptions` configure the steps applied to recognized text:
+//! 1. Unicode normalization - NFC or NFKC, NFKC also replaces ligatures like `ﬁ` by their letters.
+//! 2. Quotes and dashes - typographic quotes become `'` and `"`, hyphens, minus signs and en dashes
+//!    become `-`, em dashes and horizontal bars become `—`.
+//! 3. OCR confusions - `O`/`o` and `l`/`I` in numbers become `0` and `1`, a run of `0` and `1`
+//!    between letters of a word becomes `O`/`o` and `I`/`l` by the case of the word.
+//! 4. Whitespace - runs of spaces become one space, lines are trimmed and runs of empty lines become one.
+//! 5. Dehyphenation - a word hyphenated at the end of a line is joined with the first word of the next line
+//!    if it starts with a lowercase letter. Remaining soft hyphens are removed, or replaced by `-` at line ends.
+//!
+//! `normalize_output` applies the steps to an output of Tesseract: plain text directly, hOCR, ALTO, PAGE XML,
+//! TSV and JSON through the `model`, so every format carries the same text. In the model a joined word keeps
+//! the box of its first part, a word whose text is changed loses its symbols. `hocr::write_hocr` doesn't write
+//! symbols, so a normalized hOCR output has no `ocrx_cinfo` elements (character boxes, confidences and choices).
+//! Set `TesseractApi::normalize` to normalize every output of an engine.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::normalize::NormalizeOptions;
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let normalize = Some(NormalizeOptions::default());
+//!     let mut tesseract_base = TesseractApi::new(Some(TesseractApi{normalize, ..Default::default()}), Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let text = tesseract_base.image_to_string("scans/page.png").await.unwrap();
+//! }
+//! ```
+
+use crate::convert::{parse_doc, write_doc};
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::model::{Line, Page, Word};
+use crate::tsv::{get_pages, get_rows, parse_tsv, write_tsv};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use unicode_normalization::UnicodeNormalization;
+
+const SOFT_HYPHEN: char = '\u{ad}';
+
+/// Hyphens which can end a line of a hyphenated word.
+const HYPHENS: [char; 4] = ['-', SOFT_HYPHEN, '\u{2010}', '\u{2011}'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// UnicodeForm is a Unicode normalization form.
+pub enum UnicodeForm {
+    Nfc,
+    Nfkc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// NormalizeOptions select the normalization steps.
+pub struct NormalizeOptions {
+    /// Unicode normalization form, None - the text is not normalized. Default value - Some(UnicodeForm::Nfkc)
+    pub unicode: Option<UnicodeForm>,
+    /// Join words hyphenated at line ends. Default value - true
+    pub dehyphenate: bool,
+    /// Collapse runs of spaces and empty lines. Default value - true
+    pub collapse_whitespace: bool,
+    /// Replace typographic quotes. Default value - true
+    pub quotes: bool,
+    /// Replace hyphen and dash variants. Default value - true
+    pub dashes: bool,
+    /// Fix 0/O and 1/l confusions. Default value - true
+    pub confusions: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        NormalizeOptions {
+            unicode: Some(UnicodeForm::Nfkc),
+            dehyphenate: true,
+            collapse_whitespace: true,
+            quotes: true,
+            dashes: true,
+            confusions: true,
+        }
+    }
+}
+
+pub fn normalize_output(
+    doc: &str,
+    output_type: &OutputFileFormat,
+    options: &NormalizeOptions,
+) -> Result<String, TesseractError> {
+    // Layout text and Markdown are written from a normalized hOCR output, other formats are returned unchanged.
+    match output_type {
+        OutputFileFormat::TXT => Ok(normalize_text(doc, options)),
+        OutputFileFormat::TSV => {
+            let mut pages = get_pages(&parse_tsv(doc)?);
+            normalize_pages(&mut pages, options);
+            Ok(write_tsv(&get_rows(&pages), doc.starts_with("level\t")))
+        }
+        #[cfg(feature = "serde")]
+        OutputFileFormat::JSON => normalize_doc(doc, output_type, options),
+        OutputFileFormat::HOCR | OutputFileFormat::ALTO | OutputFileFormat::PAGE => {
+            normalize_doc(doc, output_type, options)
+        }
+        _ => Ok(doc.to_string()),
+    }
+}
+
+pub fn normalize_text(text: &str, options: &NormalizeOptions) -> String {
+    static WORD_REGEX: OnceLock<Regex> = OnceLock::new();
+    let word = WORD_REGEX.get_or_init(|| Regex::new(r"\S+").unwrap());
+    let text = normalize_chars(text, options);
+    let mut lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let line = match options.confusions {
+                true => word
+                    .replace_all(line, |captures: &regex::Captures| {
+                        fix_confusions(&captures[0])
+                    })
+                    .into_owned(),
+                false => line.to_string(),
+            };
+            match options.collapse_whitespace {
+                true => collapse_spaces(&line),
+                false => line,
+            }
+        })
+        .collect();
+    if options.dehyphenate {
+        let mut index = 0;
+        while index + 1 < lines.len() {
+            let next = lines[index + 1].trim_start();
+            if let (Some(stem), true) = (
+                get_hyphen_stem(&lines[index]),
+                next.starts_with(char::is_lowercase),
+            ) {
+                let (first, rest) = next.split_once(char::is_whitespace).unwrap_or((next, ""));
+                let (line, next) = (format!("{}{}", stem, first), rest.trim_start().to_string());
+                lines[index] = line;
+                lines[index + 1] = next;
+                if lines[index + 1].is_empty() {
+                    lines.remove(index + 1);
+                }
+            }
+            index += 1;
+        }
+        lines = lines.iter().map(|line| remove_soft_hyphens(line)).collect();
+    }
+    if options.collapse_whitespace {
+        lines.dedup_by(|line, previous| line.is_empty() && previous.is_empty());
+    }
+    lines.join("\n")
+}
+
+pub fn normalize_pages(pages: &mut [Page], options: &NormalizeOptions) {
+    for paragraph in pages
+        .iter_mut()
+        .flat_map(|page| page.blocks.iter_mut())
+        .flat_map(|block| block.paragraphs.iter_mut())
+    {
+        for line in paragraph.lines.iter_mut() {
+            for word in line.words.iter_mut() {
+                let text = normalize_word(&word.text, options);
+                set_text(word, text);
+            }
+            if options.collapse_whitespace {
+                line.words.retain(|word| !word.text.is_empty());
+            }
+        }
+        if options.dehyphenate {
+            dehyphenate_lines(&mut paragraph.lines);
+            for word in paragraph
+                .lines
+                .iter_mut()
+                .flat_map(|line| line.words.iter_mut())
+            {
+                let text = remove_soft_hyphens(&word.text);
+                set_text(word, text);
+            }
+        }
+    }
+}
+
+fn set_text(word: &mut Word, text: String) {
+    // Symbols of a changed word don't match its text anymore, so they are removed.
+    if word.text != text {
+        word.text = text;
+        word.symbols.clear();
+    }
+}
+
+fn normalize_doc(
+    doc: &str,
+    output_type: &OutputFileFormat,
+    options: &NormalizeOptions,
+) -> Result<String, TesseractError> {
+    let mut pages = parse_doc(doc, output_type)?;
+    normalize_pages(&mut pages, options);
+    write_doc(&pages, output_type)
+}
+
+fn normalize_word(text: &str, options: &NormalizeOptions) -> String {
+    let text = normalize_chars(text, options);
+    let text = match options.confusions {
+        true => fix_confusions(&text),
+        false => text,
+    };
+    match options.collapse_whitespace {
+        true => collapse_spaces(&text),
+        false => text,
+    }
+}
+
+fn normalize_chars(text: &str, options: &NormalizeOptions) -> String {
+    let text: String = match options.unicode {
+        Some(UnicodeForm::Nfc) => text.nfc().collect(),
+        Some(UnicodeForm::Nfkc) => text.nfkc().collect(),
+        None => text.to_string(),
+    };
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' if options.quotes => {
+                '\''
+            }
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' if options.quotes => '"',
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2212}' if options.dashes => '-',
+            '\u{2014}' | '\u{2015}' if options.dashes => '—',
+            c => c,
+        })
+        .collect()
+}
+
+fn collapse_spaces(text: &str) -> String {
+    // Form feeds separating pages are kept.
+    text.split(|c: char| c.is_whitespace() && c != '\u{c}')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn fix_confusions(word: &str) -> String {
+    // Leading and trailing punctuation of the word is kept.
+    let (start, end) = match (
+        word.find(char::is_alphanumeric),
+        word.rfind(char::is_alphanumeric),
+    ) {
+        (Some(start), Some(end)) => (start, end + word[end..].chars().next().unwrap().len_utf8()),
+        _ => return word.to_string(),
+    };
+    let core: Vec<char> = word[start..end].chars().collect();
+    let is_number = core.iter().any(char::is_ascii_digit)
+        && core.iter().all(|c| {
+            c.is_ascii_digit() || matches!(c, 'O' | 'o' | 'l' | 'I' | '.' | ',' | '-' | '/' | ':')
+        });
+    let fixed: String = if is_number {
+        core.iter()
+            .map(|c| match c {
+                'O' | 'o' => '0',
+                'l' | 'I' => '1',
+                c => *c,
+            })
+            .collect()
+    } else if core.iter().filter(|c| c.is_alphabetic()).count() >= 2
+        && core
+            .iter()
+            .all(|c| c.is_alphabetic() || matches!(c, '0' | '1'))
+    {
+        // Only runs of digits with letters on both sides are replaced, e.g. 1st is kept.
+        let is_upper = core
+            .iter()
+            .filter(|c| c.is_alphabetic())
+            .all(|c| c.is_uppercase());
+        let mut fixed = core.clone();
+        let mut index = 0;
+        while index < core.len() {
+            let run = core[index..]
+                .iter()
+                .take_while(|c| !c.is_alphabetic())
+                .count();
+            if run > 0 && index > 0 && index + run < core.len() {
+                for c in fixed[index..index + run].iter_mut() {
+                    *c = match (*c, is_upper) {
+                        ('0', true) => 'O',
+                        ('0', false) => 'o',
+                        (_, true) => 'I',
+                        (_, false) => 'l',
+                    };
+                }
+            }
+            index += run.max(1);
+        }
+        fixed.into_iter().collect()
+    } else {
+        return word.to_string();
+    };
+    format!("{}{}{}", &word[..start], fixed, &word[end..])
+}
+
+fn get_hyphen_stem(text: &str) -> Option<&str> {
+    // Text without the hyphen at its end, if the hyphen follows a letter.
+    let text = text.trim_end();
+    let hyphen = text.chars().last().filter(|c| HYPHENS.contains(c))?;
+    let stem = &text[..text.len() - hyphen.len_utf8()];
+    match stem.chars().last() {
+        Some(c) if c.is_alphabetic() => Some(stem),
+        _ => None,
+    }
+}
+
+fn remove_soft_hyphens(text: &str) -> String {
+    match text.strip_suffix(SOFT_HYPHEN) {
+        Some(text) => format!("{}-", text.replace(SOFT_HYPHEN, "")),
+        None => text.replace(SOFT_HYPHEN, ""),
+    }
+}
+
+fn dehyphenate_lines(lines: &mut Vec<Line>) {
+    // The last word of a line is joined with the first word of the next line,
+    // a line left without words is removed.
+    let mut index = 0;
+    while index + 1 < lines.len() {
+        let next = match lines[index + 1].words.first() {
+            Some(word) if word.text.starts_with(char::is_lowercase) => word.clone(),
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        if let Some(word) = lines[index].words.last_mut() {
+            if let Some(stem) = get_hyphen_stem(&word.text) {
+                let text = format!("{}{}", stem, next.text);
+                set_text(word, text);
+                word.confidence = match (word.confidence, next.confidence) {
+                    (Some(confidence), Some(next)) => Some(confidence.min(next)),
+                    (confidence, next) => confidence.or(next),
+                };
+                lines[index + 1].words.remove(0);
+                if lines[index + 1].words.is_empty() {
+                    lines.remove(index + 1);
+                }
+            }
+        }
+        index += 1;
+    }
+}
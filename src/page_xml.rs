@@ -0,0 +1,294 @@
+//! PAGE XML reader and writer.
+//!
+//! `parse_page_xml` reads a PAGE XML document (2013-07-15 or 2019-07-15 schema) into a `model::Page`,
+//! `write_page_xml` writes a page with the 2019-07-15 schema. A PAGE XML document contains one page.
+//! PAGE has no blocks, so every paragraph is written as a `TextRegion` of type paragraph and every
+//! `TextRegion` is read as a block with one paragraph. Line baselines are converted between
+//! PAGE polylines and the hOCR slope and offset.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::hocr::parse_hocr;
+//! use rusttesserast::page_xml::write_page_xml;
+//!
+//! let pages = parse_hocr(&std::fs::read_to_string("archive/page.hocr").unwrap()).unwrap();
+//! std::fs::write("page.xml", write_page_xml(&pages[0])).unwrap();
+//! ```
+
+use crate::errors::TesseractError;
+use crate::model::{Block, BoundingBox, Line, Page, Paragraph, Word};
+use crate::utils::get_utc_time;
+use crate::xml::{escape_xml, parse_xml, Element};
+use std::time::SystemTime;
+
+pub fn parse_page_xml(page_xml: &str) -> Result<Page, TesseractError> {
+    let root = parse_xml(page_xml)?;
+    let page = root
+        .get_child("PcGts")
+        .and_then(|document| document.get_child("Page"))
+        .ok_or(TesseractError::ParseError)?;
+    let mut region_elements = Vec::new();
+    find_regions(page, &mut region_elements);
+    let blocks = region_elements
+        .into_iter()
+        .map(parse_region)
+        .collect::<Result<Vec<Block>, TesseractError>>()?;
+    let width = parse_size(page, "imageWidth")?;
+    let height = parse_size(page, "imageHeight")?;
+    Ok(Page {
+        bbox: BoundingBox::new(0, 0, width, height),
+        page_number: 0,
+        image: page
+            .get_attribute("imageFilename")
+            .filter(|image| !image.is_empty())
+            .map(str::to_string),
+        blocks,
+        elapsed_ms: None,
+    })
+}
+
+pub fn write_page_xml(page: &Page) -> String {
+    let timestamp = get_timestamp();
+    let mut page_xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <PcGts xmlns=\"http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15 \
+         http://schema.primaresearch.org/PAGE/gts/pagecontent/2019-07-15/pagecontent.xsd\">\n\
+         \t<Metadata>\n\
+         \t\t<Creator>rusttesserast</Creator>\n\
+         \t\t<Created>{}</Created>\n\
+         \t\t<LastChange>{}</LastChange>\n\
+         \t</Metadata>\n\
+         \t<Page imageFilename=\"{}\" imageWidth=\"{}\" imageHeight=\"{}\">\n",
+        timestamp,
+        timestamp,
+        escape_xml(page.image.as_deref().unwrap_or_default()),
+        page.bbox.right,
+        page.bbox.bottom
+    );
+    let (mut region_id, mut line_id, mut word_id) = (0, 0, 0);
+    for paragraph in page.blocks.iter().flat_map(|block| block.paragraphs.iter()) {
+        region_id += 1;
+        page_xml.push_str(&format!(
+            "\t\t<TextRegion id=\"r{}\" type=\"paragraph\">\n\
+             \t\t\t<Coords points=\"{}\"/>\n",
+            region_id,
+            write_points(&paragraph.bbox)
+        ));
+        for line in paragraph.lines.iter() {
+            line_id += 1;
+            page_xml.push_str(&format!(
+                "\t\t\t<TextLine id=\"l{}\">\n\
+                 \t\t\t\t<Coords points=\"{}\"/>\n",
+                line_id,
+                write_points(&line.bbox)
+            ));
+            if let Some(baseline) = write_baseline(line) {
+                page_xml.push_str(&format!("\t\t\t\t<Baseline points=\"{}\"/>\n", baseline));
+            }
+            for word in line.words.iter() {
+                word_id += 1;
+                let confidence = match word.confidence {
+                    Some(confidence) => format!(" conf=\"{:.4}\"", confidence / 100.0),
+                    None => String::new(),
+                };
+                page_xml.push_str(&format!(
+                    "\t\t\t\t<Word id=\"w{}\">\n\
+                     \t\t\t\t\t<Coords points=\"{}\"/>\n\
+                     \t\t\t\t\t<TextEquiv{}>\n\
+                     \t\t\t\t\t\t<Unicode>{}</Unicode>\n\
+                     \t\t\t\t\t</TextEquiv>\n\
+                     \t\t\t\t</Word>\n",
+                    word_id,
+                    write_points(&word.bbox),
+                    confidence,
+                    escape_xml(&word.text)
+                ));
+            }
+            page_xml.push_str(&format!(
+                "\t\t\t\t<TextEquiv>\n\
+                 \t\t\t\t\t<Unicode>{}</Unicode>\n\
+                 \t\t\t\t</TextEquiv>\n\
+                 \t\t\t</TextLine>\n",
+                escape_xml(&line.get_text())
+            ));
+        }
+        page_xml.push_str(&format!(
+            "\t\t\t<TextEquiv>\n\
+             \t\t\t\t<Unicode>{}</Unicode>\n\
+             \t\t\t</TextEquiv>\n\
+             \t\t</TextRegion>\n",
+            escape_xml(&paragraph.get_text())
+        ));
+    }
+    page_xml.push_str("\t</Page>\n</PcGts>\n");
+    page_xml
+}
+
+fn get_timestamp() -> String {
+    // Current UTC time as xsd:dateTime.
+    let (year, month, day, hour, minute, second) = get_utc_time(SystemTime::now());
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+fn write_points(bbox: &BoundingBox) -> String {
+    format!(
+        "{},{} {},{} {},{} {},{}",
+        bbox.left, bbox.top, bbox.right, bbox.top, bbox.right, bbox.bottom, bbox.left, bbox.bottom
+    )
+}
+
+fn write_baseline(line: &Line) -> Option<String> {
+    // hOCR baseline: y = slope * (x - left) + bottom + offset.
+    let (slope, offset) = line.baseline?;
+    let left_y = line.bbox.bottom as f32 + offset;
+    let right_y = left_y + slope * line.bbox.get_width() as f32;
+    Some(format!(
+        "{},{} {},{}",
+        line.bbox.left,
+        left_y.max(0.0).round(),
+        line.bbox.right,
+        right_y.max(0.0).round()
+    ))
+}
+
+fn parse_size(element: &Element, name: &str) -> Result<u32, TesseractError> {
+    element
+        .get_attribute(name)
+        .ok_or(TesseractError::ParseError)?
+        .trim()
+        .parse()
+        .map_err(|_| TesseractError::ParseError)
+}
+
+fn parse_points(element: &Element) -> Result<Vec<(f32, f32)>, TesseractError> {
+    let points = element
+        .get_attribute("points")
+        .ok_or(TesseractError::ParseError)?
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point.split_once(',').ok_or(TesseractError::ParseError)?;
+            match (x.parse::<f32>(), y.parse::<f32>()) {
+                (Ok(x), Ok(y)) => Ok((x, y)),
+                _ => Err(TesseractError::ParseError),
+            }
+        })
+        .collect::<Result<Vec<(f32, f32)>, TesseractError>>()?;
+    if points.is_empty() {
+        return Err(TesseractError::ParseError);
+    }
+    Ok(points)
+}
+
+fn parse_coords(element: &Element) -> Result<BoundingBox, TesseractError> {
+    // Bounding box of the Coords polygon.
+    let points = parse_points(
+        element
+            .get_child("Coords")
+            .ok_or(TesseractError::ParseError)?,
+    )?;
+    let coordinate = |value: f32| value.max(0.0).round() as u32;
+    Ok(points
+        .iter()
+        .map(|(x, y)| {
+            BoundingBox::new(
+                coordinate(*x),
+                coordinate(*y),
+                coordinate(*x),
+                coordinate(*y),
+            )
+        })
+        .reduce(|union, bbox| union.union(&bbox))
+        .unwrap_or_default())
+}
+
+fn get_unicode(element: &Element) -> Option<(String, Option<f32>)> {
+    // Text and confidence of the first TextEquiv.
+    let text_equiv = element.get_child("TextEquiv")?;
+    let text = text_equiv
+        .get_child("Unicode")
+        .map(|unicode| unicode.get_text())
+        .unwrap_or_default();
+    let confidence = text_equiv
+        .get_attribute("conf")
+        .and_then(|conf| conf.trim().parse::<f32>().ok())
+        .map(|conf| (conf * 10000.0).round() / 100.0);
+    Some((text, confidence))
+}
+
+fn find_regions<'a>(element: &'a Element, found: &mut Vec<&'a Element>) {
+    // Text regions, including regions nested in other regions.
+    for child in element.get_children() {
+        if child.get_local_name() == "TextRegion" {
+            found.push(child);
+        }
+        find_regions(child, found);
+    }
+}
+
+fn parse_region(element: &Element) -> Result<Block, TesseractError> {
+    let lines = element
+        .get_children()
+        .filter(|child| child.get_local_name() == "TextLine")
+        .map(parse_line)
+        .collect::<Result<Vec<Line>, TesseractError>>()?;
+    let bbox = parse_coords(element)?;
+    Ok(Block {
+        bbox,
+        paragraphs: vec![Paragraph { bbox, lines }],
+        order: None,
+    })
+}
+
+fn parse_line(element: &Element) -> Result<Line, TesseractError> {
+    let bbox = parse_coords(element)?;
+    let mut words = element
+        .get_children()
+        .filter(|child| child.get_local_name() == "Word")
+        .map(|word| {
+            let (text, confidence) = get_unicode(word).unwrap_or_default();
+            Ok(Word {
+                text,
+                bbox: parse_coords(word)?,
+                confidence,
+                symbols: Vec::new(),
+            })
+        })
+        .collect::<Result<Vec<Word>, TesseractError>>()?;
+    if words.is_empty() {
+        // A line without words is read as one word with the text of the line.
+        if let Some((text, confidence)) = get_unicode(element).filter(|(text, _)| !text.is_empty())
+        {
+            words.push(Word {
+                text,
+                bbox,
+                confidence,
+                symbols: Vec::new(),
+            });
+        }
+    }
+    let baseline = match element.get_child("Baseline") {
+        Some(baseline) => {
+            let points = parse_points(baseline)?;
+            let (first, last) = (points[0], points[points.len() - 1]);
+            let slope = match last.0 - first.0 {
+                width if width > 0.0 => (last.1 - first.1) / width,
+                _ => 0.0,
+            };
+            let left_y = first.1 - slope * (first.0 - bbox.left as f32);
+            Some((slope, left_y - bbox.bottom as f32))
+        }
+        None => None,
+    };
+    Ok(Line {
+        bbox,
+        baseline,
+        x_size: None,
+        words,
+    })
+}
@@ -0,0 +1,414 @@
+//! PDF input.
+//!
+//! PDF pages are rasterized with pdfium (`pdfium-render`) at the configured dpi and recognized
+//! like images. Pages which already have a text layer can be recognized anyway, skipped or read
+//! from the text layer without OCR, see `PdfTextLayer`. The pdfium library is loaded at runtime
+//! from `PdfOptions::library_path` or from the system library path.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::pdf::{PdfOptions, PdfTextLayer};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let pdf_options = PdfOptions{text_layer: PdfTextLayer::Reuse, ..Default::default()};
+//!     let mut tesseract_base = TesseractApi::new(Some(TesseractApi{pdf_options, ..Default::default()}), Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let pages = tesseract_base.pdf_to_hocr("scans/contract.pdf").await.unwrap();
+//! }
+//! ```
+
+use crate::constanst::TesseractDefaultConstants;
+use crate::convert::write_doc;
+use crate::engine::OcrEngine;
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::model::{get_union, Block, BoundingBox, Line, Page, Paragraph, Word};
+use crate::subprocess::get_page_output;
+use pdfium_render::prelude::{PdfDocument, PdfPage, PdfRenderConfig, Pdfium};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// PdfTextLayer selects how pages which already contain text are processed.
+pub enum PdfTextLayer {
+    /// Rasterize and recognize every page.
+    #[default]
+    Ocr,
+    /// Leave out pages with a text layer.
+    Skip,
+    /// Return the text layer of a page instead of recognizing it.
+    Reuse,
+}
+
+#[derive(Debug, Default, Clone)]
+/// PdfOptions describe how PDF files are rasterized.
+pub struct PdfOptions {
+    /// Rendering resolution. If it is None, the dpi of the engine is used. Default value - None
+    pub dpi: Option<i32>,
+    /// Processing of pages with a text layer. Default value - PdfTextLayer::Ocr
+    pub text_layer: PdfTextLayer,
+    /// Password of an encrypted PDF.
+    pub password: Option<String>,
+    /// Directory with the pdfium library. If it is None, the library is loaded from the system library path.
+    pub library_path: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// PdfPageResult is the output of one PDF page.
+pub struct PdfPageResult {
+    /// One-based page number in the PDF file.
+    pub page_number: usize,
+    /// The output was read from the text layer of the page, not recognized.
+    pub text_layer: bool,
+    pub output: Result<String, TesseractError>,
+    /// Time of the rendering and recognition of the page in milliseconds.
+    pub elapsed_ms: u128,
+}
+
+pub fn is_pdf(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case("pdf"))
+}
+
+pub fn get_page_count(path: &str, options: &PdfOptions) -> Result<usize, TesseractError> {
+    let pdfium = bind_pdfium(options)?;
+    let document = load_document(&pdfium, path, options)?;
+    Ok(document.pages().len() as usize)
+}
+
+pub fn recognize_pdf<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    path: &str,
+    output_type: &OutputFileFormat,
+    options: &PdfOptions,
+) -> Result<Vec<PdfPageResult>, TesseractError> {
+    // Recognize all pages of a PDF file. Pages are rendered one at a time.
+    // Pages with a text layer are left out if options.text_layer is PdfTextLayer::Skip.
+    let pdfium = bind_pdfium(options)?;
+    let document = load_document(&pdfium, path, options)?;
+    let mut results = Vec::new();
+    for (index, page) in document.pages().iter().enumerate() {
+        let text_layer = has_text_layer(&page);
+        if text_layer && options.text_layer == PdfTextLayer::Skip {
+            continue;
+        }
+        let started = Instant::now();
+        let output = recognize_page(engine, &page, index, output_type, options);
+        results.push(PdfPageResult {
+            page_number: index + 1,
+            text_layer: text_layer && options.text_layer == PdfTextLayer::Reuse,
+            output,
+            elapsed_ms: started.elapsed().as_millis(),
+        });
+    }
+    Ok(results)
+}
+
+pub fn recognize_pdf_page<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    path: &str,
+    page_number: usize,
+    output_type: &OutputFileFormat,
+    options: &PdfOptions,
+) -> Result<String, TesseractError> {
+    // Recognize one page of a PDF file, page_number is one-based.
+    let pdfium = bind_pdfium(options)?;
+    let document = load_document(&pdfium, path, options)?;
+    let index = page_number.checked_sub(1).ok_or(TesseractError::PdfError)?;
+    let page = get_page(&document, index)?;
+    recognize_page(engine, &page, index, output_type, options)
+}
+
+fn get_page<'a>(document: &PdfDocument<'a>, index: usize) -> Result<PdfPage<'a>, TesseractError> {
+    // Page of a document by its zero-based index.
+    let index = u16::try_from(index).map_err(|_| TesseractError::PdfError)?;
+    document
+        .pages()
+        .get(index)
+        .map_err(|_| TesseractError::PdfError)
+}
+
+fn bind_pdfium(options: &PdfOptions) -> Result<Pdfium, TesseractError> {
+    let bindings = match options.library_path.as_deref() {
+        Some(library_path) => {
+            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(library_path))
+        }
+        None => Pdfium::bind_to_system_library(),
+    };
+    bindings
+        .map(Pdfium::new)
+        .map_err(|_| TesseractError::PdfError)
+}
+
+fn load_document<'a>(
+    pdfium: &'a Pdfium,
+    path: &str,
+    options: &PdfOptions,
+) -> Result<PdfDocument<'a>, TesseractError> {
+    if !Path::new(path).exists() {
+        return Err(TesseractError::NoSuchFileException);
+    }
+    pdfium
+        .load_pdf_from_file(path, options.password.as_deref())
+        .map_err(|_| TesseractError::PdfError)
+}
+
+fn has_text_layer(page: &PdfPage) -> bool {
+    page.text().is_ok_and(|text| !text.all().trim().is_empty())
+}
+
+pub(crate) fn get_dpi(options: &PdfOptions) -> i32 {
+    options
+        .dpi
+        .filter(|dpi| *dpi > 0)
+        .unwrap_or(TesseractDefaultConstants::DEFAULT_DPI)
+}
+
+fn recognize_page<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    page: &PdfPage,
+    index: usize,
+    output_type: &OutputFileFormat,
+    options: &PdfOptions,
+) -> Result<String, TesseractError> {
+    if options.text_layer == PdfTextLayer::Reuse && has_text_layer(page) {
+        return get_text_layer_output(page, index, output_type, get_dpi(options));
+    }
+    // The page is recognized at the resolution it was rendered at.
+    let dpi = get_dpi(options);
+    engine.set_image_from_mem(&render_page(page, dpi)?)?;
+    engine.set_source_resolution(dpi);
+    engine.recognize()?;
+    engine.get_output(output_type)
+}
+
+fn render_page(page: &PdfPage, dpi: i32) -> Result<Vec<u8>, TesseractError> {
+    // Render a page to a binary PPM image, PDF pages are measured in points (1/72 inch).
+    let config = PdfRenderConfig::new().scale_page_by_factor(dpi as f32 / 72.0);
+    let bitmap = page
+        .render_with_config(&config)
+        .map_err(|_| TesseractError::PdfError)?;
+    let (width, height) = (bitmap.width() as usize, bitmap.height() as usize);
+    let rgba = bitmap.as_rgba_bytes();
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    image.reserve(width * height * 3);
+    for pixel in rgba.chunks_exact(4) {
+        image.extend_from_slice(&pixel[..3]);
+    }
+    Ok(image)
+}
+
+fn get_text_layer_output(
+    page: &PdfPage,
+    index: usize,
+    output_type: &OutputFileFormat,
+    dpi: i32,
+) -> Result<String, TesseractError> {
+    let text = page.text().map_err(|_| TesseractError::PdfError)?;
+    if *output_type == OutputFileFormat::TXT {
+        return Ok(text.all());
+    }
+    // Every text segment is read as a line, the width of a segment is split between its words
+    // by the number of chars. Boxes are converted from points to pixels at the rendering dpi.
+    let scale = dpi as f32 / 72.0;
+    let page_height = page.height().value;
+    let pixel = |value: f32| (value.max(0.0) * scale).round() as u32;
+    let lines: Vec<Line> = text
+        .segments()
+        .iter()
+        .map(|segment| {
+            let bounds = segment.bounds();
+            let bbox = BoundingBox::new(
+                pixel(bounds.left.value),
+                pixel(page_height - bounds.top.value),
+                pixel(bounds.right.value),
+                pixel(page_height - bounds.bottom.value),
+            );
+            Line {
+                bbox,
+                words: split_words(&segment.text(), &bbox),
+                ..Default::default()
+            }
+        })
+        .filter(|line| !line.words.is_empty())
+        .collect();
+    let bbox = get_union(lines.iter().map(|line| &line.bbox));
+    let page = Page {
+        bbox: BoundingBox::new(0, 0, pixel(page.width().value), pixel(page_height)),
+        page_number: index,
+        image: None,
+        blocks: vec![Block {
+            bbox,
+            paragraphs: vec![Paragraph { bbox, lines }],
+            order: None,
+        }],
+        elapsed_ms: None,
+    };
+    Ok(get_page_output(
+        output_type,
+        &write_doc(&[page], output_type)?,
+    ))
+}
+
+fn split_words(text: &str, bbox: &BoundingBox) -> Vec<Word> {
+    let chars: Vec<char> = text.chars().collect();
+    let x = |index: usize| bbox.left + (bbox.get_width() as usize * index / chars.len()) as u32;
+    let mut words = Vec::new();
+    let mut start = None;
+    for index in 0..=chars.len() {
+        match (start, chars.get(index).filter(|c| !c.is_whitespace())) {
+            (None, Some(_)) => start = Some(index),
+            (Some(word_start), None) => {
+                words.push(Word {
+                    text: chars[word_start..index].iter().collect(),
+                    bbox: BoundingBox::new(x(word_start), bbox.top, x(index), bbox.bottom),
+                    confidence: None,
+                    symbols: Vec::new(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// PdfPages is an engine adapter used by `engine::recognize_doc`, every PDF file is split
+/// into pages named `{file name}-{page}.pdf`, which are rasterized when they are recognized.
+/// Documents are loaded once with the pdfium bound by `bind_pdf_library`.
+pub(crate) struct PdfPages<'a, E: OcrEngine + ?Sized> {
+    engine: &'a mut E,
+    options: PdfOptions,
+    documents: Vec<PdfDocument<'a>>,
+    /// Index of the document and zero-based page index of every page name,
+    /// or the error of a PDF file which could not be loaded.
+    pages: HashMap<String, Result<(usize, usize), TesseractError>>,
+}
+
+pub(crate) fn bind_pdf_library(
+    image_array: &[&str],
+    options: &PdfOptions,
+) -> Option<Result<Pdfium, TesseractError>> {
+    // The pdfium library is bound only if there are PDF files among the images.
+    // A binding error is kept, so only the PDF files fail and the images are recognized.
+    match image_array.iter().any(|image| is_pdf(image)) {
+        true => Some(bind_pdfium(options)),
+        false => None,
+    }
+}
+
+impl<'a, E: OcrEngine + ?Sized> PdfPages<'a, E> {
+    pub fn new(
+        engine: &'a mut E,
+        pdfium: Option<&'a Result<Pdfium, TesseractError>>,
+        image_array: &[&str],
+        options: &PdfOptions,
+    ) -> (Self, Vec<String>) {
+        // Returns the adapter and the input array with PDF files replaced by their pages.
+        // Pages with a text layer are left out if options.text_layer is PdfTextLayer::Skip.
+        // A PDF file which can't be loaded (e.g. encrypted or corrupt, or pdfium is missing)
+        // stays in the array and fails alone when it is recognized.
+        let mut documents = Vec::new();
+        let mut pages = HashMap::new();
+        let mut images = Vec::with_capacity(image_array.len());
+        for image in image_array.iter() {
+            let pdfium = match pdfium {
+                Some(pdfium) if is_pdf(image) => pdfium,
+                _ => {
+                    images.push(image.to_string());
+                    continue;
+                }
+            };
+            let document = match pdfium
+                .as_ref()
+                .map_err(TesseractError::clone)
+                .and_then(|pdfium| load_document(pdfium, image, options))
+            {
+                Ok(document) => document,
+                Err(error) => {
+                    pages.insert(image.to_string(), Err(error));
+                    images.push(image.to_string());
+                    continue;
+                }
+            };
+            let path = Path::new(image);
+            // Pages are named after the full file name, so `scan.pdf` and `scan.PDF` don't collide.
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            for (index, page) in document.pages().iter().enumerate() {
+                if options.text_layer == PdfTextLayer::Skip && has_text_layer(&page) {
+                    continue;
+                }
+                let name = path
+                    .with_file_name(format!("{}-{}.pdf", file_name, index + 1))
+                    .to_string_lossy()
+                    .into_owned();
+                pages.insert(name.clone(), Ok((documents.len(), index)));
+                images.push(name);
+            }
+            documents.push(document);
+        }
+        let options = options.clone();
+        (
+            PdfPages {
+                engine,
+                options,
+                documents,
+                pages,
+            },
+            images,
+        )
+    }
+}
+
+impl<E: OcrEngine + ?Sized> OcrEngine for PdfPages<'_, E> {
+    fn set_image(&mut self, filename: &str) -> Result<(), TesseractError> {
+        self.engine.set_image(filename)
+    }
+
+    fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError> {
+        self.engine.set_image_from_mem(image_data)
+    }
+
+    fn set_source_resolution(&mut self, dpi: i32) {
+        self.engine.set_source_resolution(dpi)
+    }
+
+    fn set_page_seg_mode(&mut self, psm: u32) {
+        self.engine.set_page_seg_mode(psm)
+    }
+
+    fn recognize(&mut self) -> Result<(), TesseractError> {
+        self.engine.recognize()
+    }
+
+    fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        self.engine.get_output(output_type)
+    }
+
+    fn mean_text_conf(&mut self) -> i32 {
+        self.engine.mean_text_conf()
+    }
+
+    fn recognize_image(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+    ) -> Result<String, TesseractError> {
+        match self.pages.get(filename) {
+            Some(Ok((document, index))) => {
+                let page = get_page(&self.documents[*document], *index)?;
+                recognize_page(self.engine, &page, *index, output_type, &self.options)
+            }
+            Some(Err(error)) => Err(error.clone()),
+            None => self.engine.recognize_image(filename, output_type),
+        }
+    }
+}
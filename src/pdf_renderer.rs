@@ -0,0 +1,392 @@
+//! PDF output.
+//!
+//! `render_pdf` recognizes images and writes them with Tesseract's PDF renderer (`OutputFileFormat::PDF`):
+//! every image becomes a page with an invisible text layer, or with the text layer only if
+//! `PdfRendererOptions::text_only` is set (Tesseract's textonly_pdf, for overlaying on the originals).
+//! The renderer output is then updated with the document metadata and, if `pdfa` is set,
+//! the XMP metadata and RGB output intent required by PDF/A-2b.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::pdf_renderer::{PdfImageCompression, PdfRendererOptions};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let options = PdfRendererOptions{pdfa: true, compression: PdfImageCompression::Jpeg(60), title: Some(String::from("Contract")), ..Default::default()};
+//!     let pdf = tesseract_base.images_to_pdf(&["scans/1.png", "scans/2.png"], "archive/contract.pdf", &options).await.unwrap();
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::pix::RawPix;
+use crate::tess_lib::TesseractApi;
+use crate::utils::get_utc_time;
+use lopdf::{dictionary, Document, Object, Stream, StringFormat};
+use pl::leptonica_sys::pixClone;
+use pl::tesseract_sys::{
+    TessBaseAPI, TessBaseAPICreate, TessBaseAPIDelete, TessBaseAPIEnd, TessBaseAPIGetDatapath,
+    TessBaseAPIGetThresholdedImage, TessBaseAPIInit2, TessBaseAPIRecognize, TessBaseAPISetImage2,
+    TessBaseAPISetInputImage, TessBaseAPISetInputName, TessBaseAPISetPageSegMode,
+    TessBaseAPISetSourceResolution, TessBaseAPISetVariable, TessDeleteResultRenderer,
+    TessPDFRendererCreate, TessResultRenderer, TessResultRendererAddImage,
+    TessResultRendererBeginDocument, TessResultRendererEndDocument,
+};
+use sha2::{Digest, Sha256};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::time::SystemTime;
+use tesseract_plumbing as pl;
+
+/// Description of the output intent profile, see `get_rgb_profile`.
+const RGB_PROFILE_DESCRIPTION: &str = "RGB, sRGB primaries, gamma 2.2";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// PdfImageCompression selects how page images are stored.
+pub enum PdfImageCompression {
+    /// Tesseract default: JPEG files are embedded as they are, PNG files are compressed losslessly,
+    /// other images are stored as JPEG (quality 85) or CCITT G4 for bilevel images.
+    #[default]
+    Auto,
+    /// Images are encoded again, gray and color images as JPEG with the given quality 1-100.
+    Jpeg(u8),
+    /// Images are binarized by Tesseract and stored as CCITT G4, the smallest output for text documents.
+    Bilevel,
+}
+
+#[derive(Debug, Default, Clone)]
+/// PdfRendererOptions describe a PDF written by `render_pdf`.
+pub struct PdfRendererOptions {
+    /// Write only the invisible text layer without images. Default value - false
+    pub text_only: bool,
+    /// Compression of page images. Default value - PdfImageCompression::Auto
+    pub compression: PdfImageCompression,
+    /// Write a PDF/A-2b document. Default value - false
+    pub pdfa: bool,
+    /// Document title. Default value - None
+    pub title: Option<String>,
+    /// Document author. Default value - None
+    pub author: Option<String>,
+    /// Creation date of the document. If it is None, the current time is used.
+    pub creation_date: Option<SystemTime>,
+}
+
+/// Owned TessBaseAPI handle of the C API, the PDF renderer needs the handle itself.
+struct RawApi(*mut TessBaseAPI);
+
+impl Drop for RawApi {
+    fn drop(&mut self) {
+        unsafe {
+            TessBaseAPIEnd(self.0);
+            TessBaseAPIDelete(self.0);
+        }
+    }
+}
+
+struct RawRenderer(*mut TessResultRenderer);
+
+impl Drop for RawRenderer {
+    fn drop(&mut self) {
+        unsafe { TessDeleteResultRenderer(self.0) }
+    }
+}
+
+fn to_cstring(value: &str) -> Result<CString, TesseractError> {
+    CString::new(value).map_err(|_| TesseractError::TesseractInitError)
+}
+
+fn create_api(tesseract: &TesseractApi) -> Result<RawApi, TesseractError> {
+    // A new engine with the configuration of tesseract.
+    let datapath = tesseract.datapath.as_deref().map(to_cstring).transpose()?;
+    let lang = tesseract.lang.as_deref().map(to_cstring).transpose()?;
+    let api = RawApi(unsafe { TessBaseAPICreate() });
+    if api.0.is_null() {
+        return Err(TesseractError::TesseractInitError);
+    }
+    let result = unsafe {
+        TessBaseAPIInit2(
+            api.0,
+            datapath
+                .as_ref()
+                .map_or(ptr::null(), |datapath| datapath.as_ptr()),
+            lang.as_ref().map_or(ptr::null(), |lang| lang.as_ptr()),
+            tesseract.oem,
+        )
+    };
+    if result != 0 {
+        return Err(TesseractError::TesseractInitError);
+    }
+    for (name, value) in tesseract.variables.iter() {
+        let (name, value) = (to_cstring(name)?, to_cstring(value)?);
+        if unsafe { TessBaseAPISetVariable(api.0, name.as_ptr(), value.as_ptr()) } == 0 {
+            return Err(TesseractError::TesseractInitError);
+        }
+    }
+    unsafe { TessBaseAPISetPageSegMode(api.0, tesseract.psm) };
+    Ok(api)
+}
+
+pub fn render_pdf(
+    tesseract: &TesseractApi,
+    image_array: &[&str],
+    output_path: &str,
+    options: &PdfRendererOptions,
+) -> Result<PathBuf, TesseractError> {
+    // Recognize images and save them as one PDF document.
+    // tesseract - configuration of the engine (datapath, lang, oem, psm, dpi and variables).
+    // output_path - path of the document, .pdf is appended if it has another extension.
+    // Tesseract loads the glyphless font of the text layer from pdf.ttf in the tessdata directory.
+    let output_base = output_path.strip_suffix(".pdf").unwrap_or(output_path);
+    let output = PathBuf::from(format!("{}.pdf", output_base));
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).map_err(|_| TesseractError::SaveDocError)?;
+        }
+    }
+    let api = create_api(tesseract)?;
+    if let PdfImageCompression::Jpeg(quality) = options.compression {
+        let name = to_cstring("jpg_quality")?;
+        let quality = to_cstring(&quality.clamp(1, 100).to_string())?;
+        unsafe { TessBaseAPISetVariable(api.0, name.as_ptr(), quality.as_ptr()) };
+    }
+    let output_base = to_cstring(output_base)?;
+    let title = to_cstring(options.title.as_deref().unwrap_or_default())?;
+    let renderer = RawRenderer(unsafe {
+        TessPDFRendererCreate(
+            output_base.as_ptr(),
+            TessBaseAPIGetDatapath(api.0),
+            options.text_only as i32,
+        )
+    });
+    if renderer.0.is_null()
+        || unsafe { TessResultRendererBeginDocument(renderer.0, title.as_ptr()) } == 0
+    {
+        return Err(TesseractError::PdfError);
+    }
+    for image in image_array.iter() {
+        let pix = RawPix::read(image)?;
+        let filename = to_cstring(image)?;
+        unsafe {
+            TessBaseAPISetImage2(api.0, pix.0);
+            TessBaseAPISetSourceResolution(api.0, tesseract.dpi);
+            if TessBaseAPIRecognize(api.0, ptr::null_mut()) != 0 {
+                return Err(TesseractError::TesseracRuntimeError);
+            }
+        }
+        // The renderer stores the input image, or the original file if the input name is set.
+        // Tesseract takes ownership of the input image, so it gets a clone and RawPix keeps its own reference.
+        let thresholded = match options.compression {
+            PdfImageCompression::Bilevel => {
+                Some(RawPix(unsafe { TessBaseAPIGetThresholdedImage(api.0) }))
+            }
+            _ => None,
+        };
+        unsafe {
+            match (&thresholded, options.compression) {
+                (Some(thresholded), _) if !thresholded.is_null() => {
+                    TessBaseAPISetInputName(api.0, ptr::null());
+                    TessBaseAPISetInputImage(api.0, pixClone(thresholded.0));
+                }
+                (_, PdfImageCompression::Auto) => {
+                    TessBaseAPISetInputName(api.0, filename.as_ptr());
+                    TessBaseAPISetInputImage(api.0, pixClone(pix.0));
+                }
+                _ => {
+                    TessBaseAPISetInputName(api.0, ptr::null());
+                    TessBaseAPISetInputImage(api.0, pixClone(pix.0));
+                }
+            }
+            if TessResultRendererAddImage(renderer.0, api.0) == 0 {
+                return Err(TesseractError::PdfError);
+            }
+        }
+    }
+    if unsafe { TessResultRendererEndDocument(renderer.0) } == 0 {
+        return Err(TesseractError::PdfError);
+    }
+    drop(renderer);
+    write_metadata(&output, options)?;
+    Ok(output)
+}
+
+fn get_text_string(value: &str) -> Object {
+    // PDF text string, non ASCII text is written as UTF-16BE with a byte order mark.
+    if value.is_ascii() {
+        return Object::String(value.as_bytes().to_vec(), StringFormat::Literal);
+    }
+    let mut bytes = vec![0xfe, 0xff];
+    bytes.extend(value.encode_utf16().flat_map(u16::to_be_bytes));
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+pub fn write_metadata(path: &Path, options: &PdfRendererOptions) -> Result<(), TesseractError> {
+    // Set the title, author and dates of a PDF file, if options.pdfa is set add the PDF/A-2b
+    // identification (XMP metadata), an RGB output intent and a document ID.
+    let mut document = Document::load(path).map_err(|_| TesseractError::PdfError)?;
+    let (year, month, day, hour, minute, second) =
+        get_utc_time(options.creation_date.unwrap_or_else(SystemTime::now));
+    let producer = format!("Tesseract {}", pl::version().to_string_lossy());
+    let pdf_date = format!(
+        "D:{:04}{:02}{:02}{:02}{:02}{:02}+00'00'",
+        year, month, day, hour, minute, second
+    );
+    let mut info = dictionary! {
+        "Producer" => get_text_string(&producer),
+        "Creator" => get_text_string("rusttesserast"),
+        "CreationDate" => Object::string_literal(pdf_date.as_str()),
+        "ModDate" => Object::string_literal(pdf_date),
+    };
+    if let Some(title) = options.title.as_deref() {
+        info.set("Title", get_text_string(title));
+    }
+    if let Some(author) = options.author.as_deref() {
+        info.set("Author", get_text_string(author));
+    }
+    let info_id = document.add_object(info);
+    document.trailer.set("Info", info_id);
+
+    if options.pdfa {
+        let date = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}+00:00",
+            year, month, day, hour, minute, second
+        );
+        let metadata = document.add_object(Stream::new(
+            dictionary! {"Type" => "Metadata", "Subtype" => "XML"},
+            get_xmp(options, &producer, &date).into_bytes(),
+        ));
+        let profile = document.add_object(Stream::new(dictionary! {"N" => 3}, get_rgb_profile()));
+        // The embedded profile is not the registered sRGB one, so the condition is Custom
+        // and Info describes the profile.
+        let output_intent = dictionary! {
+            "Type" => "OutputIntent",
+            "S" => "GTS_PDFA1",
+            "OutputConditionIdentifier" => Object::string_literal("Custom"),
+            "Info" => Object::string_literal(RGB_PROFILE_DESCRIPTION),
+            "DestOutputProfile" => profile,
+        };
+        let catalog = document
+            .catalog_mut()
+            .map_err(|_| TesseractError::PdfError)?;
+        catalog.set("Metadata", metadata);
+        catalog.set("OutputIntents", vec![Object::Dictionary(output_intent)]);
+        let id = Sha256::digest(format!("{}{}{:?}", date, producer, options.title).as_bytes());
+        let id = Object::String(id[..16].to_vec(), StringFormat::Hexadecimal);
+        document.trailer.set("ID", vec![id.clone(), id]);
+    }
+    document
+        .save(path)
+        .map_err(|_| TesseractError::SaveDocError)?;
+    Ok(())
+}
+
+fn get_xmp(options: &PdfRendererOptions, producer: &str, date: &str) -> String {
+    let escape = crate::xml::escape_xml;
+    let mut properties = String::new();
+    if let Some(title) = options.title.as_deref() {
+        properties.push_str(&format!(
+            "   <dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>\n",
+            escape(title)
+        ));
+    }
+    if let Some(author) = options.author.as_deref() {
+        properties.push_str(&format!(
+            "   <dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>\n",
+            escape(author)
+        ));
+    }
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n \
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  \
+         <rdf:Description rdf:about=\"\" \
+         xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" \
+         xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n   \
+         <pdfaid:part>2</pdfaid:part>\n   \
+         <pdfaid:conformance>B</pdfaid:conformance>\n\
+         {}   \
+         <xmp:CreateDate>{}</xmp:CreateDate>\n   \
+         <xmp:ModifyDate>{}</xmp:ModifyDate>\n   \
+         <xmp:CreatorTool>rusttesserast</xmp:CreatorTool>\n   \
+         <pdf:Producer>{}</pdf:Producer>\n  \
+         </rdf:Description>\n \
+         </rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>",
+        properties,
+        date,
+        date,
+        escape(producer)
+    )
+}
+
+fn get_rgb_profile() -> Vec<u8> {
+    // Minimal ICC v2 display profile with sRGB primaries (D50 adapted) and a 2.2 gamma.
+    let fixed = |value: f64| ((value * 65536.0).round() as i32).to_be_bytes();
+    let xyz = |x: f64, y: f64, z: f64| {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for value in [x, y, z] {
+            tag.extend(fixed(value));
+        }
+        tag
+    };
+    let text = |value: &str| {
+        let mut tag = b"text\0\0\0\0".to_vec();
+        tag.extend(value.as_bytes());
+        tag.push(0);
+        tag
+    };
+    let mut description = b"desc\0\0\0\0".to_vec();
+    description.extend((RGB_PROFILE_DESCRIPTION.len() as u32 + 1).to_be_bytes());
+    description.extend(RGB_PROFILE_DESCRIPTION.as_bytes());
+    description.push(0);
+    description.extend([0; 8]);
+    description.extend([0; 3 + 67]);
+    let curve = [
+        b"curv\0\0\0\0".as_slice(),
+        &1u32.to_be_bytes(),
+        &[0x02, 0x33],
+    ]
+    .concat();
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", description),
+        (b"cprt", text("No copyright, use freely")),
+        (b"wtpt", xyz(0.9642, 1.0, 0.8249)),
+        (b"rXYZ", xyz(0.4361, 0.2225, 0.0139)),
+        (b"gXYZ", xyz(0.3851, 0.7169, 0.0971)),
+        (b"bXYZ", xyz(0.1431, 0.0606, 0.7141)),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve),
+    ];
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let data_offset = 128 + 4 + 12 * tags.len();
+    for (signature, tag) in tags.iter() {
+        table.extend(*signature);
+        table.extend(((data_offset + data.len()) as u32).to_be_bytes());
+        table.extend((tag.len() as u32).to_be_bytes());
+        data.extend(tag);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+    let mut profile = Vec::with_capacity(data_offset + data.len());
+    profile.extend(((data_offset + data.len()) as u32).to_be_bytes());
+    profile.extend([0; 4]);
+    profile.extend([0x02, 0x10, 0x00, 0x00]);
+    profile.extend(b"mntrRGB XYZ ");
+    profile.extend([0; 12]);
+    profile.extend(b"acsp");
+    profile.extend([0; 24]);
+    profile.extend([0; 4]);
+    profile.extend(fixed(0.9642));
+    profile.extend(fixed(1.0));
+    profile.extend(fixed(0.8249));
+    profile.resize(128, 0);
+    profile.extend(table);
+    profile.extend(data);
+    profile
+}
@@ -0,0 +1,36 @@
+//! Owned leptonica images for the functions which call leptonica directly.
+
+use crate::errors::TesseractError;
+use pl::leptonica_sys::{pixDestroy, pixRead, Pix};
+use std::ffi::CString;
+use std::path::Path;
+use tesseract_plumbing as pl;
+
+/// RawPix destroys the image when it is dropped.
+pub(crate) struct RawPix(pub(crate) *mut Pix);
+
+impl Drop for RawPix {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { pixDestroy(&mut self.0) }
+        }
+    }
+}
+
+impl RawPix {
+    pub(crate) fn read(filename: &str) -> Result<RawPix, TesseractError> {
+        if !Path::new(filename).exists() {
+            return Err(TesseractError::NoSuchFileException);
+        }
+        let filename = CString::new(filename).map_err(|_| TesseractError::NoSuchFileException)?;
+        let pix = RawPix(unsafe { pixRead(filename.as_ptr()) });
+        if pix.0.is_null() {
+            return Err(TesseractError::InvalidImageError);
+        }
+        Ok(pix)
+    }
+
+    pub(crate) fn is_null(&self) -> bool {
+        self.0.is_null()
+    }
+}
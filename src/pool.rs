@@ -0,0 +1,166 @@
+//! Pool of initialized `TesseractApi` engines.
+//!
+//! Tesseract engines are expensive to initialize and can not be shared between threads,
+//! so the pool keeps idle engines per language and runs every job on a blocking thread
+//! with the configured timeout.
+
+use crate::constanst::TesseractDefaultConstants;
+use crate::errors::TesseractError;
+use crate::tess_lib::TesseractApi;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Clone)]
+/// EngineConfig describes how new engines are created.
+pub struct EngineConfig {
+    /// Source image resolution. Default value - 300
+    pub dpi: i32,
+    /// Tesseract Page Segmentation Mode. Default value - 4
+    pub psm: u32,
+    /// Tesseract Engine Mode. Default value - 3
+    pub oem: u32,
+    /// Timeout for a single job in seconds. Default value - 30
+    pub timeout: i32,
+    /// Path to the tessdata directory.
+    pub datapath: Option<String>,
+    /// Tesseract languages, e.g. eng+rus. Default value - eng
+    pub lang: String,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            dpi: TesseractDefaultConstants::DEFAULT_DPI,
+            psm: TesseractDefaultConstants::DEFAULT_PSM,
+            oem: TesseractDefaultConstants::DEFAULT_OEM,
+            timeout: TesseractDefaultConstants::DEFAULT_TIMEOUT,
+            datapath: None,
+            lang: String::from("eng"),
+        }
+    }
+}
+
+impl EngineConfig {
+    pub fn create(&self, lang: Option<&str>) -> Result<TesseractApi, TesseractError> {
+        // Create a new engine. lang - optional languages overriding the configured ones.
+        TesseractApi::new(
+            Some(TesseractApi {
+                dpi: self.dpi,
+                psm: self.psm,
+                oem: self.oem,
+                timeout: self.timeout,
+                ..Default::default()
+            }),
+            self.datapath.as_deref(),
+            Some(lang.unwrap_or(&self.lang)),
+        )
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.max(1) as u64)
+    }
+}
+
+/// EnginePool runs jobs on at most `size` engines at a time.
+pub struct EnginePool {
+    config: EngineConfig,
+    size: usize,
+    permits: Arc<Semaphore>,
+    idle: Arc<Mutex<HashMap<String, Vec<TesseractApi>>>>,
+}
+
+impl EnginePool {
+    pub fn new(config: EngineConfig, size: usize) -> EnginePool {
+        let size = size.max(1);
+        EnginePool {
+            config,
+            size,
+            permits: Arc::new(Semaphore::new(size)),
+            idle: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn get_config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_idle(&self) -> usize {
+        self.idle.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    pub async fn run<T, F>(
+        &self,
+        lang: Option<&str>,
+        psm: Option<u32>,
+        job: F,
+    ) -> Result<T, TesseractError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut TesseractApi) -> Result<T, TesseractError> + Send + 'static,
+    {
+        // Run a job on an idle engine of the requested language or on a new one.
+        // lang - optional languages overriding the configured ones.
+        // psm - optional page segmentation mode for this job only.
+        // If the job exceeds the timeout, TesseractTimeoutError is returned right away,
+        // but the engine slot stays busy until the job finishes.
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| TesseractError::TesseracRuntimeError)?;
+        let lang = lang.unwrap_or(&self.config.lang).to_string();
+        let engine = self.idle.lock().unwrap().get_mut(&lang).and_then(Vec::pop);
+        let config = self.config.clone();
+        let idle = self.idle.clone();
+        let size = self.size;
+        let handle = tokio::task::spawn_blocking(move || {
+            let mut engine = match engine {
+                Some(engine) => engine,
+                None => config.create(Some(&lang))?,
+            };
+            let psm = psm.unwrap_or(config.psm);
+            engine.psm = psm;
+            engine.tess_pl.set_page_seg_mode(psm);
+            let result = job(&mut engine);
+            release_engine(&idle, size, lang, engine);
+            drop(permit);
+            result
+        });
+        match tokio::time::timeout(self.config.get_timeout(), handle).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(TesseractError::TesseracRuntimeError),
+            Err(_) => Err(TesseractError::TesseractTimeoutError),
+        }
+    }
+}
+
+fn release_engine(
+    idle: &Mutex<HashMap<String, Vec<TesseractApi>>>,
+    size: usize,
+    lang: String,
+    engine: TesseractApi,
+) {
+    // Keep at most `size` idle engines, engines of other languages are dropped first.
+    let mut idle = idle.lock().unwrap();
+    let total: usize = idle.values().map(Vec::len).sum();
+    if total >= size {
+        let other_lang = idle
+            .iter()
+            .find(|(idle_lang, engines)| **idle_lang != lang && !engines.is_empty())
+            .map(|(idle_lang, _)| idle_lang.clone());
+        match other_lang {
+            Some(other_lang) => {
+                idle.get_mut(&other_lang).unwrap().pop();
+            }
+            None => return,
+        }
+    }
+    idle.entry(lang).or_default().push(engine);
+}
@@ -0,0 +1,198 @@
+//! Reading order of blocks.
+//!
+//! Tesseract returns the blocks of a page in the order of its layout analysis, which sometimes interleaves
+//! the columns of newspapers and multi-column reports. `order_pages` sorts the blocks of every page in reading
+//! order (recursive XY-cut):
+//! 1. Columns - blocks are split at vertical gaps of at least `min_column_gap` pixels not crossed by any block.
+//!    Columns are read left to right, or right to left for RTL languages (see `TextDirection::from_lang`).
+//! 2. Rows - blocks which can't be split into columns (e.g. a headline over several columns) are split at
+//!    horizontal gaps into rows read top to bottom. Adjacent rows are merged while they still have a common
+//!    column gap, so the paragraphs of a column are not interleaved with the paragraphs of the next column.
+//! 3. Blocks which can't be split are read top to bottom, then in the direction of the text.
+//!
+//! The `order` of every block is set to its position, paragraphs, lines and words keep their order.
+//! Text and other outputs written from the ordered pages (see `convert::write_doc`) follow the reading order.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::file_types::OutputFileFormat;
+//! use rusttesserast::reading_order::ReadingOrderOptions;
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let text = tesseract_base.image_to_reading_order("scans/newspaper.png", &OutputFileFormat::TXT, &ReadingOrderOptions::default()).await.unwrap();
+//!     println!("{}", text);
+//! }
+//! ```
+
+use crate::model::{BoundingBox, Page};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Tesseract languages and scripts written right to left.
+const RTL_LANGS: [&str; 14] = [
+    "ara",
+    "div",
+    "fas",
+    "heb",
+    "pus",
+    "snd",
+    "syr",
+    "uig",
+    "urd",
+    "yid",
+    "script/Arabic",
+    "script/Hebrew",
+    "script/Syriac",
+    "script/Thaana",
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// TextDirection is the direction columns and blocks of a row are read in.
+pub enum TextDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+impl TextDirection {
+    pub fn from_lang(lang: &str) -> TextDirection {
+        // Direction of the first language, e.g. RightToLeft for ara+eng.
+        match RTL_LANGS.contains(&lang.split('+').next().unwrap_or_default()) {
+            true => TextDirection::RightToLeft,
+            false => TextDirection::LeftToRight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// ReadingOrderOptions describe how blocks are ordered.
+pub struct ReadingOrderOptions {
+    /// If it is None, `TesseractApi::image_to_reading_order` takes the direction of the first language of the api,
+    /// `order_pages` reads left to right. Default value - None
+    pub direction: Option<TextDirection>,
+    /// Narrowest gap between columns in pixels. Default value - 10
+    pub min_column_gap: u32,
+}
+
+impl Default for ReadingOrderOptions {
+    fn default() -> Self {
+        ReadingOrderOptions {
+            direction: None,
+            min_column_gap: 10,
+        }
+    }
+}
+
+pub fn order_pages(pages: &mut [Page], options: &ReadingOrderOptions) {
+    for page in pages.iter_mut() {
+        order_page(page, options);
+    }
+}
+
+pub fn order_page(page: &mut Page, options: &ReadingOrderOptions) {
+    // Sort the blocks of a page in reading order and set their order.
+    let boxes: Vec<BoundingBox> = page.blocks.iter().map(|block| block.bbox).collect();
+    let mut blocks: Vec<_> = page.blocks.drain(..).map(Some).collect();
+    for (order, index) in get_reading_order(&boxes, options).into_iter().enumerate() {
+        if let Some(mut block) = blocks[index].take() {
+            block.order = Some(order);
+            page.blocks.push(block);
+        }
+    }
+}
+
+pub fn get_reading_order(boxes: &[BoundingBox], options: &ReadingOrderOptions) -> Vec<usize> {
+    // Indexes of block boxes in reading order.
+    get_order(
+        boxes,
+        (0..boxes.len()).collect(),
+        options.direction.unwrap_or_default(),
+        options.min_column_gap,
+    )
+}
+
+fn get_order(
+    boxes: &[BoundingBox],
+    mut indexes: Vec<usize>,
+    direction: TextDirection,
+    min_gap: u32,
+) -> Vec<usize> {
+    if indexes.len() < 2 {
+        return indexes;
+    }
+    let mut groups = get_columns(boxes, &indexes, min_gap);
+    if groups.len() > 1 {
+        if direction == TextDirection::RightToLeft {
+            groups.reverse();
+        }
+    } else {
+        groups = get_rows(boxes, &indexes, min_gap);
+    }
+    if groups.len() > 1 {
+        return groups
+            .into_iter()
+            .flat_map(|group| get_order(boxes, group, direction, min_gap))
+            .collect();
+    }
+    indexes.sort_by_key(|&index| {
+        let bbox = &boxes[index];
+        match direction {
+            TextDirection::LeftToRight => (bbox.top, bbox.left as i64),
+            TextDirection::RightToLeft => (bbox.top, -(bbox.right as i64)),
+        }
+    });
+    indexes
+}
+
+fn get_columns(boxes: &[BoundingBox], indexes: &[usize], min_gap: u32) -> Vec<Vec<usize>> {
+    split(indexes, min_gap, |index| {
+        (boxes[index].left, boxes[index].right)
+    })
+}
+
+fn get_rows(boxes: &[BoundingBox], indexes: &[usize], min_gap: u32) -> Vec<Vec<usize>> {
+    // Rows separated by horizontal gaps, adjacent rows with a common column gap are merged.
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    for row in split(indexes, 0, |index| (boxes[index].top, boxes[index].bottom)) {
+        if let Some(last) = rows.last_mut() {
+            let merged = [last.as_slice(), row.as_slice()].concat();
+            if get_columns(boxes, &merged, min_gap).len() > 1 {
+                *last = merged;
+                continue;
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+fn split(
+    indexes: &[usize],
+    min_gap: u32,
+    get_range: impl Fn(usize) -> (u32, u32),
+) -> Vec<Vec<usize>> {
+    // Groups of blocks separated by gaps of at least min_gap along an axis, ordered along the axis.
+    let mut sorted = indexes.to_vec();
+    sorted.sort_by_key(|&index| get_range(index));
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_end: u32 = 0;
+    for index in sorted {
+        let (start, end) = get_range(index);
+        match groups.last_mut() {
+            Some(group) if start < group_end.saturating_add(min_gap) => {
+                group.push(index);
+                group_end = group_end.max(end);
+            }
+            _ => {
+                groups.push(vec![index]);
+                group_end = end;
+            }
+        }
+    }
+    groups
+}
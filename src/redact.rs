@@ -0,0 +1,329 @@
+//! Redaction of scans.
+//!
+//! `redact_image` recognizes an image, finds the words matching the redaction patterns (regexes, word lists
+//! or entities like emails and phone numbers, see `RedactionPattern`) and paints opaque black rectangles
+//! over every matched line on a copy of the image. The redacted copy is recognized again and redacted until
+//! no pattern matches, so the text recognized from the output (e.g. the text layer of `redact_image_to_pdf`)
+//! doesn't contain the redacted text. The source image is not changed.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::redact::{RedactionEntity, RedactionOptions, RedactionPattern};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let options = RedactionOptions {
+//!         patterns: vec![
+//!             RedactionPattern::Entity(RedactionEntity::Email),
+//!             RedactionPattern::Words(vec![String::from("John Smith")]),
+//!         ],
+//!         ..Default::default()
+//!     };
+//!     let redactions = tesseract_base.redact_image("scans/letter.png", "redacted/letter.png", &options).await.unwrap();
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::hocr::parse_hocr;
+use crate::model::{BoundingBox, Page};
+#[cfg(feature = "pdf")]
+use crate::pdf_renderer::{render_pdf, PdfRendererOptions};
+use crate::pix::RawPix;
+use crate::search::{search_pages, SearchMatch, SearchMode, SearchOptions};
+use crate::tess_lib::TesseractApi;
+#[cfg(feature = "pdf")]
+use pl::leptonica_sys::pixWrite;
+use pl::leptonica_sys::{
+    boxCreate, boxDestroy, l_int32, lept_free, pixCopy, pixGetColormap, pixGetDepth,
+    pixRemoveColormap, pixSetInRectArbitrary, pixWriteImpliedFormat, pixWriteMem, IFF_PNG,
+    REMOVE_CMAP_BASED_ON_SRC,
+};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::path::Path;
+use std::ptr;
+use std::sync::OnceLock;
+use tesseract_plumbing as pl;
+
+/// Maximal number of recognitions of a redacted image which still matches the patterns.
+const MAX_PASSES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// RedactionEntity is a kind of personal data found by a built-in pattern.
+pub enum RedactionEntity {
+    Email,
+    /// Phone number of 8-15 digits in groups separated by spaces, `.` or `-`, with an optional
+    /// +country code and (area code), or + followed by the digits. Dates and decimal amounts are not matched.
+    Phone,
+    Iban,
+    /// Payment card number of 16 digits in groups of 4.
+    CardNumber,
+    /// Date as YYYY-MM-DD or DD.MM.YYYY, the separator is `-`, `.` or `/`.
+    Date,
+}
+
+impl RedactionEntity {
+    pub fn get_pattern(&self) -> &'static str {
+        match self {
+            RedactionEntity::Email => r"[\w.+-]+@[\w-]+(\.[\w-]+)+",
+            RedactionEntity::Phone => {
+                r"(\+\d{1,3}[ .-]?(\(\d{1,4}\)[ .-]?)?|\(\d{1,4}\)[ .-]?|\b)\d{2,4}([ .-]\d{2,4}){1,4}([.,]\d+)?\b|\+\d{8,15}\b"
+            }
+            RedactionEntity::Iban => r"\b[A-Z]{2}\d{2}( ?[A-Z0-9]{4}){2,7}( ?[A-Z0-9]{1,4})?\b",
+            RedactionEntity::CardNumber => r"\b\d{4}([ -]?\d{4}){3}\b",
+            RedactionEntity::Date => {
+                r"\b(\d{4}[-./]\d{1,2}[-./]\d{1,2}|\d{1,2}[-./]\d{1,2}[-./]\d{4})\b"
+            }
+        }
+    }
+
+    pub fn is_valid(&self, text: &str) -> bool {
+        // Check a match of the pattern, phone numbers must not look like dates or amounts.
+        static DATE_REGEX: OnceLock<Regex> = OnceLock::new();
+        match self {
+            RedactionEntity::Phone => {
+                let date_regex = DATE_REGEX
+                    .get_or_init(|| Regex::new(RedactionEntity::Date.get_pattern()).unwrap());
+                let digits = text.chars().filter(char::is_ascii_digit).count();
+                (8..=15).contains(&digits) && !date_regex.is_match(text) && !is_decimal(text)
+            }
+            _ => true,
+        }
+    }
+}
+
+fn is_decimal(text: &str) -> bool {
+    // An amount like 1 234.56 or 12.345.678,90: the last separator is a decimal point or comma
+    // used once and followed by one or two decimals. Punctuation around the words is ignored.
+    let text = text.trim_matches(|char: char| !char.is_ascii_digit());
+    let separators: Vec<(usize, char)> = text
+        .char_indices()
+        .filter(|(_, char)| !char.is_ascii_digit())
+        .collect();
+    match separators.last() {
+        Some((index, separator @ ('.' | ','))) => {
+            let count = separators
+                .iter()
+                .filter(|(_, other)| other == separator)
+                .count();
+            count == 1 && text.len() - index - 1 <= 2
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// RedactionPattern selects the text to redact.
+pub enum RedactionPattern {
+    /// Regular expression matched against the words of a page joined with spaces.
+    Regex(String),
+    /// Words or phrases matched case-insensitively as whole words.
+    Words(Vec<String>),
+    Entity(RedactionEntity),
+}
+
+impl RedactionPattern {
+    pub fn get_regex(&self) -> Result<Option<Regex>, TesseractError> {
+        // Regex of the pattern, None for an empty word list.
+        let pattern = match self {
+            RedactionPattern::Regex(pattern) => pattern.clone(),
+            RedactionPattern::Words(words) => {
+                let words: Vec<String> = words
+                    .iter()
+                    .map(|word| {
+                        word.split_whitespace()
+                            .map(regex::escape)
+                            .collect::<Vec<String>>()
+                            .join(r"\s+")
+                    })
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                if words.is_empty() {
+                    return Ok(None);
+                }
+                format!(r"(?i)\b({})\b", words.join("|"))
+            }
+            RedactionPattern::Entity(entity) => entity.get_pattern().to_string(),
+        };
+        Regex::new(&pattern)
+            .map(Some)
+            .map_err(|_| TesseractError::ParseError)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// RedactionOptions describe what is redacted.
+pub struct RedactionOptions {
+    pub patterns: Vec<RedactionPattern>,
+    /// Pixels added around the boxes of the matched words. Default value - 2
+    pub margin: u32,
+}
+
+impl Default for RedactionOptions {
+    fn default() -> Self {
+        RedactionOptions {
+            patterns: Vec::new(),
+            margin: 2,
+        }
+    }
+}
+
+pub fn find_redactions(
+    pages: &[Page],
+    options: &RedactionOptions,
+) -> Result<Vec<SearchMatch>, TesseractError> {
+    // Matches of all patterns in recognized pages, see `search::search_pages`.
+    let search_options = SearchOptions {
+        mode: SearchMode::Regex,
+        ..Default::default()
+    };
+    let mut redactions = Vec::new();
+    for pattern in options.patterns.iter() {
+        if let Some(regex) = pattern.get_regex()? {
+            let mut matches = search_pages(pages, regex.as_str(), &search_options)?;
+            if let RedactionPattern::Entity(entity) = pattern {
+                matches.retain(|found| entity.is_valid(&found.text));
+            }
+            redactions.extend(matches);
+        }
+    }
+    Ok(redactions)
+}
+
+pub fn redact_image(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    output_path: &str,
+    options: &RedactionOptions,
+) -> Result<Vec<SearchMatch>, TesseractError> {
+    // Redact an image and save it, the format is chosen by the extension of output_path.
+    let (pix, redactions) = redact(tesseract, filename, options)?;
+    let output_path = get_output_path(output_path)?;
+    if unsafe { pixWriteImpliedFormat(output_path.as_ptr(), pix.0, 0, 0) } != 0 {
+        return Err(TesseractError::SaveDocError);
+    }
+    Ok(redactions)
+}
+
+#[cfg(feature = "pdf")]
+pub fn redact_image_to_pdf(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    output_path: &str,
+    options: &RedactionOptions,
+    pdf_options: &PdfRendererOptions,
+) -> Result<Vec<SearchMatch>, TesseractError> {
+    // Redact an image and save it as a PDF, see `pdf_renderer::render_pdf`.
+    // The text layer is recognized from the redacted image.
+    let (pix, redactions) = redact(tesseract, filename, options)?;
+    let image = std::env::temp_dir().join(format!(
+        "rusttesserast-redact-{}-{}.png",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos())
+    ));
+    let image = image.to_string_lossy().into_owned();
+    let c_image = CString::new(image.as_str()).map_err(|_| TesseractError::SaveDocError)?;
+    if unsafe { pixWrite(c_image.as_ptr(), pix.0, IFF_PNG as l_int32) } != 0 {
+        return Err(TesseractError::SaveDocError);
+    }
+    let pdf = render_pdf(tesseract, &[image.as_str()], output_path, pdf_options);
+    let _ = std::fs::remove_file(&image);
+    pdf.map(|_| redactions)
+}
+
+fn redact(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    options: &RedactionOptions,
+) -> Result<(RawPix, Vec<SearchMatch>), TesseractError> {
+    // Paint the matches on a copy of the image until its recognition doesn't match the patterns.
+    let source = RawPix::read(filename)?;
+    let pix = RawPix(unsafe {
+        match pixGetColormap(source.0).is_null() {
+            true => pixCopy(ptr::null_mut(), source.0),
+            // Colors of a colormap are replaced, so black is painted as black.
+            false => pixRemoveColormap(source.0, REMOVE_CMAP_BASED_ON_SRC as l_int32),
+        }
+    });
+    if pix.is_null() {
+        return Err(TesseractError::InvalidImageError);
+    }
+    tesseract.set_image(filename)?;
+    let mut redactions = Vec::new();
+    for pass in 0..=MAX_PASSES {
+        if pass > 0 {
+            tesseract.set_image_from_mem(&get_png(&pix)?)?;
+        }
+        let hocr = tesseract.get_output(&OutputFileFormat::HOCR)?;
+        let matches = find_redactions(&parse_hocr(&hocr)?, options)?;
+        if matches.is_empty() {
+            return Ok((pix, redactions));
+        }
+        for found in matches.iter() {
+            paint_boxes(&pix, &found.line_boxes, options.margin)?;
+        }
+        redactions.extend(matches);
+    }
+    Err(TesseractError::RedactionError)
+}
+
+fn paint_boxes(pix: &RawPix, boxes: &[BoundingBox], margin: u32) -> Result<(), TesseractError> {
+    // Black is 1 in binary images, 0 in grayscale images and RGBA 0, 0, 0, 255 in 32 bpp images,
+    // so the alpha channel of RGBA images stays opaque.
+    let black = match unsafe { pixGetDepth(pix.0) } {
+        1 => 1,
+        32 => 0xff,
+        _ => 0,
+    };
+    for bbox in boxes {
+        let mut rect = unsafe {
+            boxCreate(
+                bbox.left.saturating_sub(margin) as l_int32,
+                bbox.top.saturating_sub(margin) as l_int32,
+                (bbox.get_width() + 2 * margin) as l_int32,
+                (bbox.get_height() + 2 * margin) as l_int32,
+            )
+        };
+        if rect.is_null() {
+            return Err(TesseractError::InvalidImageError);
+        }
+        let result = unsafe { pixSetInRectArbitrary(pix.0, rect, black) };
+        unsafe { boxDestroy(&mut rect) };
+        if result != 0 {
+            return Err(TesseractError::InvalidImageError);
+        }
+    }
+    Ok(())
+}
+
+fn get_png(pix: &RawPix) -> Result<Vec<u8>, TesseractError> {
+    let mut data = ptr::null_mut();
+    let mut size = 0;
+    if unsafe { pixWriteMem(&mut data, &mut size, pix.0, IFF_PNG as l_int32) } != 0
+        || data.is_null()
+    {
+        return Err(TesseractError::InvalidImageError);
+    }
+    let png = unsafe { std::slice::from_raw_parts(data, size) }.to_vec();
+    unsafe { lept_free(data.cast()) };
+    Ok(png)
+}
+
+fn get_output_path(output_path: &str) -> Result<CString, TesseractError> {
+    if let Some(parent) = Path::new(output_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).map_err(|_| TesseractError::SaveDocError)?;
+        }
+    }
+    CString::new(output_path).map_err(|_| TesseractError::SaveDocError)
+}
@@ -0,0 +1,284 @@
+//! Text search.
+//!
+//! `search_pages` finds a phrase in recognized pages and returns the boxes of the matched words, e.g. for
+//! highlighting. The words of a page are joined with single spaces in reading order, so a match can span
+//! several words, lines and paragraphs. Search modes:
+//! 1. Exact - the query with whitespace reduced to single spaces.
+//! 2. IgnoreCase - the same, letters are compared in lowercase.
+//! 3. Regex - a regular expression, use `(?i)` for case-insensitive patterns.
+//! 4. Fuzzy - runs of words whose lowercase text is within `max_distance` edits (Levenshtein) of the query,
+//!    runs can have one word more or less than the query.
+//!
+//! Matches don't overlap. A match covers the whole words it touches, every match has a box per line.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::search::{SearchMode, SearchOptions};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let options = SearchOptions { mode: SearchMode::IgnoreCase, ..Default::default() };
+//!     let matches = tesseract_base.search_images(&["scans/page1.png", "scans/page2.png"], "total amount", &options).await.unwrap();
+//!     for found in matches.iter() {
+//!         println!("image {:?}: {} {:?}", found.image, found.text, found.bbox);
+//!     }
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::model::{BoundingBox, Page};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// SearchMode is the way a query is compared to the page text.
+pub enum SearchMode {
+    #[default]
+    Exact,
+    IgnoreCase,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone)]
+/// SearchOptions describe how pages are searched.
+pub struct SearchOptions {
+    /// Default value - Exact
+    pub mode: SearchMode,
+    /// Maximal number of edits of a fuzzy match. Default value - 2
+    pub max_distance: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            mode: SearchMode::Exact,
+            max_distance: 2,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// SearchMatch is a found phrase.
+pub struct SearchMatch {
+    /// Zero-based index of the page in the searched pages. The pages of all images of
+    /// `TesseractApi::search_images` are searched together, a multi-page image has several pages.
+    pub page: usize,
+    /// Zero-based index of the image of `TesseractApi::search_images`, None for `search_pages`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub image: Option<usize>,
+    /// Matched words joined with spaces.
+    pub text: String,
+    /// Union of the word boxes of the match.
+    pub bbox: BoundingBox,
+    /// Union of the word boxes of every matched line.
+    pub line_boxes: Vec<BoundingBox>,
+    /// Number of edits of a fuzzy match, 0 for other modes.
+    pub distance: usize,
+}
+
+/// Word of the page text, start and end are byte offsets of the word.
+struct Entry<'a> {
+    start: usize,
+    end: usize,
+    line: usize,
+    text: &'a str,
+    bbox: BoundingBox,
+}
+
+pub fn search_pages(
+    pages: &[Page],
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<SearchMatch>, TesseractError> {
+    let regex = get_regex(query, options)?;
+    Ok(find_matches(pages, query, regex.as_ref(), options))
+}
+
+pub(crate) fn get_regex(
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Option<Regex>, TesseractError> {
+    // The regex of the Regex mode, an invalid regex is a ParseError.
+    match options.mode {
+        SearchMode::Regex => Regex::new(query)
+            .map(Some)
+            .map_err(|_| TesseractError::ParseError),
+        _ => Ok(None),
+    }
+}
+
+pub(crate) fn find_matches(
+    pages: &[Page],
+    query: &str,
+    regex: Option<&Regex>,
+    options: &SearchOptions,
+) -> Vec<SearchMatch> {
+    let ignore_case = options.mode != SearchMode::Exact;
+    let query = normalize(query, ignore_case);
+    let mut matches = Vec::new();
+    for (index, page) in pages.iter().enumerate() {
+        let (text, entries) = get_entries(page, ignore_case && regex.is_none());
+        // Word ranges of the matches, end is exclusive.
+        let ranges: Vec<(usize, usize, usize)> = match (regex, options.mode) {
+            (Some(regex), _) => regex
+                .find_iter(&text)
+                .filter_map(|found| get_range(&entries, found.start(), found.end()))
+                .map(|(first, last)| (first, last, 0))
+                .collect(),
+            (None, SearchMode::Fuzzy) => get_fuzzy_ranges(&entries, &query, options.max_distance),
+            (None, _) if query.is_empty() => Vec::new(),
+            (None, _) => text
+                .match_indices(query.as_str())
+                .filter_map(|(start, found)| get_range(&entries, start, start + found.len()))
+                .map(|(first, last)| (first, last, 0))
+                .collect(),
+        };
+        matches.extend(
+            ranges
+                .into_iter()
+                .map(|(first, last, distance)| get_match(index, &entries[first..last], distance)),
+        );
+    }
+    matches
+}
+
+pub fn get_distance(a: &str, b: &str) -> usize {
+    // Levenshtein distance of two strings in chars.
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a != *b);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn normalize(text: &str, ignore_case: bool) -> String {
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match ignore_case {
+        true => text.to_lowercase(),
+        false => text,
+    }
+}
+
+fn get_entries(page: &Page, ignore_case: bool) -> (String, Vec<Entry<'_>>) {
+    // Text of the page words joined with spaces and the ranges of the words.
+    let mut text = String::new();
+    let mut entries = Vec::new();
+    for (line_index, line) in page.get_lines().enumerate() {
+        for word in line.words.iter() {
+            let word_text = word.text.trim();
+            if word_text.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            let start = text.len();
+            text.push_str(&normalize(word_text, ignore_case));
+            entries.push(Entry {
+                start,
+                end: text.len(),
+                line: line_index,
+                text: word_text,
+                bbox: word.bbox,
+            });
+        }
+    }
+    (text, entries)
+}
+
+fn get_range(entries: &[Entry], start: usize, end: usize) -> Option<(usize, usize)> {
+    // Words overlapping a byte range of the page text, None if the range covers only spaces.
+    let first = entries.iter().position(|entry| entry.end > start)?;
+    let last = entries[first..]
+        .iter()
+        .position(|entry| entry.start >= end)
+        .map_or(entries.len(), |count| first + count);
+    match first < last {
+        true => Some((first, last)),
+        false => None,
+    }
+}
+
+fn get_fuzzy_ranges(
+    entries: &[Entry],
+    query: &str,
+    max_distance: usize,
+) -> Vec<(usize, usize, usize)> {
+    // Runs of words closest to the query, a run starts after the previous match.
+    let length = query.split(' ').filter(|word| !word.is_empty()).count();
+    if length == 0 {
+        return Vec::new();
+    }
+    let get_best = |first: usize| {
+        // Closest run of one word less to one word more than the query.
+        (length.saturating_sub(1).max(1)..=length + 1)
+            .filter(|count| first + count <= entries.len())
+            .map(|count| {
+                let text = entries[first..first + count]
+                    .iter()
+                    .map(|entry| entry.text)
+                    .collect::<Vec<&str>>()
+                    .join(" ");
+                (get_distance(&normalize(&text, true), query), count)
+            })
+            .min()
+    };
+    let mut ranges = Vec::new();
+    let mut first = 0;
+    while first < entries.len() {
+        // A match is skipped if the run starting at the next word is closer.
+        let next = get_best(first + 1).map(|(distance, _)| distance);
+        match get_best(first) {
+            Some((distance, count))
+                if distance <= max_distance && next.map_or(true, |next| next >= distance) =>
+            {
+                ranges.push((first, first + count, distance));
+                first += count;
+            }
+            _ => first += 1,
+        }
+    }
+    ranges
+}
+
+fn get_match(page: usize, entries: &[Entry], distance: usize) -> SearchMatch {
+    let mut line_boxes: Vec<(usize, BoundingBox)> = Vec::new();
+    for entry in entries {
+        match line_boxes.last_mut() {
+            Some((line, bbox)) if *line == entry.line => *bbox = bbox.union(&entry.bbox),
+            _ => line_boxes.push((entry.line, entry.bbox)),
+        }
+    }
+    SearchMatch {
+        page,
+        image: None,
+        text: entries
+            .iter()
+            .map(|entry| entry.text)
+            .collect::<Vec<&str>>()
+            .join(" "),
+        bbox: entries
+            .iter()
+            .skip(1)
+            .fold(entries[0].bbox, |bbox, entry| bbox.union(&entry.bbox)),
+        line_boxes: line_boxes.into_iter().map(|(_, bbox)| bbox).collect(),
+        distance,
+    }
+}
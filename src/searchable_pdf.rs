@@ -0,0 +1,350 @@
+//! Invisible text layer for existing PDF files.
+//!
+//! `add_text_layer` recognizes the pages of an image-only PDF and writes the same document with
+//! an invisible (render mode 3) text layer, so the scan becomes searchable and selectable.
+//! The original page content, metadata, bookmarks and annotations are kept, the layer is appended
+//! to the page content in its own graphics state. Like Tesseract, the layer uses a glyphless
+//! Identity-H font whose ToUnicode map returns the UTF-16 codes of the recognized text.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let pages = tesseract_base.pdf_to_searchable("scans/contract.pdf", "searchable/contract.pdf").await.unwrap();
+//! }
+//! ```
+
+use crate::engine::OcrEngine;
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::hocr::parse_hocr;
+use crate::model::{Line, Page, Word};
+use crate::pdf::{get_dpi, recognize_pdf, PdfOptions, PdfPageResult};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::path::Path;
+
+const FONT_NAME: &str = "FOCR";
+
+const TO_UNICODE: &str = "/CIDInit /ProcSet findresource begin\n\
+                          12 dict begin\n\
+                          begincmap\n\
+                          /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+                          /CMapName /Adobe-Identify-UCS def\n\
+                          /CMapType 2 def\n\
+                          1 begincodespacerange\n\
+                          <0000> <FFFF>\n\
+                          endcodespacerange\n\
+                          1 beginbfrange\n\
+                          <0000> <FFFF> <0000>\n\
+                          endbfrange\n\
+                          endcmap\n\
+                          CMapName currentdict /CMap defineresource pop\n\
+                          end\n\
+                          end\n";
+
+pub fn add_text_layer<E: OcrEngine + ?Sized>(
+    engine: &mut E,
+    input_path: &str,
+    output_path: &str,
+    options: &PdfOptions,
+) -> Result<Vec<PdfPageResult>, TesseractError> {
+    // Recognize the pages of input_path as hOCR and save the PDF with a text layer to output_path.
+    // Pages which could not be recognized are saved unchanged, their errors are returned in the results.
+    // Pages with a text layer get another one unless options.text_layer is PdfTextLayer::Skip or Reuse.
+    // An encrypted PDF is opened with options.password before recognition and saved decrypted.
+    let document = load_document(input_path, options.password.as_deref())?;
+    let mut results = recognize_pdf(engine, input_path, &OutputFileFormat::HOCR, options)?;
+    let mut pages = Vec::new();
+    for result in results.iter_mut().filter(|result| !result.text_layer) {
+        let hocr = match &result.output {
+            Ok(hocr) => hocr,
+            Err(_) => continue,
+        };
+        // A page with unreadable hOCR is saved unchanged like a page which could not be recognized.
+        match parse_hocr(hocr) {
+            Ok(parsed) => {
+                if let Some(mut page) = parsed.into_iter().next() {
+                    page.page_number = result.page_number - 1;
+                    pages.push(page);
+                }
+            }
+            Err(error) => result.output = Err(error),
+        }
+    }
+    write_layer(document, output_path, &pages, get_dpi(options))?;
+    Ok(results)
+}
+
+pub fn write_text_layer(
+    input_path: &str,
+    output_path: &str,
+    pages: &[Page],
+    dpi: i32,
+) -> Result<(), TesseractError> {
+    // Add the words of recognized pages to a PDF file as invisible text.
+    // pages - pages recognized from images rendered at dpi, page_number is the zero-based PDF page.
+    write_layer(load_document(input_path, None)?, output_path, pages, dpi)
+}
+
+fn load_document(input_path: &str, password: Option<&str>) -> Result<Document, TesseractError> {
+    // Open a PDF file, an encrypted one is decrypted with the password (an empty one if it is None).
+    if !Path::new(input_path).exists() {
+        return Err(TesseractError::NoSuchFileException);
+    }
+    let mut document = Document::load(input_path).map_err(|_| TesseractError::PdfError)?;
+    if document.is_encrypted() {
+        document
+            .decrypt(password.unwrap_or_default())
+            .map_err(|_| TesseractError::PdfError)?;
+    }
+    Ok(document)
+}
+
+fn write_layer(
+    mut document: Document,
+    output_path: &str,
+    pages: &[Page],
+    dpi: i32,
+) -> Result<(), TesseractError> {
+    let page_ids = document.get_pages();
+    if !pages.is_empty() {
+        let font_id = add_font(&mut document);
+        for page in pages {
+            let page_id = *page_ids
+                .get(&(page.page_number as u32 + 1))
+                .ok_or(TesseractError::PdfError)?;
+            let geometry = PageGeometry::new(&document, page_id, dpi)?;
+            let content = write_content(page, &geometry);
+            add_content(&mut document, page_id, font_id, content)?;
+        }
+    }
+    if let Some(parent) = Path::new(output_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent).map_err(|_| TesseractError::SaveDocError)?;
+        }
+    }
+    document
+        .save(output_path)
+        .map_err(|_| TesseractError::SaveDocError)?;
+    Ok(())
+}
+
+fn add_font(document: &mut Document) -> ObjectId {
+    let to_unicode = document.add_object(Stream::new(
+        Dictionary::new(),
+        TO_UNICODE.as_bytes().to_vec(),
+    ));
+    let descriptor = document.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => "GlyphLessFont",
+        "Flags" => 5,
+        "FontBBox" => vec![0.into(), 0.into(), 500.into(), 1000.into()],
+        "ItalicAngle" => 0,
+        "Ascent" => 1000,
+        "Descent" => 0,
+        "CapHeight" => 1000,
+        "StemV" => 80,
+    });
+    let cid_font = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "CIDFontType2",
+        "BaseFont" => "GlyphLessFont",
+        "CIDToGIDMap" => "Identity",
+        "CIDSystemInfo" => dictionary! {
+            "Registry" => Object::string_literal("Adobe"),
+            "Ordering" => Object::string_literal("Identity"),
+            "Supplement" => 0,
+        },
+        "FontDescriptor" => descriptor,
+        "DW" => 500,
+    });
+    document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type0",
+        "BaseFont" => "GlyphLessFont",
+        "Encoding" => "Identity-H",
+        "DescendantFonts" => vec![cid_font.into()],
+        "ToUnicode" => to_unicode,
+    })
+}
+
+fn get_inherited(document: &Document, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+    // Page attributes like MediaBox and Resources can be set on a parent in the page tree.
+    let mut dictionary = document.get_dictionary(page_id).ok()?;
+    loop {
+        if let Ok(value) = dictionary.get(key) {
+            return match value {
+                Object::Reference(id) => document.get_object(*id).ok().cloned(),
+                value => Some(value.clone()),
+            };
+        }
+        let parent = dictionary
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .ok()?;
+        dictionary = document.get_dictionary(parent).ok()?;
+    }
+}
+
+/// PageGeometry maps pixels of a rendered page to PDF user space.
+struct PageGeometry {
+    left: f32,
+    bottom: f32,
+    width: f32,
+    height: f32,
+    rotate: i64,
+    scale: f32,
+}
+
+impl PageGeometry {
+    fn new(document: &Document, page_id: ObjectId, dpi: i32) -> Result<Self, TesseractError> {
+        // Pages are rendered from the crop box (the media box by default) with /Rotate applied.
+        let area = get_inherited(document, page_id, b"CropBox")
+            .or_else(|| get_inherited(document, page_id, b"MediaBox"))
+            .ok_or(TesseractError::PdfError)?;
+        let area = area
+            .as_array()
+            .map_err(|_| TesseractError::PdfError)?
+            .iter()
+            .map(|value| value.as_float().map_err(|_| TesseractError::PdfError))
+            .collect::<Result<Vec<f32>, TesseractError>>()?;
+        if area.len() != 4 {
+            return Err(TesseractError::PdfError);
+        }
+        let rotate = get_inherited(document, page_id, b"Rotate")
+            .and_then(|rotate| rotate.as_i64().ok())
+            .unwrap_or(0)
+            .rem_euclid(360);
+        Ok(PageGeometry {
+            left: area[0].min(area[2]),
+            bottom: area[1].min(area[3]),
+            width: (area[2] - area[0]).abs(),
+            height: (area[3] - area[1]).abs(),
+            rotate,
+            scale: 72.0 / dpi as f32,
+        })
+    }
+
+    fn get_point(&self, x: f32, y: f32) -> (f32, f32) {
+        // Pixel (x, y) from the top left corner of the rendered page to user space.
+        let (u, v) = (x * self.scale, y * self.scale);
+        let (x, y) = match self.rotate {
+            90 => (v, u),
+            180 => (self.width - u, v),
+            270 => (self.width - v, self.height - u),
+            _ => (u, self.height - v),
+        };
+        (self.left + x, self.bottom + y)
+    }
+
+    fn get_direction(&self) -> [f32; 4] {
+        // Text matrix keeping the text horizontal on the displayed page.
+        match self.rotate {
+            90 => [0.0, 1.0, -1.0, 0.0],
+            180 => [-1.0, 0.0, 0.0, -1.0],
+            270 => [0.0, -1.0, 1.0, 0.0],
+            _ => [1.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+fn get_baseline(line: &Line, word: &Word) -> f32 {
+    // hOCR baseline: y = slope * (x - left) + bottom + offset, the bottom of the word without a baseline.
+    match line.baseline {
+        Some((slope, offset)) => {
+            line.bbox.bottom as f32
+                + offset
+                + slope * (word.bbox.left as f32 - line.bbox.left as f32)
+        }
+        None => word.bbox.bottom as f32,
+    }
+}
+
+fn write_content(page: &Page, geometry: &PageGeometry) -> Vec<u8> {
+    let direction = geometry.get_direction();
+    let mut content = String::from("BT\n3 Tr\n");
+    for line in page.get_lines() {
+        let size = (line.bbox.get_height() as f32 * geometry.scale).max(1.0);
+        for word in line
+            .words
+            .iter()
+            .filter(|word| !word.text.trim().is_empty())
+        {
+            let units: Vec<u16> = word.text.encode_utf16().collect();
+            let (x, y) = geometry.get_point(word.bbox.left as f32, get_baseline(line, word));
+            // Glyphs are 500 units wide, the horizontal scaling fits the text to the word box.
+            let width = word.bbox.get_width() as f32 * geometry.scale;
+            let scaling = (100.0 * width / (units.len() as f32 * size * 0.5)).max(1.0);
+            let text: String = units.iter().map(|unit| format!("{:04X}", unit)).collect();
+            content.push_str(&format!(
+                "/{} {:.2} Tf\n{} {} {} {} {:.2} {:.2} Tm\n{:.2} Tz\n<{}> Tj\n",
+                FONT_NAME,
+                size,
+                direction[0],
+                direction[1],
+                direction[2],
+                direction[3],
+                x,
+                y,
+                scaling,
+                text
+            ));
+        }
+    }
+    content.push_str("ET\n");
+    content.into_bytes()
+}
+
+fn add_content(
+    document: &mut Document,
+    page_id: ObjectId,
+    font_id: ObjectId,
+    content: Vec<u8>,
+) -> Result<(), TesseractError> {
+    // The original content is wrapped in q/Q, so the text layer starts with the default graphics state.
+    let mut resources = match get_inherited(document, page_id, b"Resources") {
+        Some(Object::Dictionary(resources)) => resources,
+        _ => Dictionary::new(),
+    };
+    let mut fonts = match resources.get(b"Font") {
+        Ok(Object::Reference(id)) => document
+            .get_dictionary(*id)
+            .cloned()
+            .map_err(|_| TesseractError::PdfError)?,
+        Ok(Object::Dictionary(fonts)) => fonts.clone(),
+        _ => Dictionary::new(),
+    };
+    fonts.set(FONT_NAME, font_id);
+    resources.set("Font", fonts);
+
+    let mut contents = vec![Object::Reference(
+        document.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec())),
+    )];
+    let page = document
+        .get_dictionary(page_id)
+        .map_err(|_| TesseractError::PdfError)?;
+    match page.get(b"Contents") {
+        Ok(Object::Reference(id)) => match document.get_object(*id) {
+            Ok(Object::Array(array)) => contents.extend(array.iter().cloned()),
+            _ => contents.push(Object::Reference(*id)),
+        },
+        Ok(Object::Array(array)) => contents.extend(array.iter().cloned()),
+        _ => {}
+    }
+    let mut layer = b"Q\n".to_vec();
+    layer.extend(content);
+    contents.push(Object::Reference(
+        document.add_object(Stream::new(Dictionary::new(), layer)),
+    ));
+
+    let page = document
+        .get_dictionary_mut(page_id)
+        .map_err(|_| TesseractError::PdfError)?;
+    page.set("Resources", resources);
+    page.set("Contents", contents);
+    Ok(())
+}
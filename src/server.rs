@@ -0,0 +1,225 @@
+//! Local HTTP OCR service built on the crate's tokio runtime.
+//!
+//! Endpoints:
+//! 1. `GET /health` - service status and the number of engines.
+//! 2. `POST /ocr` - recognize an image uploaded as a raw body or as a multipart form field.
+//!    Query parameters: `format` (txt, hocr, tsv, alto, json; default txt), `psm` and `lang`
+//!    override the page segmentation mode and languages of the pool for this request.
+//!
+//! Images are recognized by an `EnginePool`, so the pool size limits the number of parallel recognitions
+//! and its timeout is mapped to `504 Gateway Timeout`.
+
+use crate::assembly::assemble_doc;
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::pool::EnginePool;
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, FromRequest, Multipart, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::net::TcpListener;
+
+/// Default limit of an uploaded image - 20 MiB.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 20 * 1024 * 1024;
+
+#[derive(Debug, Default, Deserialize)]
+/// OcrQuery contains query parameters of `POST /ocr`.
+pub struct OcrQuery {
+    pub format: Option<String>,
+    pub psm: Option<u32>,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// OcrResponse is returned by `POST /ocr?format=json`.
+pub struct OcrResponse {
+    pub text: String,
+    pub confidence: i32,
+    pub lang: String,
+    pub psm: u32,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+/// HealthResponse is returned by `GET /health`.
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub engines: usize,
+    pub idle: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn error_response(status: StatusCode, error: &str) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+pub fn get_error_status(error: &TesseractError, lang_override: bool) -> StatusCode {
+    // Map a TesseractError to an HTTP status.
+    // An init error caused by a requested language is a client error.
+    match error {
+        TesseractError::TesseractInitError if lang_override => StatusCode::BAD_REQUEST,
+        TesseractError::TesseractInitError => StatusCode::SERVICE_UNAVAILABLE,
+        TesseractError::NoSuchFileException | TesseractError::InvalidImageError => {
+            StatusCode::BAD_REQUEST
+        }
+        TesseractError::TesseractTimeoutError => StatusCode::GATEWAY_TIMEOUT,
+        TesseractError::TesseracRuntimeError
+        | TesseractError::SaveDocError
+        | TesseractError::CacheError
+        | TesseractError::JournalError
+        | TesseractError::ParseError
+        | TesseractError::PdfError
+        | TesseractError::RedactionError => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+pub fn router(pool: Arc<EnginePool>, max_body_bytes: usize) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ocr", post(ocr))
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .with_state(pool)
+}
+
+pub async fn serve(
+    listener: TcpListener,
+    pool: Arc<EnginePool>,
+    max_body_bytes: usize,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(pool, max_body_bytes)).await
+}
+
+async fn health(State(pool): State<Arc<EnginePool>>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        engines: pool.get_size(),
+        idle: pool.get_idle(),
+    })
+}
+
+async fn read_image(request: Request) -> Result<Bytes, Response> {
+    // Read an image from the first file field of a multipart form or from the raw body.
+    let is_multipart = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(|content_type| content_type.starts_with("multipart/form-data"))
+        .unwrap_or(false);
+    let image_data = if is_multipart {
+        let mut multipart = Multipart::from_request(request, &())
+            .await
+            .map_err(IntoResponse::into_response)?;
+        loop {
+            match multipart.next_field().await {
+                Ok(Some(field)) if field.file_name().is_some() || field.name() == Some("file") => {
+                    break field.bytes().await.map_err(IntoResponse::into_response)?
+                }
+                Ok(Some(_)) => continue,
+                Ok(None) => break Bytes::new(),
+                Err(error) => return Err(error.into_response()),
+            }
+        }
+    } else {
+        Bytes::from_request(request, &())
+            .await
+            .map_err(IntoResponse::into_response)?
+    };
+    if image_data.is_empty() {
+        return Err(error_response(StatusCode::BAD_REQUEST, "Empty image"));
+    }
+    Ok(image_data)
+}
+
+async fn ocr(
+    State(pool): State<Arc<EnginePool>>,
+    Query(query): Query<OcrQuery>,
+    request: Request,
+) -> Response {
+    let started = Instant::now();
+    let format = query.format.as_deref().unwrap_or("txt");
+    let output_type = match format {
+        "json" => OutputFileFormat::TXT,
+        _ => match OutputFileFormat::from_type(format) {
+            Some(
+                output_type @ (OutputFileFormat::TXT
+                | OutputFileFormat::TSV
+                | OutputFileFormat::HOCR
+                | OutputFileFormat::ALTO),
+            ) => output_type,
+            _ => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("None existing format {}", format),
+                )
+            }
+        },
+    };
+    if let Some(lang) = &query.lang {
+        let is_valid = !lang.is_empty()
+            && lang
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+');
+        if !is_valid {
+            return error_response(StatusCode::BAD_REQUEST, "Invalid lang");
+        }
+    }
+
+    let image_data = match read_image(request).await {
+        Ok(image_data) => image_data,
+        Err(response) => return response,
+    };
+
+    let result = pool
+        .run(query.lang.as_deref(), query.psm, move |engine| {
+            engine.set_image_from_mem(&image_data)?;
+            let page = engine.get_output(&output_type)?;
+            Ok((page, engine.tess_pl.mean_text_conf(), engine.psm))
+        })
+        .await;
+    let (page, confidence, psm) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            return error_response(
+                get_error_status(&error, query.lang.is_some()),
+                &error.to_string(),
+            )
+        }
+    };
+
+    let content_type = match (format, output_type) {
+        ("json", _) => {
+            return Json(OcrResponse {
+                text: page,
+                confidence,
+                lang: query.lang.unwrap_or_else(|| pool.get_config().lang.clone()),
+                psm,
+                elapsed_ms: started.elapsed().as_millis(),
+            })
+            .into_response()
+        }
+        (_, OutputFileFormat::HOCR) => "text/html; charset=utf-8",
+        (_, OutputFileFormat::ALTO) => "application/xml; charset=utf-8",
+        (_, OutputFileFormat::TSV) => "text/tab-separated-values; charset=utf-8",
+        _ => "text/plain; charset=utf-8",
+    };
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        assemble_doc(&output_type, &[page]),
+    )
+        .into_response()
+}
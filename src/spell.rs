@@ -0,0 +1,554 @@
+//! Dictionary-based spelling correction.
+//!
+//! `correct_pages` fixes low-confidence words of recognized pages with per-language dictionaries - plain word
+//! lists or Hunspell dictionaries (.dic and .aff files, simple prefix and suffix rules are expanded).
+//! A word with a confidence below `min_confidence` which is not in a dictionary is corrected:
+//! 1. Choice - Tesseract's alternatives of the word symbols (see `choices`) are combined,
+//!    the combination with the fewest replaced symbols which forms a dictionary word is taken.
+//! 2. Dictionary - otherwise the word is replaced by the only closest dictionary word within `max_distance`
+//!    edits (Levenshtein, at most one edit per three letters). Words with digits are not changed.
+//!
+//! Punctuation around a word is kept, a dictionary word gets the case of the recognized word.
+//! Every change is recorded as a `Correction` with the original and corrected text and the reason,
+//! so corrections can be audited and reverted, see `revert_corrections`.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::spell::{Dictionary, SpellOptions};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let options = SpellOptions {
+//!         dictionaries: vec![Dictionary::load_hunspell("eng", "dicts/en_US.dic", "dicts/en_US.aff").unwrap()],
+//!         ..Default::default()
+//!     };
+//!     let result = tesseract_base.image_to_corrected("scans/letter.png", &options).await.unwrap();
+//!     for correction in result.corrections.iter() {
+//!         println!("{} -> {} ({:?})", correction.original, correction.corrected, correction.reason);
+//!     }
+//! }
+//! ```
+
+use crate::choices::get_choice_hocr;
+use crate::errors::TesseractError;
+use crate::hocr::parse_hocr;
+use crate::model::{BoundingBox, Page, Symbol, Word};
+use crate::normalize::normalize_pages;
+use crate::search::get_distance;
+use crate::tess_lib::TesseractApi;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+
+/// Shortest word corrected by the closest dictionary word.
+const MIN_LENGTH: usize = 3;
+
+#[derive(Debug, Default, Clone)]
+/// Dictionary is a set of lowercase words of a language.
+pub struct Dictionary {
+    /// Tesseract language of the words, e.g. eng.
+    pub lang: String,
+    pub words: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn new<S: AsRef<str>>(lang: &str, words: impl IntoIterator<Item = S>) -> Dictionary {
+        Dictionary {
+            lang: lang.to_string(),
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn load_word_list(lang: &str, path: &str) -> Result<Dictionary, TesseractError> {
+        // A UTF-8 file with one word per line.
+        let words =
+            std::fs::read_to_string(path).map_err(|_| TesseractError::NoSuchFileException)?;
+        Ok(Dictionary::new(lang, words.lines()))
+    }
+
+    pub fn load_hunspell(
+        lang: &str,
+        dic_path: &str,
+        aff_path: &str,
+    ) -> Result<Dictionary, TesseractError> {
+        // UTF-8 Hunspell files, see `parse_hunspell`.
+        let dic =
+            std::fs::read_to_string(dic_path).map_err(|_| TesseractError::NoSuchFileException)?;
+        let aff =
+            std::fs::read_to_string(aff_path).map_err(|_| TesseractError::NoSuchFileException)?;
+        Dictionary::parse_hunspell(lang, &dic, &aff)
+    }
+
+    pub fn parse_hunspell(lang: &str, dic: &str, aff: &str) -> Result<Dictionary, TesseractError> {
+        // Words of a .dic file with their forms made by the PFX and SFX rules of the .aff file.
+        // Other directives (compounding, NEEDAFFIX, FORBIDDENWORD etc.) are ignored.
+        let affixes = Affixes::parse(aff)?;
+        let mut words = Vec::new();
+        for (index, line) in dic.lines().enumerate() {
+            let entry = line.split_whitespace().next().unwrap_or_default();
+            // The first line is the approximate number of words.
+            if entry.is_empty() || (index == 0 && entry.parse::<usize>().is_ok()) {
+                continue;
+            }
+            let (word, flags) = entry.split_once('/').unwrap_or((entry, ""));
+            words.extend(affixes.expand(word, &affixes.get_flags(flags)));
+        }
+        Ok(Dictionary::new(lang, words))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+}
+
+/// Affix rule of a Hunspell .aff file.
+struct Affix {
+    prefix: bool,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Regex,
+}
+
+impl Affix {
+    fn apply(&self, word: &str) -> Option<String> {
+        if !self.condition.is_match(word) {
+            return None;
+        }
+        match self.prefix {
+            true => word
+                .strip_prefix(self.strip.as_str())
+                .map(|stem| format!("{}{}", self.add, stem)),
+            false => word
+                .strip_suffix(self.strip.as_str())
+                .map(|stem| format!("{}{}", stem, self.add)),
+        }
+    }
+}
+
+/// Affix rules of a Hunspell .aff file by flag.
+struct Affixes {
+    flag_type: String,
+    rules: HashMap<String, Vec<Affix>>,
+}
+
+impl Affixes {
+    fn parse(aff: &str) -> Result<Affixes, TesseractError> {
+        // PFX/SFX flag cross_product count header followed by PFX/SFX flag strip add condition rules.
+        let mut affixes = Affixes {
+            flag_type: String::new(),
+            rules: HashMap::new(),
+        };
+        let mut cross_products = HashMap::new();
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", flag_type, ..] => affixes.flag_type = flag_type.to_string(),
+                [kind @ ("PFX" | "SFX"), flag, cross_product @ ("Y" | "N"), count]
+                    if count.parse::<usize>().is_ok()
+                        && !cross_products.contains_key(&(*kind, *flag)) =>
+                {
+                    cross_products.insert((*kind, *flag), *cross_product == "Y");
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let prefix = *kind == "PFX";
+                    let condition = rest.first().copied().unwrap_or(".");
+                    let condition = match prefix {
+                        true => format!("^({})", condition),
+                        false => format!("({})$", condition),
+                    };
+                    // Continuation flags of the added text are not supported.
+                    let add = add.split('/').next().unwrap_or_default();
+                    affixes
+                        .rules
+                        .entry(flag.to_string())
+                        .or_default()
+                        .push(Affix {
+                            prefix,
+                            cross_product: cross_products
+                                .get(&(*kind, *flag))
+                                .copied()
+                                .unwrap_or(false),
+                            strip: get_affix_text(strip),
+                            add: get_affix_text(add),
+                            condition: Regex::new(&condition)
+                                .map_err(|_| TesseractError::ParseError)?,
+                        });
+                }
+                _ => {}
+            }
+        }
+        Ok(affixes)
+    }
+
+    fn get_flags(&self, flags: &str) -> Vec<String> {
+        // Flags are single characters, pairs of characters (FLAG long) or comma-separated numbers (FLAG num).
+        match self.flag_type.as_str() {
+            "long" => flags
+                .chars()
+                .collect::<Vec<char>>()
+                .chunks(2)
+                .map(|flag| flag.iter().collect())
+                .collect(),
+            "num" => flags
+                .split(',')
+                .map(|flag| flag.trim().to_string())
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+            _ => flags.chars().map(String::from).collect(),
+        }
+    }
+
+    fn expand(&self, word: &str, flags: &[String]) -> Vec<String> {
+        // The word, its forms with one prefix or one suffix and with a prefix and a suffix
+        // if both rules allow cross products.
+        let rules: Vec<&Affix> = flags
+            .iter()
+            .filter_map(|flag| self.rules.get(flag))
+            .flatten()
+            .collect();
+        let mut words = vec![word.to_string()];
+        for suffix in rules.iter().filter(|rule| !rule.prefix) {
+            if let Some(suffixed) = suffix.apply(word) {
+                for prefix in rules.iter().filter(|rule| rule.prefix) {
+                    if prefix.cross_product && suffix.cross_product {
+                        words.extend(prefix.apply(&suffixed));
+                    }
+                }
+                words.push(suffixed);
+            }
+        }
+        for prefix in rules.iter().filter(|rule| rule.prefix) {
+            words.extend(prefix.apply(word));
+        }
+        words
+    }
+}
+
+fn get_affix_text(text: &str) -> String {
+    // 0 is an empty strip or add text.
+    match text {
+        "0" => String::new(),
+        text => text.to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+/// SpellOptions describe how words are corrected.
+pub struct SpellOptions {
+    /// Dictionaries of the recognized languages. `TesseractApi::image_to_corrected` uses the dictionaries
+    /// of the languages of the api, or all dictionaries if none of them matches.
+    pub dictionaries: Vec<Dictionary>,
+    /// Words with a lower confidence are corrected. Default value - 80.0
+    pub min_confidence: f32,
+    /// Maximal number of replaced symbols or edits of a correction. Default value - 2
+    pub max_distance: usize,
+}
+
+impl Default for SpellOptions {
+    fn default() -> Self {
+        SpellOptions {
+            dictionaries: Vec::new(),
+            min_confidence: 80.0,
+            max_distance: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// CorrectionReason is the way a corrected word was found.
+pub enum CorrectionReason {
+    /// Alternatives of the recognized symbols form a dictionary word, symbols is the number of replaced symbols.
+    Choice { symbols: usize },
+    /// The closest dictionary word, distance is the number of edits.
+    Dictionary { distance: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Correction is a changed word, indexes are zero-based positions of the word in the pages.
+pub struct Correction {
+    pub page: usize,
+    pub block: usize,
+    pub paragraph: usize,
+    pub line: usize,
+    pub word: usize,
+    pub bbox: BoundingBox,
+    pub original: String,
+    pub corrected: String,
+    /// Recognition confidence of the original word, 0-100.
+    pub confidence: Option<f32>,
+    pub reason: CorrectionReason,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// SpellResult is a recognized document with corrected words.
+pub struct SpellResult {
+    pub pages: Vec<Page>,
+    pub corrections: Vec<Correction>,
+}
+
+pub fn correct_pages(pages: &mut [Page], options: &SpellOptions) -> Vec<Correction> {
+    // Correct the words of pages with all dictionaries of options.
+    let dictionaries: Vec<&Dictionary> = options.dictionaries.iter().collect();
+    correct(pages, &dictionaries, options)
+}
+
+pub fn revert_corrections(pages: &mut [Page], corrections: &[Correction]) -> usize {
+    // Restore the original text of corrected words, words changed after the correction are kept.
+    // Returns the number of reverted words.
+    let mut reverted = 0;
+    for correction in corrections.iter() {
+        let word = pages
+            .get_mut(correction.page)
+            .and_then(|page| page.blocks.get_mut(correction.block))
+            .and_then(|block| block.paragraphs.get_mut(correction.paragraph))
+            .and_then(|paragraph| paragraph.lines.get_mut(correction.line))
+            .and_then(|line| line.words.get_mut(correction.word));
+        if let Some(word) = word {
+            if word.text == correction.corrected {
+                word.text = correction.original.clone();
+                reverted += 1;
+            }
+        }
+    }
+    reverted
+}
+
+pub fn correct_image(
+    tesseract: &mut TesseractApi,
+    filename: &str,
+    options: &SpellOptions,
+) -> Result<SpellResult, TesseractError> {
+    // Recognize an image with symbol alternatives (see `choices`) and correct its words.
+    // If normalize of the api is set, the pages are normalized before the correction.
+    let mut pages = parse_hocr(&get_choice_hocr(tesseract, filename)?)?;
+    if let Some(normalize) = &tesseract.normalize {
+        normalize_pages(&mut pages, normalize);
+    }
+    let langs: Vec<&str> = tesseract
+        .lang
+        .as_deref()
+        .unwrap_or_default()
+        .split('+')
+        .collect();
+    let mut dictionaries: Vec<&Dictionary> = options
+        .dictionaries
+        .iter()
+        .filter(|dictionary| langs.contains(&dictionary.lang.as_str()))
+        .collect();
+    if dictionaries.is_empty() {
+        dictionaries = options.dictionaries.iter().collect();
+    }
+    let corrections = correct(&mut pages, &dictionaries, options);
+    Ok(SpellResult { pages, corrections })
+}
+
+fn correct(
+    pages: &mut [Page],
+    dictionaries: &[&Dictionary],
+    options: &SpellOptions,
+) -> Vec<Correction> {
+    let index = WordIndex::new(dictionaries);
+    let mut corrections = Vec::new();
+    for (page_index, page) in pages.iter_mut().enumerate() {
+        for (block_index, block) in page.blocks.iter_mut().enumerate() {
+            for (paragraph_index, paragraph) in block.paragraphs.iter_mut().enumerate() {
+                for (line_index, line) in paragraph.lines.iter_mut().enumerate() {
+                    for (word_index, word) in line.words.iter_mut().enumerate() {
+                        let Some((corrected, reason)) =
+                            correct_word(word, dictionaries, &index, options)
+                        else {
+                            continue;
+                        };
+                        corrections.push(Correction {
+                            page: page_index,
+                            block: block_index,
+                            paragraph: paragraph_index,
+                            line: line_index,
+                            word: word_index,
+                            bbox: word.bbox,
+                            original: std::mem::replace(&mut word.text, corrected.clone()),
+                            corrected,
+                            confidence: word.confidence,
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    corrections
+}
+
+fn correct_word(
+    word: &Word,
+    dictionaries: &[&Dictionary],
+    index: &WordIndex,
+    options: &SpellOptions,
+) -> Option<(String, CorrectionReason)> {
+    // Words without a confidence (e.g. of plain text) are not corrected.
+    if dictionaries.is_empty() || word.confidence? >= options.min_confidence {
+        return None;
+    }
+    let (prefix, core, suffix) = split_word(&word.text);
+    if core.is_empty() || is_known(dictionaries, core) {
+        return None;
+    }
+    // Symbols of a word changed by normalization don't match its text.
+    let symbols: String = word
+        .symbols
+        .iter()
+        .map(|symbol| symbol.text.as_str())
+        .collect();
+    if symbols == word.text {
+        let choice = get_choice_texts(&word.symbols, options.max_distance)
+            .into_iter()
+            .filter(|(text, count, _)| *count > 0 && is_known(dictionaries, split_word(text).1))
+            .min_by(|a, b| a.1.cmp(&b.1).then(b.2.total_cmp(&a.2)));
+        if let Some((text, count, _)) = choice {
+            return Some((text, CorrectionReason::Choice { symbols: count }));
+        }
+    }
+    if core.chars().any(|c| c.is_numeric()) {
+        return None;
+    }
+    let (corrected, distance) = find_closest(index, core, options.max_distance)?;
+    Some((
+        format!("{}{}{}", prefix, apply_case(core, &corrected), suffix),
+        CorrectionReason::Dictionary { distance },
+    ))
+}
+
+fn split_word(text: &str) -> (&str, &str, &str) {
+    // Punctuation before the word, the word and punctuation after it.
+    let core = text.trim_matches(|c: char| !c.is_alphanumeric());
+    let start = text.len()
+        - text
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .len();
+    (&text[..start], core, &text[start + core.len()..])
+}
+
+fn is_known(dictionaries: &[&Dictionary], word: &str) -> bool {
+    // A hyphenated word is known if all its parts are known.
+    let known = |word: &str| {
+        dictionaries
+            .iter()
+            .any(|dictionary| dictionary.contains(word))
+    };
+    known(word)
+        || (word.contains('-') && word.split('-').all(|part| !part.is_empty() && known(part)))
+}
+
+fn get_choice_texts(symbols: &[Symbol], changes: usize) -> Vec<(String, usize, f32)> {
+    // Texts of the symbols with at most `changes` symbols replaced by their alternatives,
+    // the number of replaced symbols and the sum of confidences of the used alternatives.
+    let Some((symbol, rest)) = symbols.split_first() else {
+        return vec![(String::new(), 0, 0.0)];
+    };
+    let mut texts = Vec::new();
+    for (text, count, score) in get_choice_texts(rest, changes) {
+        if count < changes {
+            for choice in symbol
+                .choices
+                .iter()
+                .filter(|choice| choice.text != symbol.text)
+            {
+                texts.push((
+                    format!("{}{}", choice.text, text),
+                    count + 1,
+                    score + choice.confidence,
+                ));
+            }
+        }
+        texts.push((format!("{}{}", symbol.text, text), count, score));
+    }
+    texts
+}
+
+/// Words of dictionaries by their length in chars, built on the first search of a closest word.
+struct WordIndex<'a> {
+    dictionaries: &'a [&'a Dictionary],
+    lengths: OnceCell<HashMap<usize, Vec<&'a str>>>,
+}
+
+impl<'a> WordIndex<'a> {
+    fn new(dictionaries: &'a [&'a Dictionary]) -> WordIndex<'a> {
+        WordIndex {
+            dictionaries,
+            lengths: OnceCell::new(),
+        }
+    }
+
+    fn get_words(&self, length: usize) -> &[&'a str] {
+        let lengths = self.lengths.get_or_init(|| {
+            let mut lengths: HashMap<usize, Vec<&str>> = HashMap::new();
+            for word in self
+                .dictionaries
+                .iter()
+                .flat_map(|dictionary| dictionary.words.iter())
+            {
+                lengths.entry(word.chars().count()).or_default().push(word);
+            }
+            lengths
+        });
+        lengths.get(&length).map_or(&[], Vec::as_slice)
+    }
+}
+
+fn find_closest(index: &WordIndex, word: &str, max_distance: usize) -> Option<(String, usize)> {
+    // The only closest dictionary word, None if several words are equally close.
+    let length = word.chars().count();
+    let max_distance = max_distance.min(length / MIN_LENGTH);
+    if max_distance == 0 {
+        return None;
+    }
+    let word = word.to_lowercase();
+    // Only words whose length differs by at most max_distance can be close enough.
+    let mut closest: Option<(usize, HashSet<&str>)> = None;
+    for candidate in (length.saturating_sub(max_distance)..=length + max_distance)
+        .flat_map(|length| index.get_words(length))
+    {
+        let distance = get_distance(&word, candidate);
+        match &mut closest {
+            _ if distance > max_distance => {}
+            Some((best, words)) if distance == *best => {
+                words.insert(*candidate);
+            }
+            Some((best, _)) if distance > *best => {}
+            _ => closest = Some((distance, HashSet::from([*candidate]))),
+        }
+    }
+    match closest {
+        Some((distance, words)) if words.len() == 1 => words
+            .into_iter()
+            .next()
+            .map(|word| (word.to_string(), distance)),
+        _ => None,
+    }
+}
+
+fn apply_case(pattern: &str, word: &str) -> String {
+    // Uppercase words stay uppercase, capitalized words stay capitalized.
+    let letters: Vec<char> = pattern.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return word.to_uppercase();
+    }
+    let mut chars = word.chars();
+    match (letters.first(), chars.next()) {
+        (Some(first), Some(c)) if first.is_uppercase() => c.to_uppercase().chain(chars).collect(),
+        _ => word.to_string(),
+    }
+}
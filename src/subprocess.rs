@@ -0,0 +1,365 @@
+//! Tesseract backend running the `tesseract` executable.
+//!
+//! `TesseractProcess` implements `OcrEngine` without linking libtesseract: every output is produced
+//! by a new `tesseract` process with the same DPI, PSM, OEM, languages and variables as `TesseractApi`.
+//! Outputs are reduced to the page fragments returned by the FFI backend (hOCR and ALTO pages
+//! without the document wrapper, TSV rows without the header), so they can be assembled the same way.
+//! A process exceeding the timeout is killed. Running a separate process per image also isolates
+//! the caller from crashes on untrusted inputs.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::engine::{recognize_doc, DocOptions};
+//! use rusttesserast::subprocess::TesseractProcess;
+//!
+//! fn main() {
+//!     let mut engine = TesseractProcess::new(Some(TesseractProcess{psm: 3, ..Default::default()}), Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let doc = recognize_doc(&mut engine, vec!["/workspaces/rusttesserast/tests/test_img.png"], "hocr", &DocOptions::default(), None);
+//! }
+//! ```
+
+use crate::assembly::extract_between;
+use crate::constanst::TesseractDefaultConstants;
+use crate::convert::convert_recognized;
+use crate::engine::OcrEngine;
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::normalize::{normalize_output, NormalizeOptions};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+enum ProcessImage {
+    File(String),
+    Memory(Vec<u8>),
+}
+
+#[derive(Debug, Default, Clone)]
+/// ProcessState contains the loaded image and its outputs.
+pub struct ProcessState {
+    image: Option<ProcessImage>,
+    /// Resolution of the loaded image if it differs from dpi, e.g. of a rendered PDF page.
+    dpi: Option<i32>,
+    outputs: HashMap<OutputFileFormat, String>,
+}
+
+#[derive(Debug, Clone)]
+/// TesseractProcess recognizes images with the `tesseract` executable.
+pub struct TesseractProcess {
+    /// program - path to the tesseract executable.
+    /// Default value - tesseract
+    pub program: String,
+    /// dpi - source image resolution.
+    /// Default value - 300
+    pub dpi: i32,
+    /// psm - tesseract Page Segmentation Mode.
+    /// Default value - 4
+    pub psm: u32,
+    /// oem - tesseract Engine Mode.
+    /// Default value - 3
+    pub oem: u32,
+    /// timeout - the tesseract process is killed after timeout seconds.
+    /// Default value - 30
+    pub timeout: i32,
+    /// datapath - path to the tessdata directory.
+    /// Default value - None
+    pub datapath: Option<String>,
+    /// lang - tesseract languages, e.g. eng+rus.
+    /// Default value - None
+    pub lang: Option<String>,
+    /// variables - tesseract variables (name, value) passed with -c.
+    /// Default value - empty
+    pub variables: Vec<(String, String)>,
+    /// normalize - optional post-processing of the recognized text, see `normalize::NormalizeOptions`.
+    /// Default value - None
+    pub normalize: Option<NormalizeOptions>,
+    /// state - the loaded image and its outputs, outputs are kept until another image or psm is set.
+    pub state: ProcessState,
+}
+
+impl Default for TesseractProcess {
+    fn default() -> Self {
+        TesseractProcess {
+            program: String::from("tesseract"),
+            dpi: TesseractDefaultConstants::DEFAULT_DPI,
+            psm: TesseractDefaultConstants::DEFAULT_PSM,
+            oem: TesseractDefaultConstants::DEFAULT_OEM,
+            timeout: TesseractDefaultConstants::DEFAULT_TIMEOUT,
+            datapath: None,
+            lang: None,
+            variables: Vec::new(),
+            normalize: None,
+            state: ProcessState::default(),
+        }
+    }
+}
+
+impl TesseractProcess {
+    pub fn new(
+        tess_process: Option<TesseractProcess>,
+        datapath: Option<&str>,
+        lang: Option<&str>,
+    ) -> Result<TesseractProcess, TesseractError> {
+        // Create a backend and check that the tesseract executable can be run.
+        // tess_process - optional configuration, the same as TesseractApi.
+        // datapath, lang - take precedence over the values of tess_process.
+        let mut tess = tess_process.unwrap_or_default();
+        if let Some(datapath) = datapath {
+            tess.datapath = Some(datapath.to_string());
+        }
+        if let Some(lang) = lang {
+            tess.lang = Some(lang.to_string());
+        }
+        tess.get_version()?;
+        Ok(tess)
+    }
+
+    pub fn get_version(&self) -> Result<String, TesseractError> {
+        // First line of `tesseract --version`, e.g. tesseract 5.3.0
+        let output = Command::new(&self.program)
+            .arg("--version")
+            .output()
+            .map_err(|_| TesseractError::TesseractInitError)?;
+        if !output.status.success() {
+            return Err(TesseractError::TesseractInitError);
+        }
+        // Old versions print the version to stderr.
+        let version = match output.stdout.is_empty() {
+            true => output.stderr,
+            false => output.stdout,
+        };
+        Ok(String::from_utf8_lossy(&version)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    fn get_args(&self, output_type: &OutputFileFormat) -> Result<Vec<String>, TesseractError> {
+        let input = match &self.state.image {
+            Some(ProcessImage::File(filename)) => filename.clone(),
+            Some(ProcessImage::Memory(_)) => String::from("stdin"),
+            None => return Err(TesseractError::TesseracRuntimeError),
+        };
+        let mut args = vec![input, String::from("stdout")];
+        if let Some(datapath) = &self.datapath {
+            args.push(String::from("--tessdata-dir"));
+            args.push(datapath.clone());
+        }
+        if let Some(lang) = &self.lang {
+            args.push(String::from("-l"));
+            args.push(lang.clone());
+        }
+        args.extend([
+            String::from("--dpi"),
+            self.state.dpi.unwrap_or(self.dpi).to_string(),
+            String::from("--psm"),
+            self.psm.to_string(),
+            String::from("--oem"),
+            self.oem.to_string(),
+        ]);
+        for (name, value) in self.variables.iter() {
+            args.push(String::from("-c"));
+            args.push(format!("{}={}", name, value));
+        }
+        match output_type {
+            OutputFileFormat::TXT => {}
+            OutputFileFormat::TSV | OutputFileFormat::HOCR | OutputFileFormat::ALTO => {
+                args.push(output_type.get_type().to_string())
+            }
+            _ => return Err(TesseractError::TesseracRuntimeError),
+        }
+        Ok(args)
+    }
+
+    fn run(&self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        // Run tesseract and return its stdout.
+        let args = self.get_args(output_type)?;
+        let mut child = Command::new(&self.program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|_| TesseractError::TesseractInitError)?;
+
+        // Pipes are served by threads, so a full pipe never blocks the process.
+        let mut stdin = child.stdin.take();
+        let image_data = match &self.state.image {
+            Some(ProcessImage::Memory(image_data)) => image_data.clone(),
+            _ => Vec::new(),
+        };
+        let stdin_thread = thread::spawn(move || {
+            if let Some(stdin) = stdin.as_mut() {
+                let _ = stdin.write_all(&image_data);
+            }
+        });
+        let stdout_thread = read_pipe(child.stdout.take());
+        let stderr_thread = read_pipe(child.stderr.take());
+
+        let success =
+            wait_with_timeout(&mut child, Duration::from_secs(self.timeout.max(1) as u64))?;
+        let _ = stdin_thread.join();
+        let stdout = stdout_thread.join().unwrap_or_default();
+        let stderr = stderr_thread.join().unwrap_or_default();
+        if !success {
+            return Err(get_process_error(
+                &String::from_utf8_lossy(&stderr),
+                &self.state.image,
+            ));
+        }
+        Ok(String::from_utf8_lossy(&stdout).into_owned())
+    }
+}
+
+impl OcrEngine for TesseractProcess {
+    fn set_image(&mut self, filename: &str) -> Result<(), TesseractError> {
+        if !Path::new(filename).is_file() {
+            return Err(TesseractError::NoSuchFileException);
+        }
+        self.state.image = Some(ProcessImage::File(filename.to_string()));
+        self.state.dpi = None;
+        self.state.outputs.clear();
+        Ok(())
+    }
+
+    fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError> {
+        if image_data.is_empty() {
+            return Err(TesseractError::InvalidImageError);
+        }
+        self.state.image = Some(ProcessImage::Memory(image_data.to_vec()));
+        self.state.dpi = None;
+        self.state.outputs.clear();
+        Ok(())
+    }
+
+    fn set_source_resolution(&mut self, dpi: i32) {
+        self.state.dpi = Some(dpi);
+        self.state.outputs.clear();
+    }
+
+    fn set_page_seg_mode(&mut self, psm: u32) {
+        self.psm = psm;
+        self.state.outputs.clear();
+    }
+
+    fn recognize(&mut self) -> Result<(), TesseractError> {
+        // Every output is a run of the executable, so the image is recognized by `get_output`
+        // and only the requested format is produced.
+        match self.state.image {
+            Some(_) => Ok(()),
+            None => Err(TesseractError::TesseracRuntimeError),
+        }
+    }
+
+    fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        if let Some(output) = self.state.outputs.get(output_type) {
+            return Ok(output.clone());
+        }
+        if output_type.is_converted() {
+            // The tesseract executable has no such outputs, they are converted from hOCR.
+            let started = Instant::now();
+            let hocr = self.get_output(&OutputFileFormat::HOCR)?;
+            return convert_recognized(&hocr, output_type, started.elapsed());
+        }
+        let output = get_page_output(output_type, &self.run(output_type)?);
+        let output = match &self.normalize {
+            Some(options) => normalize_output(&output, output_type, options)?,
+            None => output,
+        };
+        self.state.outputs.insert(*output_type, output.clone());
+        Ok(output)
+    }
+
+    fn mean_text_conf(&mut self) -> i32 {
+        // Mean confidence of the words of the TSV output, 0 if there are no words.
+        let tsv = match self.get_output(&OutputFileFormat::TSV) {
+            Ok(tsv) => tsv,
+            Err(_) => return 0,
+        };
+        let confidences: Vec<f32> = tsv
+            .lines()
+            .filter_map(|row| {
+                let columns: Vec<&str> = row.split('\t').collect();
+                match columns.as_slice() {
+                    ["5", .., conf, _] => conf.parse::<f32>().ok().filter(|conf| *conf >= 0.0),
+                    _ => None,
+                }
+            })
+            .collect();
+        match confidences.len() {
+            0 => 0,
+            len => (confidences.iter().sum::<f32>() / len as f32).round() as i32,
+        }
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut data = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut data);
+        }
+        data
+    })
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<bool, TesseractError> {
+    // Wait for the process, kill it when the timeout is exceeded.
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status.success()),
+            Ok(None) if started.elapsed() < timeout => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(TesseractError::TesseractTimeoutError);
+            }
+            Err(_) => return Err(TesseractError::TesseracRuntimeError),
+        }
+    }
+}
+
+fn get_process_error(stderr: &str, image: &Option<ProcessImage>) -> TesseractError {
+    // Map tesseract error messages to the errors of the FFI backend.
+    if stderr.contains("Failed loading language") || stderr.contains("Could not initialize") {
+        return TesseractError::TesseractInitError;
+    }
+    if stderr.contains("Error in pixRead")
+        || stderr.contains("Cannot open input file")
+        || stderr.contains("cannot be read")
+    {
+        return match image {
+            Some(ProcessImage::File(filename)) if !Path::new(filename).is_file() => {
+                TesseractError::NoSuchFileException
+            }
+            _ => TesseractError::InvalidImageError,
+        };
+    }
+    TesseractError::TesseracRuntimeError
+}
+
+pub fn get_page_output(output_type: &OutputFileFormat, output: &str) -> String {
+    // Reduce a document printed by the tesseract executable to the page returned by the FFI backend.
+    match output_type {
+        OutputFileFormat::TXT => output.trim_end_matches('\u{c}').to_string(),
+        OutputFileFormat::TSV => output
+            .lines()
+            .filter(|row| !row.starts_with("level\t"))
+            .map(|row| format!("{}\n", row))
+            .collect(),
+        OutputFileFormat::HOCR => extract_between(output, "<body>", "</body>")
+            .trim_end_matches([' ', '\t'])
+            .to_string(),
+        OutputFileFormat::ALTO => extract_between(output, "<Layout>", "</Layout>")
+            .trim_end_matches([' ', '\t'])
+            .to_string(),
+        _ => output.to_string(),
+    }
+}
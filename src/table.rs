@@ -0,0 +1,487 @@
+//! Table extraction.
+//!
+//! `detect_tables` finds tables on a recognized page and assigns its words to a grid of cells:
+//! 1. Ruled tables - horizontal and vertical ruling lines crossing each other (see `find_rulings`),
+//!    rows and columns are the spaces between the lines.
+//! 2. Aligned tables - at least `min_rows` consecutive rows of words split into cells by gaps wider than
+//!    `column_gap`, whose cells stay in the same columns. Rows can leave cells empty.
+//!
+//! Words inside a ruled table are not used for aligned tables. Every cell keeps its bounding box
+//! for review, `write_csv` exports the grid.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::table::{write_csv, TableOptions};
+//! use rusttesserast::tess_lib::TesseractApi;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut tesseract_base = TesseractApi::new(None, Some(String::from("/usr/local/share/tessdata").as_str()), Some(String::from("eng").as_str())).unwrap();
+//!     let tables = tesseract_base.image_to_tables("scans/invoice.png", &TableOptions::default()).await.unwrap();
+//!     for table in tables.iter() {
+//!         println!("{}", write_csv(table, ','));
+//!     }
+//! }
+//! ```
+
+use crate::errors::TesseractError;
+use crate::layout::get_line_height;
+use crate::model::{get_union, BoundingBox, Page, Word};
+use crate::pix::RawPix;
+use pl::leptonica_sys::{
+    boxaDestroy, boxaGetBoxGeometry, boxaGetCount, pixConnCompBB, pixConvertTo1, pixOpenBrick,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ptr;
+use tesseract_plumbing as pl;
+
+/// Distance in pixels within which ruling lines cross or are the same line.
+const RULING_TOLERANCE: i64 = 5;
+
+/// Longest mean number of words in the cells of an aligned table, longer cells are columns of text.
+const MAX_CELL_WORDS: f32 = 5.0;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// TableCell is a cell of a table grid.
+pub struct TableCell {
+    /// Zero-based row of the cell.
+    pub row: usize,
+    /// Zero-based column of the cell.
+    pub column: usize,
+    /// Space between the ruling lines, or the column and row of an aligned table.
+    pub bbox: BoundingBox,
+    /// Words of the cell joined with spaces, empty for empty cells.
+    pub text: String,
+    /// Mean confidence of the words, 0-100.
+    pub confidence: Option<f32>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Table is a grid of cells, every row has the same number of cells.
+pub struct Table {
+    pub bbox: BoundingBox,
+    /// The table was found by ruling lines.
+    pub ruled: bool,
+    pub rows: Vec<Vec<TableCell>>,
+}
+
+impl Table {
+    pub fn get_row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get_column_count(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    pub fn get_cell(&self, row: usize, column: usize) -> Option<&TableCell> {
+        self.rows.get(row).and_then(|cells| cells.get(column))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Rulings are the boxes of ruling lines of an image.
+pub struct Rulings {
+    pub horizontal: Vec<BoundingBox>,
+    pub vertical: Vec<BoundingBox>,
+}
+
+#[derive(Debug, Clone)]
+/// TableOptions describe how tables are detected.
+pub struct TableOptions {
+    /// Minimal number of rows of a table. Default value - 2
+    pub min_rows: usize,
+    /// Minimal number of columns of a table. Default value - 2
+    pub min_columns: usize,
+    /// Minimal gap between words of different cells in pixels. If it is None, the median line height is used.
+    pub column_gap: Option<f32>,
+    /// Ruling lines of the image. Default value - None
+    pub rulings: Option<Rulings>,
+    /// Minimal length of ruling lines searched by `TesseractApi::image_to_tables` if rulings is None,
+    /// 0 - ruling lines are not searched. Default value - 40
+    pub min_ruling_length: u32,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            min_rows: 2,
+            min_columns: 2,
+            column_gap: None,
+            rulings: None,
+            min_ruling_length: 40,
+        }
+    }
+}
+
+pub fn detect_tables(page: &Page, options: &TableOptions) -> Vec<Table> {
+    let mut tables = match &options.rulings {
+        Some(rulings) => get_ruled_tables(page, rulings, options),
+        None => Vec::new(),
+    };
+    let column_gap = options.column_gap.unwrap_or_else(|| get_line_height(page));
+    let rows = get_rows(page, &tables);
+    tables.extend(get_aligned_tables(&rows, column_gap, options));
+    tables.sort_by_key(|table| (table.bbox.top, table.bbox.left));
+    tables
+}
+
+pub fn write_csv(table: &Table, delimiter: char) -> String {
+    // Fields containing the delimiter, quotes or line breaks are quoted as in RFC 4180, rows end with \n.
+    let mut csv = String::new();
+    for row in table.rows.iter() {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|cell| {
+                if cell.text.contains([delimiter, '"', '\n', '\r']) {
+                    format!("\"{}\"", cell.text.replace('"', "\"\""))
+                } else {
+                    cell.text.clone()
+                }
+            })
+            .collect();
+        csv.push_str(&fields.join(&delimiter.to_string()));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn find_rulings(filename: &str, min_length: u32) -> Result<Rulings, TesseractError> {
+    // Ruling lines are runs of dark pixels at least min_length long: opening the binarized image with
+    // a min_length x 1 (1 x min_length) brick removes the text, the remaining components are the lines.
+    let pix = RawPix::read(filename)?;
+    let binary = RawPix(unsafe { pixConvertTo1(pix.0, 128) });
+    if binary.is_null() {
+        return Err(TesseractError::InvalidImageError);
+    }
+    let length = min_length.max(2) as i32;
+    Ok(Rulings {
+        horizontal: get_components(&binary, length, 1),
+        vertical: get_components(&binary, 1, length),
+    })
+}
+
+fn get_components(pix: &RawPix, width: i32, height: i32) -> Vec<BoundingBox> {
+    let opened = RawPix(unsafe { pixOpenBrick(ptr::null_mut(), pix.0, width, height) });
+    if opened.is_null() {
+        return Vec::new();
+    }
+    let mut boxa = unsafe { pixConnCompBB(opened.0, 8) };
+    if boxa.is_null() {
+        return Vec::new();
+    }
+    let mut boxes = Vec::new();
+    for index in 0..unsafe { boxaGetCount(boxa) } {
+        let (mut x, mut y, mut w, mut h) = (0, 0, 0, 0);
+        if unsafe { boxaGetBoxGeometry(boxa, index, &mut x, &mut y, &mut w, &mut h) } == 0 {
+            boxes.push(BoundingBox::new(
+                x as u32,
+                y as u32,
+                (x + w) as u32,
+                (y + h) as u32,
+            ));
+        }
+    }
+    unsafe { boxaDestroy(&mut boxa) };
+    boxes
+}
+
+pub(crate) fn get_center(bbox: &BoundingBox) -> (u32, u32) {
+    ((bbox.left + bbox.right) / 2, (bbox.top + bbox.bottom) / 2)
+}
+
+pub(crate) fn is_inside(point: (u32, u32), bbox: &BoundingBox) -> bool {
+    (bbox.left..bbox.right).contains(&point.0) && (bbox.top..bbox.bottom).contains(&point.1)
+}
+
+fn get_cell(row: usize, column: usize, bbox: BoundingBox, words: &[&Word]) -> TableCell {
+    let confidences: Vec<f32> = words.iter().filter_map(|word| word.confidence).collect();
+    TableCell {
+        row,
+        column,
+        bbox,
+        text: words
+            .iter()
+            .map(|word| word.text.trim())
+            .collect::<Vec<&str>>()
+            .join(" "),
+        confidence: match confidences.len() {
+            0 => None,
+            len => Some(confidences.iter().sum::<f32>() / len as f32),
+        },
+    }
+}
+
+fn crosses(horizontal: &BoundingBox, vertical: &BoundingBox) -> bool {
+    let (h, v) = (
+        [
+            horizontal.left,
+            horizontal.top,
+            horizontal.right,
+            horizontal.bottom,
+        ]
+        .map(i64::from),
+        [vertical.left, vertical.top, vertical.right, vertical.bottom].map(i64::from),
+    );
+    v[2] + RULING_TOLERANCE >= h[0]
+        && v[0] <= h[2] + RULING_TOLERANCE
+        && h[3] + RULING_TOLERANCE >= v[1]
+        && h[1] <= v[3] + RULING_TOLERANCE
+}
+
+fn get_bounds(mut positions: Vec<u32>) -> Vec<u32> {
+    // Positions of lines closer than the tolerance are one line.
+    positions.sort();
+    positions.dedup_by(|next, previous| i64::from(*next - *previous) <= RULING_TOLERANCE);
+    positions
+}
+
+fn find_group(groups: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while groups[root] != root {
+        root = groups[root];
+    }
+    groups[index] = root;
+    root
+}
+
+fn get_ruled_tables(page: &Page, rulings: &Rulings, options: &TableOptions) -> Vec<Table> {
+    // Ruling lines crossing each other are grouped, every group with at least two horizontal
+    // and two vertical lines is a grid.
+    let count = rulings.horizontal.len();
+    let mut groups: Vec<usize> = (0..count + rulings.vertical.len()).collect();
+    for (h, horizontal) in rulings.horizontal.iter().enumerate() {
+        for (v, vertical) in rulings.vertical.iter().enumerate() {
+            if crosses(horizontal, vertical) {
+                let (a, b) = (
+                    find_group(&mut groups, h),
+                    find_group(&mut groups, count + v),
+                );
+                groups[a] = b;
+            }
+        }
+    }
+    let mut grids: BTreeMap<usize, (Vec<u32>, Vec<u32>)> = BTreeMap::new();
+    for index in 0..groups.len() {
+        let root = find_group(&mut groups, index);
+        let grid = grids.entry(root).or_default();
+        match index < count {
+            true => grid.0.push(get_center(&rulings.horizontal[index]).1),
+            false => grid.1.push(get_center(&rulings.vertical[index - count]).0),
+        }
+    }
+
+    let words: Vec<&Word> = page.get_words().collect();
+    let mut tables = Vec::new();
+    for (ys, xs) in grids.into_values() {
+        let (ys, xs) = (get_bounds(ys), get_bounds(xs));
+        if ys.len() < options.min_rows + 1 || xs.len() < options.min_columns + 1 {
+            continue;
+        }
+        let rows = (0..ys.len() - 1)
+            .map(|row| {
+                (0..xs.len() - 1)
+                    .map(|column| {
+                        let bbox =
+                            BoundingBox::new(xs[column], ys[row], xs[column + 1], ys[row + 1]);
+                        let cell_words: Vec<&Word> = words
+                            .iter()
+                            .filter(|word| is_inside(get_center(&word.bbox), &bbox))
+                            .copied()
+                            .collect();
+                        get_cell(row, column, bbox, &cell_words)
+                    })
+                    .collect()
+            })
+            .collect();
+        tables.push(Table {
+            bbox: BoundingBox::new(xs[0], ys[0], xs[xs.len() - 1], ys[ys.len() - 1]),
+            ruled: true,
+            rows,
+        });
+    }
+    tables
+}
+
+/// Row of words at the same height.
+struct Row<'a> {
+    bbox: BoundingBox,
+    words: Vec<&'a Word>,
+}
+
+fn get_rows<'a>(page: &'a Page, tables: &[Table]) -> Vec<Row<'a>> {
+    // Lines are grouped into rows by their middles, words in tables are left out.
+    let mut lines: Vec<(BoundingBox, Vec<&Word>)> = page
+        .get_lines()
+        .map(|line| {
+            let words: Vec<&Word> = line
+                .words
+                .iter()
+                .filter(|word| !word.text.trim().is_empty())
+                .filter(|word| {
+                    let center = get_center(&word.bbox);
+                    !tables.iter().any(|table| is_inside(center, &table.bbox))
+                })
+                .collect();
+            (line.bbox, words)
+        })
+        .filter(|(_, words)| !words.is_empty())
+        .collect();
+    lines.sort_by_key(|(bbox, _)| bbox.top + bbox.bottom);
+    let mut rows: Vec<Row> = Vec::new();
+    for (bbox, words) in lines {
+        match rows.last_mut() {
+            Some(row) if (bbox.top + bbox.bottom) / 2 < row.bbox.bottom => {
+                row.bbox = row.bbox.union(&bbox);
+                row.words.extend(words);
+            }
+            _ => rows.push(Row { bbox, words }),
+        }
+    }
+    for row in rows.iter_mut() {
+        row.words.sort_by_key(|word| word.bbox.left);
+    }
+    rows
+}
+
+/// Span is a cell of a row: words closer than the column gap.
+struct Span {
+    left: u32,
+    right: u32,
+    words: usize,
+}
+
+fn get_spans(row: &Row, column_gap: f32) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    for word in row.words.iter() {
+        match spans.last_mut() {
+            Some(span) if (word.bbox.left as f32) < span.right as f32 + column_gap => {
+                span.right = span.right.max(word.bbox.right);
+                span.words += 1;
+            }
+            _ => spans.push(Span {
+                left: word.bbox.left,
+                right: word.bbox.right,
+                words: 1,
+            }),
+        }
+    }
+    spans
+}
+
+fn overlaps(a: (u32, u32), b: (u32, u32)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+fn is_aligned(columns: &[(u32, u32)], spans: &[Span]) -> bool {
+    // Every cell is in at most one column, no column has two cells and at least two cells are in columns.
+    let mut used = vec![false; columns.len()];
+    let mut aligned = 0;
+    for span in spans {
+        let found: Vec<usize> = (0..columns.len())
+            .filter(|index| overlaps(columns[*index], (span.left, span.right)))
+            .collect();
+        match found.as_slice() {
+            [] => {}
+            [index] if !used[*index] => {
+                used[*index] = true;
+                aligned += 1;
+            }
+            _ => return false,
+        }
+    }
+    aligned >= 2
+}
+
+fn add_columns(columns: &mut Vec<(u32, u32)>, spans: &[Span]) {
+    for span in spans {
+        columns.push((span.left, span.right));
+    }
+    columns.sort();
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(columns.len());
+    for column in columns.drain(..) {
+        match merged.last_mut() {
+            Some(last) if column.0 < last.1 => last.1 = last.1.max(column.1),
+            _ => merged.push(column),
+        }
+    }
+    *columns = merged;
+}
+
+fn get_aligned_tables(rows: &[Row], column_gap: f32, options: &TableOptions) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut run: Vec<(&Row, Vec<Span>)> = Vec::new();
+    let mut columns: Vec<(u32, u32)> = Vec::new();
+    for row in rows {
+        let spans = get_spans(row, column_gap);
+        if !run.is_empty() && !is_aligned(&columns, &spans) {
+            tables.extend(get_aligned_table(&run, &columns, options));
+            run.clear();
+            columns.clear();
+        }
+        if spans.len() >= 2 {
+            add_columns(&mut columns, &spans);
+            run.push((row, spans));
+        }
+    }
+    tables.extend(get_aligned_table(&run, &columns, options));
+    tables
+}
+
+fn get_aligned_table(
+    run: &[(&Row, Vec<Span>)],
+    columns: &[(u32, u32)],
+    options: &TableOptions,
+) -> Option<Table> {
+    let (cells, words) = run
+        .iter()
+        .flat_map(|(_, spans)| spans.iter())
+        .fold((0, 0), |(cells, words), span| {
+            (cells + 1, words + span.words)
+        });
+    if run.len() < options.min_rows.max(1)
+        || columns.len() < options.min_columns.max(2)
+        || words as f32 / cells as f32 > MAX_CELL_WORDS
+    {
+        return None;
+    }
+    let rows: Vec<Vec<TableCell>> = run
+        .iter()
+        .enumerate()
+        .map(|(row_index, (row, _))| {
+            // Words are assigned to the column containing their center, or to the nearest column.
+            let mut cells: Vec<Vec<&Word>> = vec![Vec::new(); columns.len()];
+            for word in row.words.iter() {
+                let center = get_center(&word.bbox).0;
+                let column = (0..columns.len())
+                    .min_by_key(|index| {
+                        let (left, right) = columns[*index];
+                        left.saturating_sub(center) + center.saturating_sub(right)
+                    })
+                    .unwrap_or_default();
+                cells[column].push(word);
+            }
+            cells
+                .iter()
+                .enumerate()
+                .map(|(column, words)| {
+                    let (left, right) = columns[column];
+                    let bbox = BoundingBox::new(left, row.bbox.top, right, row.bbox.bottom);
+                    get_cell(row_index, column, bbox, words)
+                })
+                .collect()
+        })
+        .collect();
+    let bbox = get_union(rows.iter().flatten().map(|cell| &cell.bbox));
+    Some(Table {
+        bbox,
+        ruled: false,
+        rows,
+    })
+}
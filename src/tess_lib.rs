@@ -29,19 +29,40 @@
 //!     let test = tesseract_base.recognize_doc("test/save/path", "test_data_file.txt", image_array, "tsv").await;
 //! }
 
+use crate::cache::{get_cache_key, get_tessdata_version, ResultCache};
+use crate::choices::{recognize_choices, WordChoices};
 use crate::constanst::TesseractDefaultConstants;
+use crate::convert::{convert_recognized, write_doc};
+use crate::engine::{get_doc_format, recognize_doc, DocOptions, OcrEngine};
 use crate::errors::TesseractError;
 use crate::file_types::OutputFileFormat;
-use crate::utils::get_current_working_dir;
+use crate::form::{recognize_form, FormResult, FormTemplate};
+use crate::hocr::parse_hocr;
+use crate::job::JobJournal;
+use crate::normalize::{normalize_output, NormalizeOptions};
+#[cfg(feature = "pdf")]
+use crate::pdf::{recognize_pdf, PdfOptions, PdfPageResult};
+#[cfg(feature = "pdf")]
+use crate::pdf_renderer::{render_pdf, PdfRendererOptions};
+use crate::reading_order::{order_pages, ReadingOrderOptions, TextDirection};
+#[cfg(feature = "pdf")]
+use crate::redact::redact_image_to_pdf;
+use crate::redact::{redact_image, RedactionOptions};
+use crate::search::{find_matches, get_regex, SearchMatch, SearchOptions};
+#[cfg(feature = "pdf")]
+use crate::searchable_pdf::add_text_layer;
+use crate::spell::{correct_image, SpellOptions, SpellResult};
+use crate::table::{detect_tables, find_rulings, Table, TableOptions};
+use crate::tsv::{parse_tsv, TsvRow};
+use crate::utils::{save_doc, save_pages};
 use derivative::Derivative;
 use either::*;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
 use pl::TessBaseApiInitError;
 use std::ffi::CString;
-use std::fs::{metadata, File};
-use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tesseract_plumbing as pl;
 
 #[derive(Derivative)]
@@ -64,8 +85,46 @@ pub struct TesseractApi {
     /// All available options are described here - `https://github.com/tesseract-ocr/tesseract/blob/main/doc/tesseract.1.asc`.
     /// Default value - 3
     pub oem: u32,
+    #[derivative(Default(value = "2"))]
+    /// lstm_choice_mode - alternatives of symbols reported by `image_to_choices` and used by `image_to_corrected`.
+    /// 0 - no alternatives, 1 - alternatives of every LSTM timestep, 2 - alternatives of every symbol.
+    /// Alternatives are reported only by the LSTM engine (oem 1, 2 or 3).
+    /// Default value - 2
+    pub lstm_choice_mode: u32,
     #[derivative(Default(value = "30"))]
     pub timeout: i32,
+    /// page_template - optional naming template for saving one output file per image.
+    /// Supported placeholders: {stem}, {name}, {ext}, {index}, {page}, e.g. `{stem}.{ext}` or `{index:04}.hocr`.
+    /// If it is set, `recognize_doc` saves every page separately instead of a single doc.
+    /// Default value - None
+    pub page_template: Option<String>,
+    /// datapath - path to the tessdata directory. It is set by `new`, if `new` gets None, this value is used.
+    /// Default value - None
+    pub datapath: Option<String>,
+    /// lang - tesseract languages, e.g. eng+rus. It is set by `new`, if `new` gets None, this value is used.
+    /// Default value - None
+    pub lang: Option<String>,
+    /// variables - tesseract variables (name, value) applied by `new` after initialization.
+    /// Use `set_variable` to change a variable of an initialized api.
+    /// Default value - empty
+    pub variables: Vec<(String, String)>,
+    /// cache - optional cache of recognized documents, see `cache::ResultCache`.
+    /// If it is set, `image_to_*` and `recognize_doc` return cached documents for already recognized images.
+    /// Default value - None
+    pub cache: Option<ResultCache>,
+    /// journal - optional journal of a batch job, see `job::JobJournal`.
+    /// If it is set, `recognize_doc` records every recognized page and skips pages recognized by previous runs.
+    /// Default value - None
+    pub journal: Option<JobJournal>,
+    /// normalize - optional post-processing of the recognized text, see `normalize::NormalizeOptions`.
+    /// If it is set, every output is normalized, cached documents are kept separately for every configuration.
+    /// Default value - None
+    pub normalize: Option<NormalizeOptions>,
+    #[cfg(feature = "pdf")]
+    /// pdf_options - rasterization of PDF files by `pdf_to_*` and `recognize_doc`, see `pdf::PdfOptions`.
+    /// If pdf_options.dpi is None, PDF pages are rendered at dpi.
+    /// Default value - every page is recognized
+    pub pdf_options: PdfOptions,
     /// tesseract_plumbing base api
     pub tess_pl: pl::TessBaseApi,
 }
@@ -77,7 +136,17 @@ impl TesseractApi {
             dpi: TesseractDefaultConstants::DEFAULT_DPI,
             psm: TesseractDefaultConstants::DEFAULT_PSM,
             oem: TesseractDefaultConstants::DEFAULT_OEM,
+            lstm_choice_mode: TesseractDefaultConstants::DEFAULT_LSTM_CHOICE_MODE,
             timeout: TesseractDefaultConstants::DEFAULT_TIMEOUT,
+            page_template: None,
+            datapath: None,
+            lang: None,
+            variables: Vec::new(),
+            cache: None,
+            journal: None,
+            normalize: None,
+            #[cfg(feature = "pdf")]
+            pdf_options: PdfOptions::default(),
             tess_pl: pl::TessBaseApi::create(),
         };
     }
@@ -90,6 +159,7 @@ impl TesseractApi {
             "dpi" => Left(Ok(&self.dpi)),
             "psm" => Right(Ok(&self.psm)),
             "oem" => Right(Ok(&self.oem)),
+            "lstm_choice_mode" => Right(Ok(&self.lstm_choice_mode)),
             "timeout" => Left(Ok(&self.timeout)),
             _ => Right(Err(format!("invalid field name to get '{}'", field_string))),
         }
@@ -112,24 +182,75 @@ impl TesseractApi {
         tess.tess_pl.set_source_resolution(tess.dpi);
         tess.tess_pl.set_page_seg_mode(tess.psm);
 
-        let datapath = match datapath {
-            Some(i) => Some(CString::new(i).unwrap()),
-            None => None,
-        };
-        let lang = match lang {
-            Some(i) => Some(CString::new(i).unwrap()),
-            None => None,
-        };
+        if let Some(datapath) = datapath {
+            tess.datapath = Some(datapath.to_string());
+        }
+        if let Some(lang) = lang {
+            tess.lang = Some(lang.to_string());
+        }
+        let datapath = tess.datapath.as_deref().map(|i| CString::new(i).unwrap());
+        let lang = tess.lang.as_deref().map(|i| CString::new(i).unwrap());
         match tess
             .tess_pl
             .init_4(datapath.as_deref(), lang.as_deref(), tess.oem)
         {
-            Ok(()) => Ok(tess),
-            Err(TessBaseApiInitError {}) => Err(TesseractError::TesseractInitError),
+            Ok(()) => {}
+            Err(TessBaseApiInitError {}) => return Err(TesseractError::TesseractInitError),
+        }
+        for (name, value) in tess.variables.iter() {
+            let name =
+                CString::new(name.as_str()).map_err(|_| TesseractError::TesseractInitError)?;
+            let value =
+                CString::new(value.as_str()).map_err(|_| TesseractError::TesseractInitError)?;
+            tess.tess_pl
+                .set_variable(&name, &value)
+                .map_err(|_| TesseractError::TesseractInitError)?;
         }
+        Ok(tess)
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: &str) -> Result<(), TesseractError> {
+        // Set a tesseract variable and remember it in variables.
+        let c_name = CString::new(name).map_err(|_| TesseractError::TesseracRuntimeError)?;
+        let c_value = CString::new(value).map_err(|_| TesseractError::TesseracRuntimeError)?;
+        self.tess_pl
+            .set_variable(&c_name, &c_value)
+            .map_err(|_| TesseractError::TesseracRuntimeError)?;
+        self.variables.retain(|(variable, _)| variable != name);
+        self.variables.push((name.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    pub fn get_cache_key(&self, image_data: &[u8], output_type: &OutputFileFormat) -> String {
+        // Cache key of an image recognized with the current configuration.
+        let mut variables = self.variables.clone();
+        variables.sort();
+        let variables: Vec<String> = variables
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        let mut parts = vec![
+            ("format", output_type.get_type().to_string()),
+            ("lang", self.lang.clone().unwrap_or_default()),
+            ("psm", self.psm.to_string()),
+            ("oem", self.oem.to_string()),
+            ("dpi", self.dpi.to_string()),
+            ("variables", variables.join(";")),
+            ("tesseract", pl::version().to_string_lossy().into_owned()),
+            (
+                "tessdata",
+                get_tessdata_version(self.datapath.as_deref(), self.lang.as_deref()),
+            ),
+        ];
+        // Keys of documents recognized without normalization don't change.
+        if let Some(normalize) = &self.normalize {
+            parts.push(("normalize", format!("{:?}", normalize)));
+        }
+        get_cache_key(image_data, &parts)
     }
 
     pub fn set_image(&mut self, filename: &str) -> Result<(), TesseractError> {
+        // Panics if the file can't be opened, `OcrEngine::set_image` returns NoSuchFileException instead.
         match pl::leptonica_plumbing::Pix::read(&CString::new(filename).unwrap()) {
             Ok(pix) => self.tess_pl.set_image_2(&pix),
             Err(_) => panic!(
@@ -140,51 +261,354 @@ impl TesseractApi {
         Ok(())
     }
 
+    pub fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError> {
+        // Set an image from an encoded in-memory file (png, jpeg, tiff etc.).
+        // image_data - bytes of the image file.
+        match pl::leptonica_plumbing::Pix::read_mem(image_data) {
+            Ok(pix) => self.tess_pl.set_image_2(&pix),
+            Err(_) => return Err(TesseractError::InvalidImageError),
+        };
+        self.tess_pl.set_source_resolution(self.dpi);
+        Ok(())
+    }
+
+    pub fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        // Recognize the current image and return its output in the given format.
+        // So far, only txt, tsv, hocr, alto, layout, md and json (with the serde feature) are available.
+        // Layout text, Markdown and JSON are converted from the hOCR output, see `convert`.
+        // If normalize is set, the text of the output is normalized, see `normalize`.
+        let text = match output_type {
+            output_type if output_type.is_converted() => {
+                let started = Instant::now();
+                let hocr = self.get_output(&OutputFileFormat::HOCR)?;
+                return convert_recognized(&hocr, output_type, started.elapsed());
+            }
+            OutputFileFormat::TXT => self.tess_pl.get_utf8_text().ok(),
+            OutputFileFormat::TSV => self.tess_pl.get_tsv_text(0).ok(),
+            OutputFileFormat::HOCR => self.tess_pl.get_hocr_text(0).ok(),
+            OutputFileFormat::ALTO => self.tess_pl.get_alto_text(0).ok(),
+            _ => None,
+        };
+        let text = match text {
+            Some(text) => text.as_ref().to_string_lossy().into_owned(),
+            None => return Err(TesseractError::TesseracRuntimeError),
+        };
+        match &self.normalize {
+            Some(options) => normalize_output(&text, output_type, options),
+            None => Ok(text),
+        }
+    }
+
     pub fn image_to_string(
         &mut self,
         filename: &str,
     ) -> futures::future::Ready<Result<String, TesseractError>> {
-        self.set_image(filename);
-        future::ok(
-            self.tess_pl
-                .get_utf8_text()
-                .unwrap()
-                .as_ref()
-                .to_string_lossy()
-                .into_owned(),
-        )
+        future::ready(self.image_to_output(filename, &OutputFileFormat::TXT))
     }
 
     pub fn image_to_hocr(
         &mut self,
         filename: &str,
     ) -> futures::future::Ready<Result<String, TesseractError>> {
-        self.set_image(filename);
-        future::ok(
-            self.tess_pl
-                .get_hocr_text(0)
-                .unwrap()
-                .as_ref()
-                .to_string_lossy()
-                .into_owned(),
-        )
+        future::ready(self.image_to_output(filename, &OutputFileFormat::HOCR))
     }
 
     pub fn image_to_tsv(
         &mut self,
         filename: &str,
     ) -> futures::future::Ready<Result<String, TesseractError>> {
-        self.set_image(filename);
-        future::ok(
-            self.tess_pl
-                .get_tsv_text(0)
-                .unwrap()
-                .as_ref()
-                .to_string_lossy()
-                .into_owned(),
+        future::ready(self.image_to_output(filename, &OutputFileFormat::TSV))
+    }
+
+    pub fn image_to_tsv_rows(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<Vec<TsvRow>, TesseractError>> {
+        future::ready(
+            self.image_to_output(filename, &OutputFileFormat::TSV)
+                .and_then(|tsv| parse_tsv(&tsv)),
         )
     }
 
+    pub fn image_to_tables(
+        &mut self,
+        filename: &str,
+        options: &TableOptions,
+    ) -> futures::future::Ready<Result<Vec<Table>, TesseractError>> {
+        // Recognize an image and detect its tables. If options.rulings is None,
+        // ruling lines are searched in the image unless options.min_ruling_length is 0.
+        future::ready(self.get_tables(filename, options))
+    }
+
+    pub fn image_to_form(
+        &mut self,
+        filename: &str,
+        template: &FormTemplate,
+    ) -> futures::future::Ready<Result<FormResult, TesseractError>> {
+        // Recognize only the fields of a form template, see `form`.
+        future::ready(recognize_form(self, filename, template))
+    }
+
+    pub fn search_images(
+        &mut self,
+        image_array: &[&str],
+        query: &str,
+        options: &SearchOptions,
+    ) -> futures::future::Ready<Result<Vec<SearchMatch>, TesseractError>> {
+        // Find a phrase in the recognized images, the image of a match is the index of its image in image_array.
+        future::ready(self.get_matches(image_array, query, options))
+    }
+
+    pub fn redact_image(
+        &mut self,
+        filename: &str,
+        output_path: &str,
+        options: &RedactionOptions,
+    ) -> futures::future::Ready<Result<Vec<SearchMatch>, TesseractError>> {
+        // Black out the text matching options.patterns and save the redacted image, see `redact`.
+        future::ready(redact_image(self, filename, output_path, options))
+    }
+
+    pub fn image_to_choices(
+        &mut self,
+        filename: &str,
+        top_n: usize,
+    ) -> futures::future::Ready<Result<Vec<WordChoices>, TesseractError>> {
+        // Recognize an image and return its words with at most top_n alternatives of every word and symbol,
+        // see `choices`. The alternatives are reported as set by lstm_choice_mode.
+        future::ready(recognize_choices(self, filename, top_n))
+    }
+
+    pub fn image_to_corrected(
+        &mut self,
+        filename: &str,
+        options: &SpellOptions,
+    ) -> futures::future::Ready<Result<SpellResult, TesseractError>> {
+        // Recognize an image and correct its low-confidence words with dictionaries, see `spell`.
+        future::ready(correct_image(self, filename, options))
+    }
+
+    pub fn image_to_reading_order(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+        options: &ReadingOrderOptions,
+    ) -> futures::future::Ready<Result<String, TesseractError>> {
+        // Recognize an image and write its blocks in reading order, e.g. as text or JSON, see `reading_order`.
+        future::ready(self.get_ordered(filename, output_type, options))
+    }
+
+    pub fn image_to_alto(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<String, TesseractError>> {
+        future::ready(self.image_to_output(filename, &OutputFileFormat::ALTO))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn image_to_json(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<String, TesseractError>> {
+        future::ready(self.image_to_output(filename, &OutputFileFormat::JSON))
+    }
+
+    pub fn image_to_layout_text(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<String, TesseractError>> {
+        future::ready(self.image_to_output(filename, &OutputFileFormat::LAYOUT))
+    }
+
+    pub fn image_to_markdown(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<String, TesseractError>> {
+        future::ready(self.image_to_output(filename, &OutputFileFormat::MARKDOWN))
+    }
+
+    fn get_tables(
+        &mut self,
+        filename: &str,
+        options: &TableOptions,
+    ) -> Result<Vec<Table>, TesseractError> {
+        let hocr = self.image_to_output(filename, &OutputFileFormat::HOCR)?;
+        let rulings = match (&options.rulings, options.min_ruling_length) {
+            (Some(rulings), _) => Some(rulings.clone()),
+            (None, 0) => None,
+            (None, min_length) => Some(find_rulings(filename, min_length)?),
+        };
+        let options = TableOptions {
+            rulings,
+            ..options.clone()
+        };
+        Ok(parse_hocr(&hocr)?
+            .iter()
+            .flat_map(|page| detect_tables(page, &options))
+            .collect())
+    }
+
+    fn get_matches(
+        &mut self,
+        image_array: &[&str],
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchMatch>, TesseractError> {
+        // The regex is checked before the images are recognized.
+        let regex = get_regex(query, options)?;
+        let mut pages = Vec::new();
+        let mut images = Vec::new();
+        for (image, filename) in image_array.iter().enumerate() {
+            let hocr = self.image_to_output(filename, &OutputFileFormat::HOCR)?;
+            let image_pages = parse_hocr(&hocr)?;
+            images.extend(std::iter::repeat(image).take(image_pages.len()));
+            pages.extend(image_pages);
+        }
+        let mut matches = find_matches(&pages, query, regex.as_ref(), options);
+        for found in matches.iter_mut() {
+            found.image = Some(images[found.page]);
+        }
+        Ok(matches)
+    }
+
+    fn get_ordered(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+        options: &ReadingOrderOptions,
+    ) -> Result<String, TesseractError> {
+        let options = ReadingOrderOptions {
+            direction: options
+                .direction
+                .or_else(|| self.lang.as_deref().map(TextDirection::from_lang)),
+            ..*options
+        };
+        let hocr = self.image_to_output(filename, &OutputFileFormat::HOCR)?;
+        let mut pages = parse_hocr(&hocr)?;
+        order_pages(&mut pages, &options);
+        write_doc(&pages, output_type)
+    }
+
+    fn image_to_output(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+    ) -> Result<String, TesseractError> {
+        // Recognize an image file. If the cache is set, the image is recognized only on a cache miss.
+        let cache_key = match &self.cache {
+            Some(_) => std::fs::read(filename)
+                .ok()
+                .map(|image_data| self.get_cache_key(&image_data, output_type)),
+            None => None,
+        };
+        if let (Some(cache), Some(cache_key)) = (self.cache.as_mut(), cache_key.as_deref()) {
+            if let Some(doc) = cache.get(cache_key) {
+                return Ok(doc);
+            }
+        }
+        self.set_image(filename)?;
+        let doc = self.get_output(output_type)?;
+        if let (Some(cache), Some(cache_key)) = (self.cache.as_mut(), cache_key.as_deref()) {
+            cache.put(cache_key, &doc);
+        }
+        Ok(doc)
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn pdf_to_string(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<Vec<PdfPageResult>, TesseractError>> {
+        future::ready(self.pdf_to_output(filename, &OutputFileFormat::TXT))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn pdf_to_hocr(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<Vec<PdfPageResult>, TesseractError>> {
+        future::ready(self.pdf_to_output(filename, &OutputFileFormat::HOCR))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn pdf_to_tsv(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<Vec<PdfPageResult>, TesseractError>> {
+        future::ready(self.pdf_to_output(filename, &OutputFileFormat::TSV))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn pdf_to_alto(
+        &mut self,
+        filename: &str,
+    ) -> futures::future::Ready<Result<Vec<PdfPageResult>, TesseractError>> {
+        future::ready(self.pdf_to_output(filename, &OutputFileFormat::ALTO))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn pdf_to_searchable(
+        &mut self,
+        filename: &str,
+        output_path: &str,
+    ) -> futures::future::Ready<Result<Vec<PdfPageResult>, TesseractError>> {
+        // Recognize a PDF file and save it to output_path with an invisible text layer.
+        // Returns the recognized hOCR pages, pages which could not be recognized are saved unchanged.
+        let options = self.get_pdf_options();
+        future::ready(add_text_layer(self, filename, output_path, &options))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn images_to_pdf(
+        &mut self,
+        image_array: &[&str],
+        output_path: &str,
+        options: &PdfRendererOptions,
+    ) -> futures::future::Ready<Result<PathBuf, TesseractError>> {
+        // Recognize images and save them as one PDF document, returns the path of the document.
+        // The images are recognized by a new engine with the configuration of self.
+        future::ready(render_pdf(self, image_array, output_path, options))
+    }
+
+    #[cfg(feature = "pdf")]
+    pub fn redact_image_to_pdf(
+        &mut self,
+        filename: &str,
+        output_path: &str,
+        options: &RedactionOptions,
+        pdf_options: &PdfRendererOptions,
+    ) -> futures::future::Ready<Result<Vec<SearchMatch>, TesseractError>> {
+        // Black out the text matching options.patterns and save the redacted image as a PDF document.
+        // The text layer is recognized from the redacted image, so it doesn't contain the redacted text.
+        future::ready(redact_image_to_pdf(
+            self,
+            filename,
+            output_path,
+            options,
+            pdf_options,
+        ))
+    }
+
+    #[cfg(feature = "pdf")]
+    fn pdf_to_output(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+    ) -> Result<Vec<PdfPageResult>, TesseractError> {
+        // Recognize every page of a PDF file, results keep the page numbers of the PDF.
+        let options = self.get_pdf_options();
+        recognize_pdf(self, filename, output_type, &options)
+    }
+
+    #[cfg(feature = "pdf")]
+    fn get_pdf_options(&self) -> PdfOptions {
+        // PDF pages are rendered at the resolution set for Tesseract unless pdf_options.dpi is set.
+        PdfOptions {
+            dpi: self.pdf_options.dpi.or(Some(self.dpi)),
+            ..self.pdf_options.clone()
+        }
+    }
+
     #[allow(dead_code)]
     async fn get_text(&mut self) -> futures::future::Ready<Result<String, TesseractError>> {
         future::ok(
@@ -213,36 +637,48 @@ impl TesseractApi {
         rec_vec.collect().await
     }
 
-    pub fn save_doc(&mut self, path: Option<&str>, file_name: Option<&str>, doc_vec: &Vec<String>) {
-        // This function set path and file_name, join texts with \n sep and save doc.
+    pub fn save_doc(
+        &mut self,
+        path: Option<&str>,
+        file_name: Option<&str>,
+        doc_vec: &[String],
+        output_type: &OutputFileFormat,
+    ) {
+        // This function set path and file_name, assemble recognized pages into one document and save doc.
         // path - optional path for saving a doc. If path is None, then file will be saved to a project dir.
         // file_name - optional name of a doc. Default value - data.txt.
         // doc_vec - vector with recognized documents.
-        let binding = get_current_working_dir();
-
-        let path = match path {
-            Some(path) => path,
-            None => binding.as_os_str().to_str().unwrap(),
-        };
-
-        let defaul_filename = String::from("data.txt");
-
-        let file_name = match file_name {
-            Some(file_name) => file_name,
-            None => &defaul_filename,
-        };
-
-        if !Path::new(path).exists() {
-            format!("Path {path} doesnt exist.");
-            panic!("Path {} doesnt exist. Use another path.", path)
+        // output_type - output format of the pages. hOCR and ALTO pages are merged into one well-formed
+        // document, TSV pages get a single header and consecutive page numbers, other formats are joined with \n sep.
+        // Panics if the doc can't be saved, `utils::save_doc` returns a SaveDocError instead.
+        if let Some(path) = path {
+            if !Path::new(path).exists() {
+                panic!("Path {} doesnt exist. Use another path.", path)
+            }
+        }
+        if let Err(error) = save_doc(path, file_name, doc_vec, output_type) {
+            panic!("Unable to save the doc: {:?}", error)
         }
+    }
 
-        let mut data_file =
-            File::create(path.to_owned() + "/" + file_name).expect("creation failed");
-        data_file
-            .write(doc_vec.join("\n").as_bytes())
-            .expect("Unable to write file");
+    pub fn save_pages(
+        &mut self,
+        output_root: Option<&str>,
+        template: &str,
+        image_array: &[&str],
+        doc_vec: &[String],
+        output_type: &OutputFileFormat,
+    ) -> Result<Vec<PathBuf>, TesseractError> {
+        // This function saves one output file per image.
+        // output_root - optional root dir. If it is None, every file is saved next to its source image,
+        // otherwise the source directory structure is mirrored under output_root.
+        // template - naming template, e.g. {stem}.{ext} or {index:04}.hocr.
+        // image_array - paths to the source images.
+        // doc_vec - recognized pages, one per image.
+        // output_type - output format, its type is used as {ext}.
+        save_pages(output_root, template, image_array, doc_vec, output_type)
     }
+
     pub async fn recognize_doc(
         &mut self,
         save_path: Option<&str>,
@@ -256,46 +692,73 @@ impl TesseractApi {
         // doc_name - optional name of a doc. Default value - data.txt
         // image_array - vector which contains a paths to available images.
         // output_type - a str which contains output type value. So far, only 4 types available
-        // (txt, tsv, hocr)
-        let output_type = match output_type {
-            "txt" => OutputFileFormat::TXT,
-            "tsv" => OutputFileFormat::TSV,
-            "hocr" => OutputFileFormat::HOCR,
-            _ => panic!("None existing format {}", output_type),
-        };
-        let doc = match output_type {
-            OutputFileFormat::TXT => {
-                let api_ogject: fn(
-                    &mut TesseractApi,
-                    &str,
-                )
-                    -> future::Ready<Result<String, TesseractError>> =
-                    TesseractApi::image_to_string;
-                self.iter_through_img(api_ogject, image_array).await
-            }
-            OutputFileFormat::TSV => {
-                let api_ogject: fn(
-                    &mut TesseractApi,
-                    &str,
-                )
-                    -> future::Ready<Result<String, TesseractError>> = TesseractApi::image_to_tsv;
-                self.iter_through_img(api_ogject, image_array).await
-            }
-            OutputFileFormat::HOCR => {
-                let api_ogject: fn(
-                    &mut TesseractApi,
-                    &str,
-                )
-                    -> future::Ready<Result<String, TesseractError>> = TesseractApi::image_to_hocr;
-                self.iter_through_img(api_ogject, image_array).await
-            }
-            _ => panic!("None existing format"),
+        // (txt, tsv, hocr, alto)
+        // If page_template is set, every page is saved to its own file and save_path is used as an output root.
+        // If journal is set, pages recognized by previous runs of the job are not recognized again.
+        // An unsupported output type panics, `engine::recognize_doc` returns an error instead.
+        if get_doc_format(output_type).is_none() {
+            panic!("None existing format {}", output_type)
+        }
+        let mut journal = self.journal.take();
+        let page_template = self.page_template.clone();
+        let options = DocOptions {
+            save_path,
+            doc_name,
+            save_doc: save_doc.unwrap_or(false),
+            page_template: page_template.as_deref(),
+            #[cfg(feature = "pdf")]
+            pdf_options: self.get_pdf_options(),
         };
+        let doc = recognize_doc(self, image_array, output_type, &options, journal.as_mut());
+        self.journal = journal;
+        doc
+    }
+}
+
+impl OcrEngine for TesseractApi {
+    fn set_image(&mut self, filename: &str) -> Result<(), TesseractError> {
+        // `TesseractApi::set_image` panics on a missing file, engines return NoSuchFileException.
+        if !Path::new(filename).is_file() {
+            return Err(TesseractError::NoSuchFileException);
+        }
+        TesseractApi::set_image(self, filename)
+    }
+
+    fn set_image_from_mem(&mut self, image_data: &[u8]) -> Result<(), TesseractError> {
+        TesseractApi::set_image_from_mem(self, image_data)
+    }
 
-        let filtered_doc: Vec<String> = doc.into_iter().filter_map(|s| s.ok()).collect();
-        if save_doc.unwrap_or(false) {
-            self.save_doc(save_path, doc_name, &filtered_doc);
+    fn set_source_resolution(&mut self, dpi: i32) {
+        self.tess_pl.set_source_resolution(dpi);
+    }
+
+    fn set_page_seg_mode(&mut self, psm: u32) {
+        self.psm = psm;
+        self.tess_pl.set_page_seg_mode(psm);
+    }
+
+    fn recognize(&mut self) -> Result<(), TesseractError> {
+        self.tess_pl
+            .recognize()
+            .map_err(|_| TesseractError::TesseracRuntimeError)
+    }
+
+    fn get_output(&mut self, output_type: &OutputFileFormat) -> Result<String, TesseractError> {
+        TesseractApi::get_output(self, output_type)
+    }
+
+    fn mean_text_conf(&mut self) -> i32 {
+        self.tess_pl.mean_text_conf()
+    }
+
+    fn recognize_image(
+        &mut self,
+        filename: &str,
+        output_type: &OutputFileFormat,
+    ) -> Result<String, TesseractError> {
+        if !Path::new(filename).is_file() {
+            return Err(TesseractError::NoSuchFileException);
         }
-        Ok(filtered_doc)
+        self.image_to_output(filename, output_type)
     }
 }
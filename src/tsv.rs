@@ -0,0 +1,388 @@
+//! Typed Tesseract TSV.
+//!
+//! `parse_tsv` turns the TSV output (with or without the header) into `TsvRow`s, `write_tsv` writes
+//! rows back in the format produced by Tesseract, and `get_lines`/`get_paragraphs` group word rows
+//! into lines and paragraphs. `get_pages` and `get_rows` convert rows to the `model` types and back.
+//!
+//! # Examples
+//!
+//! ```rust, no_run
+//! use rusttesserast::tsv::{get_lines, parse_tsv};
+//!
+//! let rows = parse_tsv(&std::fs::read_to_string("page.tsv").unwrap()).unwrap();
+//! for line in get_lines(&rows) {
+//!     println!("{:?} {}", line.bbox, line.get_text());
+//! }
+//! ```
+
+use crate::assembly::TSV_HEADER;
+use crate::errors::TesseractError;
+use crate::model::{Block, BoundingBox, Line, Page, Paragraph, Word};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// TsvRow is a row of the Tesseract TSV output.
+pub struct TsvRow {
+    /// Level of the row: 1 - page, 2 - block, 3 - paragraph, 4 - line, 5 - word.
+    pub level: u32,
+    pub page_num: u32,
+    pub block_num: u32,
+    pub par_num: u32,
+    pub line_num: u32,
+    pub word_num: u32,
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Word confidence 0-100, -1 for rows which are not words.
+    pub conf: f64,
+    pub text: String,
+}
+
+impl TsvRow {
+    pub const PAGE: u32 = 1;
+    pub const BLOCK: u32 = 2;
+    pub const PARAGRAPH: u32 = 3;
+    pub const LINE: u32 = 4;
+    pub const WORD: u32 = 5;
+
+    pub fn parse(row: &str) -> Result<TsvRow, TesseractError> {
+        // Parse a row of 12 tab separated columns.
+        let columns: Vec<&str> = row.trim_end_matches(['\r', '\n']).split('\t').collect();
+        if columns.len() != 12 {
+            return Err(TesseractError::ParseError);
+        }
+        let numbers = columns[..10]
+            .iter()
+            .map(|column| {
+                column
+                    .parse::<u32>()
+                    .map_err(|_| TesseractError::ParseError)
+            })
+            .collect::<Result<Vec<u32>, TesseractError>>()?;
+        Ok(TsvRow {
+            level: numbers[0],
+            page_num: numbers[1],
+            block_num: numbers[2],
+            par_num: numbers[3],
+            line_num: numbers[4],
+            word_num: numbers[5],
+            left: numbers[6],
+            top: numbers[7],
+            width: numbers[8],
+            height: numbers[9],
+            conf: columns[10]
+                .parse()
+                .map_err(|_| TesseractError::ParseError)?,
+            text: columns[11].to_string(),
+        })
+    }
+
+    pub fn to_tsv(&self) -> String {
+        // Row without a trailing new line. Confidence of words is written with 6 decimals like Tesseract does.
+        let conf = match self.conf < 0.0 {
+            true => String::from("-1"),
+            false => format!("{:.6}", self.conf),
+        };
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.level,
+            self.page_num,
+            self.block_num,
+            self.par_num,
+            self.line_num,
+            self.word_num,
+            self.left,
+            self.top,
+            self.width,
+            self.height,
+            conf,
+            self.text
+        )
+    }
+
+    pub fn get_bbox(&self) -> BoundingBox {
+        BoundingBox::new(
+            self.left,
+            self.top,
+            self.left + self.width,
+            self.top + self.height,
+        )
+    }
+
+    pub fn is_word(&self) -> bool {
+        self.level == TsvRow::WORD
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// TsvLine is a line rebuilt from TSV rows.
+pub struct TsvLine {
+    pub page_num: u32,
+    pub block_num: u32,
+    pub par_num: u32,
+    pub line_num: u32,
+    pub bbox: BoundingBox,
+    pub words: Vec<TsvRow>,
+}
+
+impl TsvLine {
+    pub fn get_text(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    pub fn get_conf(&self) -> Option<f64> {
+        // Mean confidence of the words.
+        match self.words.len() {
+            0 => None,
+            len => Some(self.words.iter().map(|word| word.conf).sum::<f64>() / len as f64),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// TsvParagraph is a paragraph rebuilt from TSV rows.
+pub struct TsvParagraph {
+    pub page_num: u32,
+    pub block_num: u32,
+    pub par_num: u32,
+    pub bbox: BoundingBox,
+    pub lines: Vec<TsvLine>,
+}
+
+impl TsvParagraph {
+    pub fn get_text(&self) -> String {
+        self.lines
+            .iter()
+            .map(TsvLine::get_text)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+pub fn parse_tsv(tsv: &str) -> Result<Vec<TsvRow>, TesseractError> {
+    // Header and empty lines are skipped.
+    tsv.lines()
+        .filter(|row| !row.is_empty() && !row.starts_with("level\t"))
+        .map(TsvRow::parse)
+        .collect()
+}
+
+pub fn write_tsv(rows: &[TsvRow], header: bool) -> String {
+    // Every row is terminated by \n. Without the header the result has the format of `image_to_tsv`.
+    let mut tsv = String::new();
+    if header {
+        tsv.push_str(TSV_HEADER);
+        tsv.push('\n');
+    }
+    for row in rows {
+        tsv.push_str(&row.to_tsv());
+        tsv.push('\n');
+    }
+    tsv
+}
+
+pub fn get_lines(rows: &[TsvRow]) -> Vec<TsvLine> {
+    // Group word rows by page, block, paragraph and line numbers in the order of the rows.
+    // The box of a line row is used if it is present, otherwise the union of the word boxes.
+    let line_boxes: HashMap<(u32, u32, u32, u32), BoundingBox> = rows
+        .iter()
+        .filter(|row| row.level == TsvRow::LINE)
+        .map(|row| (get_line_key(row), row.get_bbox()))
+        .collect();
+    let mut lines: Vec<TsvLine> = Vec::new();
+    for row in rows.iter().filter(|row| row.is_word()) {
+        let key = get_line_key(row);
+        if lines
+            .last()
+            .map(|line| (line.page_num, line.block_num, line.par_num, line.line_num))
+            != Some(key)
+        {
+            lines.push(TsvLine {
+                page_num: row.page_num,
+                block_num: row.block_num,
+                par_num: row.par_num,
+                line_num: row.line_num,
+                bbox: line_boxes.get(&key).copied().unwrap_or(row.get_bbox()),
+                words: Vec::new(),
+            });
+        }
+        let line = lines.last_mut().unwrap();
+        if !line_boxes.contains_key(&key) {
+            line.bbox = line.bbox.union(&row.get_bbox());
+        }
+        line.words.push(row.clone());
+    }
+    lines
+}
+
+pub fn get_paragraphs(rows: &[TsvRow]) -> Vec<TsvParagraph> {
+    // Group lines by page, block and paragraph numbers.
+    // The box of a paragraph row is used if it is present, otherwise the union of the line boxes.
+    let paragraph_boxes: HashMap<(u32, u32, u32), BoundingBox> = rows
+        .iter()
+        .filter(|row| row.level == TsvRow::PARAGRAPH)
+        .map(|row| ((row.page_num, row.block_num, row.par_num), row.get_bbox()))
+        .collect();
+    let mut paragraphs: Vec<TsvParagraph> = Vec::new();
+    for line in get_lines(rows) {
+        let key = (line.page_num, line.block_num, line.par_num);
+        if paragraphs
+            .last()
+            .map(|paragraph| (paragraph.page_num, paragraph.block_num, paragraph.par_num))
+            != Some(key)
+        {
+            paragraphs.push(TsvParagraph {
+                page_num: line.page_num,
+                block_num: line.block_num,
+                par_num: line.par_num,
+                bbox: paragraph_boxes.get(&key).copied().unwrap_or(line.bbox),
+                lines: Vec::new(),
+            });
+        }
+        let paragraph = paragraphs.last_mut().unwrap();
+        if !paragraph_boxes.contains_key(&key) {
+            paragraph.bbox = paragraph.bbox.union(&line.bbox);
+        }
+        paragraph.lines.push(line);
+    }
+    paragraphs
+}
+
+pub fn get_pages(rows: &[TsvRow]) -> Vec<Page> {
+    // Build pages from rows in the order of the rows. Missing levels are replaced by implicit elements
+    // with the box of their content, words with a negative confidence have no confidence.
+    let mut pages: Vec<Page> = Vec::new();
+    for row in rows {
+        if row.level == TsvRow::PAGE || pages.is_empty() {
+            pages.push(Page {
+                bbox: row.get_bbox(),
+                page_number: row.page_num.saturating_sub(1) as usize,
+                ..Default::default()
+            });
+            if row.level == TsvRow::PAGE {
+                continue;
+            }
+        }
+        let page = pages.last_mut().unwrap();
+        if row.level == TsvRow::BLOCK || page.blocks.is_empty() {
+            page.blocks.push(Block {
+                bbox: row.get_bbox(),
+                ..Default::default()
+            });
+            if row.level == TsvRow::BLOCK {
+                continue;
+            }
+        }
+        let block = page.blocks.last_mut().unwrap();
+        if row.level == TsvRow::PARAGRAPH || block.paragraphs.is_empty() {
+            block.paragraphs.push(Paragraph {
+                bbox: row.get_bbox(),
+                ..Default::default()
+            });
+            if row.level == TsvRow::PARAGRAPH {
+                continue;
+            }
+        }
+        let paragraph = block.paragraphs.last_mut().unwrap();
+        if row.level == TsvRow::LINE || paragraph.lines.is_empty() {
+            paragraph.lines.push(Line {
+                bbox: row.get_bbox(),
+                ..Default::default()
+            });
+            if row.level == TsvRow::LINE {
+                continue;
+            }
+        }
+        if row.is_word() {
+            paragraph.lines.last_mut().unwrap().words.push(Word {
+                text: row.text.clone(),
+                bbox: row.get_bbox(),
+                confidence: match row.conf < 0.0 {
+                    true => None,
+                    false => Some(row.conf as f32),
+                },
+                symbols: Vec::new(),
+            });
+        }
+    }
+    pages
+}
+
+pub fn get_rows(pages: &[Page]) -> Vec<TsvRow> {
+    // Rows of all levels in the order written by Tesseract, pages are numbered consecutively.
+    let mut rows = Vec::new();
+    let row = |level: u32, numbers: [u32; 5], bbox: &BoundingBox| TsvRow {
+        level,
+        page_num: numbers[0],
+        block_num: numbers[1],
+        par_num: numbers[2],
+        line_num: numbers[3],
+        word_num: numbers[4],
+        left: bbox.left,
+        top: bbox.top,
+        width: bbox.get_width(),
+        height: bbox.get_height(),
+        conf: -1.0,
+        text: String::new(),
+    };
+    for (page_index, page) in pages.iter().enumerate() {
+        let page_num = page_index as u32 + 1;
+        rows.push(row(TsvRow::PAGE, [page_num, 0, 0, 0, 0], &page.bbox));
+        for (block_index, block) in page.blocks.iter().enumerate() {
+            let block_num = block_index as u32 + 1;
+            rows.push(row(
+                TsvRow::BLOCK,
+                [page_num, block_num, 0, 0, 0],
+                &block.bbox,
+            ));
+            for (par_index, paragraph) in block.paragraphs.iter().enumerate() {
+                let par_num = par_index as u32 + 1;
+                rows.push(row(
+                    TsvRow::PARAGRAPH,
+                    [page_num, block_num, par_num, 0, 0],
+                    &paragraph.bbox,
+                ));
+                for (line_index, line) in paragraph.lines.iter().enumerate() {
+                    let line_num = line_index as u32 + 1;
+                    rows.push(row(
+                        TsvRow::LINE,
+                        [page_num, block_num, par_num, line_num, 0],
+                        &line.bbox,
+                    ));
+                    for (word_index, word) in line.words.iter().enumerate() {
+                        rows.push(TsvRow {
+                            conf: word.confidence.map(f64::from).unwrap_or(-1.0),
+                            text: word.text.clone(),
+                            ..row(
+                                TsvRow::WORD,
+                                [
+                                    page_num,
+                                    block_num,
+                                    par_num,
+                                    line_num,
+                                    word_index as u32 + 1,
+                                ],
+                                &word.bbox,
+                            )
+                        });
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+fn get_line_key(row: &TsvRow) -> (u32, u32, u32, u32) {
+    (row.page_num, row.block_num, row.par_num, row.line_num)
+}
@@ -1,5 +1,201 @@
+use crate::assembly::assemble_doc;
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use std::fs::{create_dir_all, File};
+use std::io::Write;
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env::current_dir, path::PathBuf};
 
 pub fn get_current_working_dir() -> PathBuf {
     return current_dir().unwrap().to_owned();
 }
+
+pub fn render_file_name(
+    template: &str,
+    source: &str,
+    index: usize,
+    ext: &str,
+) -> Result<String, TesseractError> {
+    // Build an output file name from a naming template.
+    // Supported placeholders:
+    // {stem} - source file name without extension,
+    // {name} - source file name with extension,
+    // {ext} - extension of the output format,
+    // {index} - zero-based position of the image in the input array,
+    // {page} - one-based position of the image in the input array.
+    // Numeric placeholders accept a zero padded width, e.g. {index:04}.
+    let source_path = Path::new(source);
+    let stem = source_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = source_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut file_name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        file_name.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => return Err(TesseractError::SaveDocError),
+        };
+        let (key, width) = match rest[start + 1..end].split_once(':') {
+            Some((key, width)) => match width.parse::<usize>() {
+                Ok(width) => (key, Some(width)),
+                Err(_) => return Err(TesseractError::SaveDocError),
+            },
+            None => (&rest[start + 1..end], None),
+        };
+        match (key, width) {
+            ("stem", None) => file_name.push_str(&stem),
+            ("name", None) => file_name.push_str(&name),
+            ("ext", None) => file_name.push_str(ext),
+            ("index", width) => file_name.push_str(&pad_number(index, width)),
+            ("page", width) => file_name.push_str(&pad_number(index + 1, width)),
+            _ => return Err(TesseractError::SaveDocError),
+        }
+        rest = &rest[end + 1..];
+    }
+    file_name.push_str(rest);
+
+    if file_name.is_empty() || file_name.contains('/') || file_name.contains('\\') {
+        return Err(TesseractError::SaveDocError);
+    }
+    Ok(file_name)
+}
+
+fn pad_number(number: usize, width: Option<usize>) -> String {
+    match width {
+        Some(width) => format!("{:0width$}", number, width = width),
+        None => number.to_string(),
+    }
+}
+
+pub fn get_output_dir(source: &str, output_root: Option<&str>) -> Result<PathBuf, TesseractError> {
+    // Directory for a per-image output file.
+    // If output_root is None, the file is placed next to the source image,
+    // otherwise the source directory structure is mirrored under output_root.
+    // A source path with .. can't be mirrored under output_root, it is rejected with SaveDocError.
+    let parent = Path::new(source)
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_default();
+    match output_root {
+        Some(output_root) => {
+            let mut output_dir = PathBuf::from(output_root);
+            for component in parent.components() {
+                match component {
+                    Component::Normal(part) => output_dir.push(part),
+                    Component::ParentDir => return Err(TesseractError::SaveDocError),
+                    _ => {}
+                }
+            }
+            Ok(output_dir)
+        }
+        None => Ok(parent),
+    }
+}
+
+pub fn save_doc(
+    path: Option<&str>,
+    file_name: Option<&str>,
+    doc_vec: &[String],
+    output_type: &OutputFileFormat,
+) -> Result<(), TesseractError> {
+    // Assemble recognized pages into one document and save it to path/file_name, see `TesseractApi::save_doc`.
+    // A missing path or a failed write is a SaveDocError.
+    let binding = get_current_working_dir();
+
+    let path = match path {
+        Some(path) => path,
+        None => binding.as_os_str().to_str().unwrap(),
+    };
+
+    let defaul_filename = String::from("data.txt");
+
+    let file_name = match file_name {
+        Some(file_name) => file_name,
+        None => &defaul_filename,
+    };
+
+    if !Path::new(path).exists() {
+        return Err(TesseractError::SaveDocError);
+    }
+
+    let mut data_file = File::create(path.to_owned() + "/" + file_name)
+        .map_err(|_| TesseractError::SaveDocError)?;
+    data_file
+        .write_all(assemble_doc(output_type, doc_vec).as_bytes())
+        .map_err(|_| TesseractError::SaveDocError)
+}
+
+pub fn save_pages(
+    output_root: Option<&str>,
+    template: &str,
+    image_array: &[&str],
+    doc_vec: &[String],
+    output_type: &OutputFileFormat,
+) -> Result<Vec<PathBuf>, TesseractError> {
+    // Save one output file per image, see `TesseractApi::save_pages`.
+    let pages: Vec<(usize, &str, &str)> = image_array
+        .iter()
+        .zip(doc_vec.iter())
+        .enumerate()
+        .map(|(index, (image, page))| (index, *image, page.as_str()))
+        .collect();
+    save_indexed_pages(output_root, template, &pages, output_type)
+}
+
+pub fn save_indexed_pages(
+    output_root: Option<&str>,
+    template: &str,
+    pages: &[(usize, &str, &str)],
+    output_type: &OutputFileFormat,
+) -> Result<Vec<PathBuf>, TesseractError> {
+    // Save recognized pages of a batch as (index, image, page), index is the position of the image
+    // in the input array, so {index} and {page} don't shift when other images of the batch fail.
+    let mut saved_pages = Vec::with_capacity(pages.len());
+    for (index, image, page) in pages.iter() {
+        let file_name = render_file_name(template, image, *index, output_type.get_type())?;
+        let output_dir = get_output_dir(image, output_root)?;
+        if !output_dir.as_os_str().is_empty() {
+            create_dir_all(&output_dir).map_err(|_| TesseractError::SaveDocError)?;
+        }
+        let page_path = output_dir.join(file_name);
+        let mut page_file = File::create(&page_path).map_err(|_| TesseractError::SaveDocError)?;
+        page_file
+            .write_all(page.as_bytes())
+            .map_err(|_| TesseractError::SaveDocError)?;
+        saved_pages.push(page_path);
+    }
+    Ok(saved_pages)
+}
+
+pub(crate) fn get_utc_time(time: SystemTime) -> (i64, i64, i64, u64, u64, u64) {
+    // Split a time into UTC year, month, day, hour, minute and second.
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (days, time) = ((seconds / 86400) as i64, seconds % 86400);
+    // Civil date from days since 1970-01-01.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
@@ -0,0 +1,183 @@
+//! JSON-lines worker mode.
+//!
+//! The worker reads one JSON request per line and writes one JSON result per line,
+//! keeping initialized engines of an `EnginePool` warm between requests.
+//! Errors are reported per line, the worker stops only at the end of the input.
+//!
+//! Request: `{"id": 1, "path": "page.png", "format": "txt", "options": {"psm": 6, "lang": "eng"}}`,
+//! `image_base64` can be used instead of `path`. `id` is any JSON value returned as is.
+//!
+//! Result: `{"id": 1, "ok": true, "format": "txt", "output": "...", "confidence": 91, "elapsed_ms": 120}`
+//! or `{"id": 1, "ok": false, "error": "No such file", "code": "NoSuchFileException"}`.
+
+use crate::errors::TesseractError;
+use crate::file_types::OutputFileFormat;
+use crate::pool::EnginePool;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+#[derive(Debug, Default, Deserialize)]
+/// WorkerOptions override the pool configuration for a single request.
+pub struct WorkerOptions {
+    pub psm: Option<u32>,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// WorkerRequest is a single line of the worker input.
+pub struct WorkerRequest {
+    #[serde(default)]
+    pub id: Value,
+    pub path: Option<String>,
+    pub image_base64: Option<String>,
+    pub format: Option<String>,
+    #[serde(default)]
+    pub options: WorkerOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// WorkerResponse is a single line of the worker output.
+pub struct WorkerResponse {
+    pub id: Value,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl WorkerResponse {
+    fn error(id: Value, error: &TesseractError) -> WorkerResponse {
+        WorkerResponse {
+            id,
+            ok: false,
+            format: None,
+            output: None,
+            confidence: None,
+            elapsed_ms: None,
+            error: Some(error.to_string()),
+            code: Some(format!("{:?}", error)),
+        }
+    }
+
+    fn invalid_request(id: Value, error: String) -> WorkerResponse {
+        WorkerResponse {
+            id,
+            ok: false,
+            format: None,
+            output: None,
+            confidence: None,
+            elapsed_ms: None,
+            error: Some(error),
+            code: Some(String::from("InvalidRequest")),
+        }
+    }
+}
+
+pub async fn handle_request(pool: &EnginePool, request: WorkerRequest) -> WorkerResponse {
+    // Recognize a single request on the pool.
+    let started = Instant::now();
+    let format = request.format.unwrap_or_else(|| String::from("txt"));
+    let output_type = match OutputFileFormat::from_type(&format) {
+        Some(
+            output_type @ (OutputFileFormat::TXT
+            | OutputFileFormat::TSV
+            | OutputFileFormat::HOCR
+            | OutputFileFormat::ALTO
+            | OutputFileFormat::JSON
+            | OutputFileFormat::LAYOUT
+            | OutputFileFormat::MARKDOWN),
+        ) => output_type,
+        _ => {
+            return WorkerResponse::invalid_request(
+                request.id,
+                format!("None existing format {}", format),
+            )
+        }
+    };
+    let image_data = match (request.path, request.image_base64) {
+        (Some(path), None) => match tokio::fs::read(&path).await {
+            Ok(image_data) => image_data,
+            Err(_) => {
+                return WorkerResponse::error(request.id, &TesseractError::NoSuchFileException)
+            }
+        },
+        (None, Some(image_base64)) => match STANDARD.decode(image_base64.trim()) {
+            Ok(image_data) => image_data,
+            Err(_) => return WorkerResponse::error(request.id, &TesseractError::InvalidImageError),
+        },
+        _ => {
+            return WorkerResponse::invalid_request(
+                request.id,
+                String::from("Exactly one of path or image_base64 is required"),
+            )
+        }
+    };
+
+    let result = pool
+        .run(
+            request.options.lang.as_deref(),
+            request.options.psm,
+            move |engine| {
+                engine.set_image_from_mem(&image_data)?;
+                let output = engine.get_output(&output_type)?;
+                Ok((output, engine.tess_pl.mean_text_conf()))
+            },
+        )
+        .await;
+    match result {
+        Ok((output, confidence)) => WorkerResponse {
+            id: request.id,
+            ok: true,
+            format: Some(format),
+            output: Some(output),
+            confidence: Some(confidence),
+            elapsed_ms: Some(started.elapsed().as_millis()),
+            error: None,
+            code: None,
+        },
+        Err(error) => WorkerResponse::error(request.id, &error),
+    }
+}
+
+pub async fn run_worker<R, W>(pool: &EnginePool, reader: R, mut writer: W) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    // Process requests line by line until the reader is exhausted.
+    // Empty lines are skipped, malformed lines get an InvalidRequest response.
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<WorkerRequest>(&line) {
+            Ok(request) => handle_request(pool, request).await,
+            Err(error) => {
+                let id = serde_json::from_str::<Value>(&line)
+                    .ok()
+                    .and_then(|value| value.get("id").cloned())
+                    .unwrap_or(Value::Null);
+                WorkerResponse::invalid_request(id, error.to_string())
+            }
+        };
+        let mut response = serde_json::to_string(&response)?;
+        response.push('\n');
+        writer.write_all(response.as_bytes()).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
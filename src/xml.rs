@@ -0,0 +1,268 @@
+//! Minimal XML reader used by the document parsers.
+//!
+//! It builds a tree of elements and text nodes from XML or XHTML produced by OCR engines.
+//! Comments, processing instructions and doctype declarations are skipped,
+//! HTML void elements (e.g. `<meta>`, `<br>`) do not need to be closed.
+
+use crate::errors::TesseractError;
+
+const VOID_ELEMENTS: [&str; 8] = ["area", "base", "br", "col", "hr", "img", "link", "meta"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_local_name(&self) -> &str {
+        // Name without a namespace prefix.
+        self.name.rsplit(':').next().unwrap_or_default()
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.get_attribute("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|name| name == class))
+    }
+
+    pub fn get_children(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn get_child(&self, local_name: &str) -> Option<&Element> {
+        self.get_children()
+            .find(|element| element.get_local_name() == local_name)
+    }
+
+    pub fn find_elements(&self, is_match: &impl Fn(&Element) -> bool) -> Vec<&Element> {
+        // Nearest matching descendants, descendants of matched elements are not searched.
+        let mut found = Vec::new();
+        self.collect_elements(is_match, &mut found);
+        found
+    }
+
+    fn collect_elements<'a>(
+        &'a self,
+        is_match: &impl Fn(&Element) -> bool,
+        found: &mut Vec<&'a Element>,
+    ) {
+        for child in self.get_children() {
+            if is_match(child) {
+                found.push(child);
+            } else {
+                child.collect_elements(is_match, found);
+            }
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        // Concatenated text of all descendants.
+        let mut text = String::new();
+        for node in self.children.iter() {
+            match node {
+                Node::Element(element) => text.push_str(&element.get_text()),
+                Node::Text(value) => text.push_str(value),
+            }
+        }
+        text
+    }
+}
+
+pub(crate) fn parse_xml(document: &str) -> Result<Element, TesseractError> {
+    // Parse a document into a root element without a name, which contains top level nodes.
+    // Fragments with several top level elements are accepted.
+    let mut stack = vec![Element::default()];
+    let mut rest = document;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = skip_past(comment, "-->")?;
+        } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+            let end = data.find("]]>").ok_or(TesseractError::ParseError)?;
+            push_text(&mut stack, data[..end].to_string());
+            rest = &data[end + 3..];
+        } else if let Some(instruction) = rest.strip_prefix("<?") {
+            rest = skip_past(instruction, "?>")?;
+        } else if let Some(declaration) = rest.strip_prefix("<!") {
+            rest = skip_past(declaration, ">")?;
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            let end = tag.find('>').ok_or(TesseractError::ParseError)?;
+            let name = tag[..end].trim();
+            rest = &tag[end + 1..];
+            close_element(&mut stack, name)?;
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let end = find_tag_end(tag)?;
+            let (content, self_closing) = match tag[..end].strip_suffix('/') {
+                Some(content) => (content, true),
+                None => (&tag[..end], false),
+            };
+            rest = &tag[end + 1..];
+            let element = parse_tag(content)?;
+            if self_closing || VOID_ELEMENTS.contains(&element.name.to_lowercase().as_str()) {
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(Node::Element(element));
+            } else {
+                stack.push(element);
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            push_text(&mut stack, decode_entities(&rest[..end])?);
+            rest = &rest[end..];
+        }
+    }
+    if stack.len() != 1 {
+        return Err(TesseractError::ParseError);
+    }
+    Ok(stack.pop().unwrap())
+}
+
+pub(crate) fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn skip_past<'a>(document: &'a str, end: &str) -> Result<&'a str, TesseractError> {
+    match document.find(end) {
+        Some(index) => Ok(&document[index + end.len()..]),
+        None => Err(TesseractError::ParseError),
+    }
+}
+
+fn push_text(stack: &mut [Element], text: String) {
+    if !text.is_empty() {
+        stack.last_mut().unwrap().children.push(Node::Text(text));
+    }
+}
+
+fn close_element(stack: &mut Vec<Element>, name: &str) -> Result<(), TesseractError> {
+    if VOID_ELEMENTS.contains(&name.to_lowercase().as_str()) {
+        return Ok(());
+    }
+    if stack.len() < 2 || stack.last().unwrap().name != name {
+        return Err(TesseractError::ParseError);
+    }
+    let element = stack.pop().unwrap();
+    stack
+        .last_mut()
+        .unwrap()
+        .children
+        .push(Node::Element(element));
+    Ok(())
+}
+
+fn find_tag_end(tag: &str) -> Result<usize, TesseractError> {
+    // Position of '>' closing the tag, '>' inside quoted attribute values is skipped.
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '>') => return Ok(index),
+            (None, '<') => return Err(TesseractError::ParseError),
+            _ => {}
+        }
+    }
+    Err(TesseractError::ParseError)
+}
+
+fn parse_tag(content: &str) -> Result<Element, TesseractError> {
+    let content = content.trim();
+    let name_end = content
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(content.len());
+    let name = &content[..name_end];
+    if name.is_empty() {
+        return Err(TesseractError::ParseError);
+    }
+    let mut element = Element {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let mut rest = content[name_end..].trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let attribute = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let quote = value.chars().next().ok_or(TesseractError::ParseError)?;
+                if quote != '"' && quote != '\'' {
+                    // Unquoted HTML attribute.
+                    let end = value
+                        .find(|c: char| c.is_whitespace())
+                        .unwrap_or(value.len());
+                    rest = value[end..].trim_start();
+                    value[..end].to_string()
+                } else {
+                    let end = value[1..].find(quote).ok_or(TesseractError::ParseError)?;
+                    rest = value[end + 2..].trim_start();
+                    decode_entities(&value[1..end + 1])?
+                }
+            }
+            None => String::new(),
+        };
+        if attribute.is_empty() {
+            return Err(TesseractError::ParseError);
+        }
+        element.attributes.push((attribute.to_string(), value));
+    }
+    Ok(element)
+}
+
+fn decode_entities(text: &str) -> Result<String, TesseractError> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or(TesseractError::ParseError)? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            _ => {
+                let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity
+                        .strip_prefix('#')
+                        .and_then(|decimal| decimal.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+                    .ok_or(TesseractError::ParseError)?
+            }
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
@@ -1,8 +1,51 @@
 use glob::glob;
+use rusttesserast::alto::{parse_alto, write_alto};
+use rusttesserast::assembly::{assemble_doc, TSV_HEADER};
+use rusttesserast::cache::{get_cache_key, get_tessdata_dirs, get_tessdata_version, ResultCache};
+use rusttesserast::choices::{get_alternatives, get_word_choices};
 use rusttesserast::constanst::TesseractDefaultConstants;
+use rusttesserast::convert::{convert, parse_doc, write_doc};
+use rusttesserast::engine::{recognize_doc, DocOptions, FixtureEngine, MockOcrEngine};
 use rusttesserast::errors::TesseractError;
+use rusttesserast::file_types::OutputFileFormat;
+use rusttesserast::form::{
+    find_anchor, parse_value, FieldType, FieldValue, FormAlignment, FormAnchor, FormCorner,
+    FormField, FormTemplate,
+};
+use rusttesserast::hocr::{parse_hocr, write_hocr};
+use rusttesserast::job::JobJournal;
+#[cfg(feature = "serde")]
+use rusttesserast::json::parse_json;
+use rusttesserast::layout::write_layout_text;
+use rusttesserast::markdown::write_markdown;
+use rusttesserast::model::{BoundingBox, Choice, Page, Symbol, Word};
+use rusttesserast::normalize::{
+    normalize_output, normalize_pages, normalize_text, NormalizeOptions,
+};
+use rusttesserast::page_xml::{parse_page_xml, write_page_xml};
+#[cfg(feature = "pdf")]
+use rusttesserast::pdf::{is_pdf, recognize_pdf, PdfOptions, PdfTextLayer};
+#[cfg(feature = "pdf")]
+use rusttesserast::pdf_renderer::{
+    render_pdf, write_metadata, PdfImageCompression, PdfRendererOptions,
+};
+use rusttesserast::reading_order::{
+    get_reading_order, order_page, ReadingOrderOptions, TextDirection,
+};
+use rusttesserast::redact::{find_redactions, RedactionEntity, RedactionOptions, RedactionPattern};
+use rusttesserast::search::{get_distance, search_pages, SearchMode, SearchOptions};
+#[cfg(feature = "pdf")]
+use rusttesserast::searchable_pdf::{add_text_layer, write_text_layer};
+use rusttesserast::spell::{
+    correct_pages, revert_corrections, CorrectionReason, Dictionary, SpellOptions,
+};
+use rusttesserast::subprocess::{get_page_output, TesseractProcess};
+use rusttesserast::table::{detect_tables, write_csv, Rulings, TableOptions};
 use rusttesserast::tess_lib::TesseractApi;
-use rusttesserast::utils::get_current_working_dir;
+use rusttesserast::tsv::{
+    get_lines, get_pages, get_paragraphs, get_rows, parse_tsv, write_tsv, TsvRow,
+};
+use rusttesserast::utils::{get_current_working_dir, get_output_dir, render_file_name, save_doc};
 use std::path::{Path, PathBuf};
 
 #[test]
@@ -195,6 +238,7 @@ fn test_save_doc() -> () {
         Some(String::from("/usr/local/share/tessdata").as_str()),
         None,
         &doc_vec,
+        &OutputFileFormat::TXT,
     );
     let files_: Vec<PathBuf> = glob("/usr/local/share/tessdata/data.txt")
         .unwrap()
@@ -255,6 +299,7 @@ fn test_save_doc_custom_name() -> () {
         Some(String::from("/usr/local/share/tessdata").as_str()),
         Some(String::from("data_custom.txt").as_str()),
         &doc_vec,
+        &OutputFileFormat::TXT,
     );
     let files_: Vec<PathBuf> = glob("/usr/local/share/tessdata/data_custom.txt")
         .unwrap()
@@ -278,6 +323,2140 @@ fn test_save_doc_panic_path() -> () {
         Some(String::from("/usr/local/share/tessdata_fake").as_str()),
         Some(String::from("data_custom.txt").as_str()),
         &doc_vec,
+        &OutputFileFormat::TXT,
     );
     ()
 }
+
+#[test]
+fn test_save_doc_result() -> () {
+    let doc_vec = vec![String::from("page")];
+    assert_eq!(
+        save_doc(
+            Some("tests/missing_dir"),
+            None,
+            &doc_vec,
+            &OutputFileFormat::TXT
+        ),
+        Err(TesseractError::SaveDocError)
+    );
+    assert_eq!(
+        save_doc(
+            Some("tests"),
+            Some("missing_dir/data.txt"),
+            &doc_vec,
+            &OutputFileFormat::TXT
+        ),
+        Err(TesseractError::SaveDocError)
+    );
+    ()
+}
+
+#[test]
+fn test_render_file_name() -> Result<(), TesseractError> {
+    assert_eq!(
+        render_file_name("{stem}.{ext}", "tests/test_img.png", 0, "hocr")?,
+        "test_img.hocr"
+    );
+    assert_eq!(
+        render_file_name("{index:04}.hocr", "tests/test_img.png", 7, "hocr")?,
+        "0007.hocr"
+    );
+    assert_eq!(
+        render_file_name("{name}_{page}.txt", "tests/test_img.png", 0, "txt")?,
+        "test_img.png_1.txt"
+    );
+    assert_eq!(
+        render_file_name("{unknown}.txt", "tests/test_img.png", 0, "txt"),
+        Err(TesseractError::SaveDocError)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_save_pages_output_root() -> Result<(), TesseractError> {
+    let mut tesseract_base = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let output_root = std::env::temp_dir().join("rusttesserast_pages");
+    let image_array = vec!["tests/test_img.png", "tests/test_img_2.png"];
+    let doc_vec = vec![String::from("first"), String::from("second")];
+    let saved_pages = tesseract_base.save_pages(
+        output_root.to_str(),
+        "{stem}_{index:02}.{ext}",
+        &image_array,
+        &doc_vec,
+        &OutputFileFormat::TXT,
+    )?;
+    assert_eq!(
+        saved_pages,
+        vec![
+            output_root.join("tests/test_img_00.txt"),
+            output_root.join("tests/test_img_2_01.txt")
+        ]
+    );
+    assert_eq!(std::fs::read_to_string(&saved_pages[1]).unwrap(), "second");
+    Ok(())
+}
+
+#[test]
+fn test_assemble_tsv() -> () {
+    let page = include_str!("data.txt").to_string();
+    let doc = assemble_doc(&OutputFileFormat::TSV, &vec![page.clone(), page]);
+    let rows: Vec<&str> = doc.lines().collect();
+    assert_eq!(rows[0], TSV_HEADER);
+    assert_eq!(doc.matches("level\t").count(), 1);
+    assert_eq!(rows.len(), 21);
+    assert!(rows[1].starts_with("1\t1\t"));
+    assert!(rows[11].starts_with("1\t2\t"));
+    ()
+}
+
+#[test]
+fn test_assemble_hocr() -> () {
+    let page = String::from(
+        "  <div class='ocr_page' id='page_1' title='image \"\"; bbox 0 0 343 430; ppageno 0'>\n   \
+         <div class='ocr_carea' id='block_1_1'>\n    \
+         <span class='ocrx_word' id='word_1_1'>Hello</span>\n   \
+         </div>\n  \
+         </div>\n",
+    );
+    let doc = assemble_doc(&OutputFileFormat::HOCR, &vec![page.clone(), page]);
+    assert_eq!(doc.matches("<html").count(), 1);
+    assert_eq!(doc.matches("</body>").count(), 1);
+    assert!(doc.contains("id='page_1'"));
+    assert!(doc.contains("id='page_2'"));
+    assert!(doc.contains("id='word_2_1'"));
+    assert!(doc.contains("ppageno 1"));
+    ()
+}
+
+#[test]
+fn test_assemble_alto() -> Result<(), TesseractError> {
+    // Tesseract numbers the ids of every element type from 0 on every page.
+    let page = String::from(
+        "\t\t<Page WIDTH=\"343\" HEIGHT=\"430\" PHYSICAL_IMG_NR=\"0\" ID=\"page_0\">\n\
+         \t\t\t<PrintSpace HPOS=\"0\" VPOS=\"0\" WIDTH=\"343\" HEIGHT=\"430\">\n\
+         \t\t\t\t<TextBlock ID=\"block_0\" HPOS=\"10\" VPOS=\"10\" WIDTH=\"130\" HEIGHT=\"50\">\n\
+         \t\t\t\t\t<TextLine ID=\"line_0\" HPOS=\"10\" VPOS=\"10\" WIDTH=\"130\" HEIGHT=\"20\">\n\
+         \t\t\t\t\t\t<String ID=\"string_0\" HPOS=\"10\" VPOS=\"10\" WIDTH=\"60\" HEIGHT=\"20\" WC=\"0.95\" CONTENT=\"Hello\"/>\n\
+         \t\t\t\t\t\t<String ID=\"string_1\" HPOS=\"80\" VPOS=\"10\" WIDTH=\"60\" HEIGHT=\"20\" WC=\"0.95\" CONTENT=\"World\"/>\n\
+         \t\t\t\t\t</TextLine>\n\
+         \t\t\t\t\t<TextLine ID=\"line_1\" HPOS=\"10\" VPOS=\"40\" WIDTH=\"130\" HEIGHT=\"20\">\n\
+         \t\t\t\t\t\t<String ID=\"string_2\" HPOS=\"10\" VPOS=\"40\" WIDTH=\"60\" HEIGHT=\"20\" WC=\"0.95\" CONTENT=\"Second\"/>\n\
+         \t\t\t\t\t\t<String ID=\"string_3\" HPOS=\"80\" VPOS=\"40\" WIDTH=\"60\" HEIGHT=\"20\" WC=\"0.95\" CONTENT=\"line\"/>\n\
+         \t\t\t\t\t</TextLine>\n\
+         \t\t\t\t</TextBlock>\n\
+         \t\t\t</PrintSpace>\n\
+         \t\t</Page>\n",
+    );
+    let doc = assemble_doc(&OutputFileFormat::ALTO, &vec![page.clone(), page]);
+    assert_eq!(doc.matches("<alto").count(), 1);
+    assert_eq!(doc.matches("</Layout>").count(), 1);
+    assert!(doc.contains("http://www.loc.gov/standards/alto/ns-v4#"));
+    assert!(!doc.contains("ns-v3#"));
+    assert!(doc.contains("ID=\"page_1\""));
+    assert!(doc.contains("PHYSICAL_IMG_NR=\"1\""));
+    assert!(doc.contains("ID=\"string_1_3\""));
+    let ids: Vec<&str> = doc
+        .split(" ID=\"")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('"').unwrap()])
+        .collect();
+    assert_eq!(ids.len(), 2 * 8 + 1);
+    assert_eq!(
+        ids.iter().collect::<std::collections::HashSet<_>>().len(),
+        ids.len()
+    );
+    let pages = parse_alto(&doc)?;
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1].get_text(), "Hello World\nSecond line");
+    // Assembled and written documents have the same header.
+    let written = write_alto(&pages);
+    assert_eq!(
+        written.split("<Layout>").next(),
+        doc.split("<Layout>").next()
+    );
+    // Written pages already carry the page index, it is replaced.
+    let written = write_alto(&pages[1..]);
+    assert!(written.contains("ID=\"string_0_3\""));
+    let doc = assemble_doc(&OutputFileFormat::ALTO, &vec![written.clone(), written]);
+    assert!(doc.contains("ID=\"string_1_3\""));
+    assert!(!doc.contains("ID=\"string_1_0_"));
+    Ok(())
+}
+
+#[test]
+fn test_output_file_format_from_type() -> () {
+    for output_type in ["pdf", "alto", "hocr", "tsv", "txt", "osd", "page"] {
+        assert_eq!(
+            OutputFileFormat::from_type(output_type).unwrap().get_type(),
+            output_type
+        );
+    }
+    assert!(OutputFileFormat::from_type("tsvs").is_none());
+    ()
+}
+
+#[test]
+fn test_memory_cache_lru() -> () {
+    let mut cache = ResultCache::memory(2, 1024);
+    cache.put("a", "first");
+    cache.put("b", "second");
+    assert_eq!(cache.get("a"), Some(String::from("first")));
+    cache.put("c", "third");
+    assert_eq!(cache.get("b"), None);
+    assert_eq!(cache.get("c"), Some(String::from("third")));
+    let stats = cache.get_stats();
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.evictions, 1);
+    assert_eq!(stats.entries, 2);
+    assert_eq!(stats.bytes, "first".len() + "third".len());
+    ()
+}
+
+#[test]
+fn test_disk_cache() -> Result<(), TesseractError> {
+    let dir = std::env::temp_dir().join("rusttesserast_cache");
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut cache = ResultCache::disk(dir.to_str().unwrap(), 8)?;
+    cache.put("a", "first");
+    assert!(Path::new(&dir).join("a.cache").exists());
+    assert_eq!(cache.get("a"), Some(String::from("first")));
+    cache.put("b", "second");
+    assert_eq!(cache.get_stats().entries, 1);
+    assert_eq!(cache.get("b"), Some(String::from("second")));
+    Ok(())
+}
+
+#[test]
+fn test_disk_cache_lru() -> Result<(), TesseractError> {
+    let dir = std::env::temp_dir().join("rusttesserast_cache_lru");
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut cache = ResultCache::disk(dir.to_str().unwrap(), 12)?;
+    cache.put("a", "first");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    cache.put("b", "second");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    // A hit makes a the most recently used entry, so b is evicted instead.
+    assert_eq!(cache.get("a"), Some(String::from("first")));
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    cache.put("c", "third");
+    assert_eq!(cache.get_stats().evictions, 1);
+    assert!(Path::new(&dir).join("a.cache").exists());
+    assert!(!Path::new(&dir).join("b.cache").exists());
+    assert!(Path::new(&dir).join("c.cache").exists());
+    Ok(())
+}
+
+#[test]
+fn test_tessdata_version() -> () {
+    let dir = std::env::temp_dir().join("rusttesserast_tessdata");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("xyz.traineddata"), "model").unwrap();
+    let version = get_tessdata_version(dir.to_str(), Some("xyz+abc"));
+    assert!(version.starts_with("xyz:5:"));
+    assert!(version.ends_with(",abc:missing"));
+    assert_eq!(get_tessdata_dirs(dir.to_str()), vec![dir.clone()]);
+    // Without datapath the version is resolved from the directories tesseract searches.
+    let dirs = get_tessdata_dirs(None);
+    assert!(dirs.contains(&PathBuf::from("/usr/local/share/tessdata")));
+    if let Some(prefix) = std::env::var_os("TESSDATA_PREFIX") {
+        assert_eq!(dirs[1], PathBuf::from(prefix));
+    }
+    ()
+}
+
+#[test]
+fn test_cache_key() -> () {
+    let config = vec![("psm", String::from("3")), ("lang", String::from("eng"))];
+    let key = get_cache_key(b"image", &config);
+    assert_eq!(key.len(), 64);
+    assert_eq!(key, get_cache_key(b"image", &config));
+    assert_ne!(key, get_cache_key(b"other image", &config));
+    assert_ne!(
+        key,
+        get_cache_key(
+            b"image",
+            &[("psm", String::from("4")), ("lang", String::from("eng"))]
+        )
+    );
+    ()
+}
+
+#[test]
+fn test_recognize_doc_fixture_engine() -> Result<(), TesseractError> {
+    let mut engine = FixtureEngine::new();
+    engine.add_page("a.png", OutputFileFormat::TXT, "Hello");
+    engine.add_page("b.png", OutputFileFormat::TXT, "World!");
+    let doc = recognize_doc(
+        &mut engine,
+        vec!["a.png", "missing.png", "b.png"],
+        "txt",
+        &DocOptions::default(),
+        None,
+    )?;
+    assert_eq!(doc, vec![String::from("Hello"), String::from("World!")]);
+    assert_eq!(engine.get_recognized(), ["a.png", "b.png"]);
+    assert_eq!(
+        recognize_doc(
+            &mut engine,
+            vec!["a.png"],
+            "tsvs",
+            &DocOptions::default(),
+            None
+        ),
+        Err(TesseractError::TesseracRuntimeError)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_recognize_doc_missing_image() -> Result<(), TesseractError> {
+    // Missing files are skipped by the batch instead of panicking in `TesseractApi::set_image`.
+    use rusttesserast::engine::OcrEngine;
+    let mut tesseract_base = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let doc = recognize_doc(
+        &mut tesseract_base,
+        vec!["tests/missing.png", "tests/test_img.png"],
+        "txt",
+        &DocOptions::default(),
+        None,
+    )?;
+    assert_eq!(doc.len(), 1);
+    assert_eq!(
+        OcrEngine::set_image(&mut tesseract_base, "tests/missing.png"),
+        Err(TesseractError::NoSuchFileException)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_recognize_doc_page_index() -> Result<(), TesseractError> {
+    let mut engine = FixtureEngine::new();
+    engine.add_page("a.png", OutputFileFormat::TXT, "Hello");
+    engine.add_page("b.png", OutputFileFormat::TXT, "World!");
+    let output_root = std::env::temp_dir().join("rusttesserast_page_index");
+    let options = DocOptions {
+        save_path: output_root.to_str(),
+        save_doc: true,
+        page_template: Some("{stem}_{index}.{ext}"),
+        ..Default::default()
+    };
+    recognize_doc(
+        &mut engine,
+        vec!["a.png", "missing.png", "b.png"],
+        "txt",
+        &options,
+        None,
+    )?;
+    assert!(output_root.join("a_0.txt").exists());
+    assert_eq!(
+        std::fs::read_to_string(output_root.join("b_2.txt")).unwrap(),
+        "World!"
+    );
+    assert_eq!(
+        get_output_dir("../scans/a.png", output_root.to_str()),
+        Err(TesseractError::SaveDocError)
+    );
+    assert_eq!(
+        get_output_dir("../scans/a.png", None)?,
+        Path::new("../scans")
+    );
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_recognize_pdf_text_layer() -> Result<(), TesseractError> {
+    assert!(is_pdf("scans/A.PDF"));
+    assert!(!is_pdf("tests/test_img.png"));
+    let mut engine = FixtureEngine::new();
+    let options = PdfOptions {
+        dpi: Some(144),
+        text_layer: PdfTextLayer::Reuse,
+        ..Default::default()
+    };
+    let pages = recognize_pdf(
+        &mut engine,
+        "tests/test_text.pdf",
+        &OutputFileFormat::TXT,
+        &options,
+    )?;
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].page_number, 1);
+    assert!(pages[0].text_layer);
+    assert_eq!(pages[0].output.as_deref().map(str::trim), Ok("Hello World"));
+    let pages = recognize_pdf(
+        &mut engine,
+        "tests/test_text.pdf",
+        &OutputFileFormat::HOCR,
+        &options,
+    )?;
+    let hocr = parse_hocr(pages[0].output.as_ref().unwrap())?;
+    assert_eq!(hocr[0].bbox, BoundingBox::new(0, 0, 400, 200));
+    let words: Vec<&Word> = hocr[0].get_words().collect();
+    assert_eq!(words.len(), 2);
+    assert!(words[0].bbox.right <= words[1].bbox.left);
+    let skip = PdfOptions {
+        text_layer: PdfTextLayer::Skip,
+        ..Default::default()
+    };
+    assert!(recognize_pdf(
+        &mut engine,
+        "tests/test_text.pdf",
+        &OutputFileFormat::TXT,
+        &skip
+    )?
+    .is_empty());
+    // A corrupt PDF fails alone, the other files of the batch are recognized.
+    let broken = std::env::temp_dir().join("rusttesserast_pdf/broken.pdf");
+    std::fs::create_dir_all(broken.parent().unwrap()).unwrap();
+    std::fs::write(&broken, "%PDF-1.4 broken").unwrap();
+    let doc = recognize_doc(
+        &mut engine,
+        vec![
+            "tests/test_text.pdf",
+            broken.to_str().unwrap(),
+            "tests/test_text.pdf",
+        ],
+        "txt",
+        &DocOptions {
+            pdf_options: options,
+            ..Default::default()
+        },
+        None,
+    )?;
+    assert_eq!(doc.len(), 2);
+    assert!(engine.get_recognized().is_empty());
+    assert_eq!(
+        recognize_pdf(
+            &mut engine,
+            "tests/missing.pdf",
+            &OutputFileFormat::TXT,
+            &skip
+        ),
+        Err(TesseractError::NoSuchFileException)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_recognize_doc_without_pdfium() -> Result<(), TesseractError> {
+    // If pdfium can't be loaded, only the PDF files fail and the images are recognized.
+    let mut engine = FixtureEngine::new();
+    engine.add_page("tests/test_img.png", OutputFileFormat::TXT, "Hello World");
+    let doc = recognize_doc(
+        &mut engine,
+        vec!["tests/test_text.pdf", "tests/test_img.png"],
+        "txt",
+        &DocOptions {
+            pdf_options: PdfOptions {
+                library_path: Some(String::from("tests/missing_pdfium")),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    )?;
+    assert_eq!(doc, vec![String::from("Hello World")]);
+    assert_eq!(engine.get_recognized(), &["tests/test_img.png"]);
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_write_text_layer() -> Result<(), TesseractError> {
+    let output = std::env::temp_dir().join("rusttesserast_pdf/searchable.pdf");
+    let output = output.to_str().unwrap();
+    let hocr = "<div class='ocr_page' title='bbox 0 0 400 200'>\
+        <span class='ocr_line' title='bbox 40 76 220 104; baseline 0 -4'>\
+        <span class='ocrx_word' title='bbox 40 76 120 104'>Scan</span>\
+        <span class='ocrx_word' title='bbox 130 76 220 104'>&#233;t&#233;</span></span></div>";
+    write_text_layer("tests/test_text.pdf", output, &parse_hocr(hocr)?, 144)?;
+    let pdf = String::from_utf8_lossy(&std::fs::read(output).unwrap()).into_owned();
+    assert!(pdf.contains("(Hello World) Tj"));
+    assert!(pdf.contains("/GlyphLessFont"));
+    assert!(pdf.contains("3 Tr"));
+    assert!(pdf.contains("1 0 0 1 20.00 50.00 Tm"));
+    assert!(pdf.contains("<005300630061006E> Tj"));
+    assert!(pdf.contains("<00E9007400E9> Tj"));
+    let mut pages = parse_hocr(hocr)?;
+    pages[0].page_number = 1;
+    assert_eq!(
+        write_text_layer("tests/test_text.pdf", output, &pages, 144),
+        Err(TesseractError::PdfError)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_add_text_layer_bad_page() -> Result<(), TesseractError> {
+    let mut engine = MockOcrEngine::new();
+    engine.expect_set_image_from_mem().returning(|_| Ok(()));
+    // Pages are recognized at the dpi they are rendered at.
+    engine
+        .expect_set_source_resolution()
+        .withf(|dpi| *dpi == TesseractDefaultConstants::DEFAULT_DPI)
+        .times(1)
+        .return_const(());
+    engine.expect_recognize().returning(|| Ok(()));
+    engine
+        .expect_get_output()
+        .returning(|_| Ok(String::from("<div class='ocr_page' title='bbox 0 0 a b'>")));
+    let output = std::env::temp_dir().join("rusttesserast_pdf/bad_page.pdf");
+    std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+    // The page with unreadable hOCR is saved unchanged and its error is returned.
+    let results = add_text_layer(
+        &mut engine,
+        "tests/test_text.pdf",
+        output.to_str().unwrap(),
+        &PdfOptions::default(),
+    )?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].output, Err(TesseractError::ParseError));
+    let pdf = String::from_utf8_lossy(&std::fs::read(&output).unwrap()).into_owned();
+    assert!(!pdf.contains("/GlyphLessFont"));
+    Ok(())
+}
+
+#[test]
+fn test_recognize_doc_mock_engine() -> Result<(), TesseractError> {
+    let mut engine = MockOcrEngine::new();
+    engine
+        .expect_recognize_image()
+        .withf(|image, output_type| image == "a.png" && *output_type == OutputFileFormat::HOCR)
+        .times(1)
+        .returning(|_, _| Ok(String::from("<div class='ocr_page'></div>")));
+    let doc = recognize_doc(
+        &mut engine,
+        vec!["a.png"],
+        "hocr",
+        &DocOptions::default(),
+        None,
+    )?;
+    assert_eq!(doc, vec![String::from("<div class='ocr_page'></div>")]);
+    Ok(())
+}
+
+#[test]
+fn test_subprocess_page_output() -> () {
+    let hocr =
+        "<html>\n <body>\n  <div class='ocr_page' id='page_1'>\n  </div>\n </body>\n</html>\n";
+    assert_eq!(
+        get_page_output(&OutputFileFormat::HOCR, hocr),
+        "  <div class='ocr_page' id='page_1'>\n  </div>\n"
+    );
+    let tsv = format!("{}\n1\t1\t0\t0\t0\t0\t0\t0\t343\t430\t-1\t\n", TSV_HEADER);
+    assert_eq!(
+        get_page_output(&OutputFileFormat::TSV, &tsv),
+        "1\t1\t0\t0\t0\t0\t0\t0\t343\t430\t-1\t\n"
+    );
+    assert_eq!(
+        get_page_output(&OutputFileFormat::TXT, "Hello World!\n\u{c}"),
+        "Hello World!\n"
+    );
+    ()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_subprocess_timeout() -> Result<(), TesseractError> {
+    use rusttesserast::engine::OcrEngine;
+    use std::os::unix::fs::PermissionsExt;
+    let program = std::env::temp_dir().join("rusttesserast_slow_tesseract.sh");
+    std::fs::write(
+        &program,
+        "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo \"tesseract 5.3.0\"; exit 0; fi\nsleep 10\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let mut engine = TesseractProcess::new(
+        Some(TesseractProcess {
+            program: program.to_str().unwrap().to_string(),
+            timeout: 1,
+            ..Default::default()
+        }),
+        None,
+        Some(String::from("eng").as_str()),
+    )?;
+    assert_eq!(engine.get_version()?, "tesseract 5.3.0");
+    engine.set_image("tests/test_img.png")?;
+    let started = std::time::Instant::now();
+    assert_eq!(
+        engine.get_output(&OutputFileFormat::TXT),
+        Err(TesseractError::TesseractTimeoutError)
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(
+        TesseractProcess::new(
+            Some(TesseractProcess {
+                program: String::from("/nonexistent/tesseract"),
+                ..Default::default()
+            }),
+            None,
+            None,
+        )
+        .unwrap_err(),
+        TesseractError::TesseractInitError
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_subprocess_runs() -> Result<(), TesseractError> {
+    use rusttesserast::engine::OcrEngine;
+    use std::os::unix::fs::PermissionsExt;
+    // The fake executable appends a line to the log on every recognition.
+    let log = std::env::temp_dir().join("rusttesserast_runs.log");
+    let _ = std::fs::remove_file(&log);
+    let program = std::env::temp_dir().join("rusttesserast_counting_tesseract.sh");
+    std::fs::write(
+        &program,
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo \"tesseract 5.3.0\"; exit 0; fi\n\
+             echo run >> {}\necho \"<div class='ocr_page' title='bbox 0 0 10 10'></div>\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let mut engine = TesseractProcess::new(
+        Some(TesseractProcess {
+            program: program.to_str().unwrap().to_string(),
+            ..Default::default()
+        }),
+        None,
+        None,
+    )?;
+    let runs = || {
+        std::fs::read_to_string(&log)
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+    assert_eq!(
+        engine.recognize(),
+        Err(TesseractError::TesseracRuntimeError)
+    );
+    engine.recognize_image("tests/test_img.png", &OutputFileFormat::HOCR)?;
+    assert_eq!(runs(), 1);
+    // Other formats of the same image are new runs, a repeated format is served from the outputs.
+    engine.get_output(&OutputFileFormat::TSV)?;
+    engine.get_output(&OutputFileFormat::HOCR)?;
+    assert_eq!(runs(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_parse_hocr() -> Result<(), TesseractError> {
+    let hocr = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <html xmlns=\"http://www.w3.org/1999/xhtml\">\n<head><meta charset=\"utf-8\"></head>\n<body>\n\
+        <div class='ocr_page' id='page_1' title='image \"scan.png\"; bbox 0 0 343 430; ppageno 2'>\n\
+         <div class='ocr_carea' id='block_1_1' title=\"bbox 36 92 309 136\">\n\
+          <p class='ocr_par' id='par_1_1' lang='eng' title=\"bbox 36 92 309 136\">\n\
+           <span class='ocr_line' id='line_1_1' title=\"bbox 36 92 309 136; baseline 0 -8; x_size 44\">\n\
+            <span class='ocrx_word' id='word_1_1' title='bbox 36 92 181 136; x_wconf 96'>Hello</span>\n\
+            <span class='ocrx_word' id='word_1_2' title='bbox 198 92 309 136; x_wconf 91.5'><strong>World&amp;!</strong></span>\n\
+           </span>\n\
+          </p>\n\
+         </div>\n\
+        </div>\n</body>\n</html>\n";
+    let pages = parse_hocr(hocr)?;
+    assert_eq!(pages.len(), 1);
+    let page = &pages[0];
+    assert_eq!(page.page_number, 2);
+    assert_eq!(page.image.as_deref(), Some("scan.png"));
+    assert_eq!(page.bbox, BoundingBox::new(0, 0, 343, 430));
+    let line = page.get_lines().next().unwrap();
+    assert_eq!(line.baseline, Some((0.0, -8.0)));
+    assert_eq!(line.x_size, Some(44.0));
+    let words: Vec<&Word> = page.get_words().collect();
+    assert_eq!(words[1].text, "World&!");
+    assert_eq!(words[1].confidence, Some(91.5));
+    assert_eq!(words[1].bbox.get_width(), 111);
+    assert_eq!(page.get_text(), "Hello World&!");
+    Ok(())
+}
+
+#[test]
+fn test_parse_hocr_errors() -> () {
+    let line = "<span class='ocr_line' title='bbox 0 0 10 10'><span class='ocrx_word' title='bbox 0 0 10 10'>a</span></span>";
+    let pages = parse_hocr(&format!("<div class='ocr_page'>{}</div>", line)).unwrap();
+    assert_eq!(pages[0].blocks[0].paragraphs[0].lines[0].get_text(), "a");
+    assert_eq!(pages[0].bbox, BoundingBox::new(0, 0, 10, 10));
+    assert_eq!(
+        parse_hocr("<div class='ocr_carea'></div>"),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(
+        parse_hocr(&format!("<div class='ocr_page'>{}", line)),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(
+        parse_hocr(
+            "<div class='ocr_page'><span class='ocrx_word' title='bbox 0 0 x 10'>a</span></div>"
+        ),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(
+        parse_hocr("<div class='ocr_page'><span class='ocrx_word'>a</span></div>"),
+        Err(TesseractError::ParseError)
+    );
+    ()
+}
+
+#[test]
+fn test_tsv_rows() -> Result<(), TesseractError> {
+    let tsv = include_str!("data.txt");
+    let rows = parse_tsv(tsv)?;
+    assert_eq!(rows.len(), 10);
+    assert_eq!(rows[4].text, "Helto");
+    assert_eq!(rows[4].conf, 59.081154);
+    assert_eq!(write_tsv(&rows, false), tsv);
+    assert_eq!(parse_tsv(&write_tsv(&rows, true))?, rows);
+    let lines = get_lines(&rows);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].get_text(), "Helto World!");
+    assert_eq!(lines[0].bbox, BoundingBox::new(101, 4, 184, 14));
+    let paragraphs = get_paragraphs(&rows);
+    assert_eq!(paragraphs.len(), 1);
+    assert_eq!(paragraphs[0].lines.len(), 2);
+    assert_eq!(paragraphs[0].bbox, BoundingBox::new(94, 4, 206, 29));
+    assert_eq!(TsvRow::parse("5\t1\t1"), Err(TesseractError::ParseError));
+    Ok(())
+}
+
+fn get_word_boxes(pages: &[Page]) -> Vec<(String, BoundingBox)> {
+    pages
+        .iter()
+        .flat_map(|page| page.get_words())
+        .map(|word| (word.text.clone(), word.bbox))
+        .collect()
+}
+
+#[test]
+fn test_convert_formats() -> Result<(), TesseractError> {
+    let tsv = include_str!("data.txt");
+    let pages = get_pages(&parse_tsv(tsv)?);
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].get_text(), "Helto World!\nI use tesseract");
+    assert_eq!(write_tsv(&get_rows(&pages), false), tsv);
+    let words = get_word_boxes(&pages);
+    assert_eq!(get_word_boxes(&parse_hocr(&write_hocr(&pages))?), words);
+    let alto = parse_alto(&write_alto(&pages))?;
+    assert_eq!(get_word_boxes(&alto), words);
+    assert_eq!(
+        alto[0].blocks[0].paragraphs[0].bbox,
+        pages[0].blocks[0].paragraphs[0].bbox
+    );
+    assert_eq!(alto[0].get_words().next().unwrap().confidence, Some(59.08));
+    let page = parse_page_xml(&write_page_xml(&pages[0]))?;
+    assert_eq!(get_word_boxes(&[page]), words);
+    let hocr = convert(tsv, &OutputFileFormat::TSV, &OutputFileFormat::HOCR)?;
+    let alto = convert(&hocr, &OutputFileFormat::HOCR, &OutputFileFormat::ALTO)?;
+    let page_xml = convert(&alto, &OutputFileFormat::ALTO, &OutputFileFormat::PAGE)?;
+    assert_eq!(
+        convert(&page_xml, &OutputFileFormat::PAGE, &OutputFileFormat::TXT)?,
+        "Helto World!\nI use tesseract"
+    );
+    assert_eq!(
+        get_word_boxes(&parse_doc(&page_xml, &OutputFileFormat::PAGE)?),
+        words
+    );
+    Ok(())
+}
+
+#[test]
+fn test_convert_errors() -> () {
+    let pages = get_pages(&parse_tsv(include_str!("data.txt")).unwrap());
+    let two_pages = [pages[0].clone(), pages[0].clone()];
+    assert_eq!(
+        write_doc(&two_pages, &OutputFileFormat::PAGE),
+        Err(TesseractError::TesseracRuntimeError)
+    );
+    assert_eq!(
+        write_doc(&pages, &OutputFileFormat::PDF),
+        Err(TesseractError::TesseracRuntimeError)
+    );
+    assert_eq!(
+        parse_doc("text", &OutputFileFormat::TXT),
+        Err(TesseractError::TesseracRuntimeError)
+    );
+    assert_eq!(
+        parse_alto("<alto><Description><MeasurementUnit>mm10</MeasurementUnit></Description><Layout/></alto>"),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(
+        parse_page_xml("<PcGts><Page imageWidth='10'></Page></PcGts>"),
+        Err(TesseractError::ParseError)
+    );
+    ()
+}
+
+#[test]
+fn test_job_journal_status() -> Result<(), TesseractError> {
+    let dir = std::env::temp_dir().join("rusttesserast_jobs");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("scan.journal"),
+        "job\ttxt\t3\n\
+         page\t0\ta.png\tHello\\nWorld\n\
+         error\t1\tb.png\tNo such file\n\
+         page\t2\tc.pn",
+    )
+    .unwrap();
+    let journal = JobJournal::open(dir.to_str().unwrap(), "scan")?;
+    let status = journal.get_status();
+    assert_eq!(status.total, 3);
+    assert_eq!(status.completed, 1);
+    assert_eq!(status.failed, 1);
+    assert!(!status.finished);
+    assert_eq!(
+        std::fs::read_to_string(journal.get_path())
+            .unwrap()
+            .lines()
+            .count(),
+        3
+    );
+    assert_eq!(
+        JobJournal::read_status(dir.to_str().unwrap(), "scan")?,
+        status
+    );
+    assert_eq!(
+        JobJournal::open(dir.to_str().unwrap(), "../scan").unwrap_err(),
+        TesseractError::JournalError
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recognize_doc_resume() -> Result<(), TesseractError> {
+    let dir = std::env::temp_dir().join("rusttesserast_resume");
+    let _ = std::fs::remove_dir_all(&dir);
+    let image_array = vec!["tests/test_img.png", "tests/test_img_2.png"];
+    let mut tesseract_base = TesseractApi::new(
+        Some(TesseractApi {
+            journal: Some(JobJournal::open(dir.to_str().unwrap(), "resume")?),
+            ..Default::default()
+        }),
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let first = tesseract_base
+        .recognize_doc(None, None, image_array.clone(), "txt", None)
+        .await?;
+    tesseract_base.journal = Some(JobJournal::open(dir.to_str().unwrap(), "resume")?);
+    let second = tesseract_base
+        .recognize_doc(None, None, image_array, "txt", None)
+        .await?;
+    assert_eq!(first, second);
+    let status = tesseract_base.journal.as_ref().unwrap().get_status();
+    assert_eq!(status.resumed, 2);
+    assert_eq!(status.completed, 2);
+    assert!(status.finished);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_image_to_string_cached() -> Result<(), TesseractError> {
+    let mut tesseract_base = TesseractApi::new(
+        Some(TesseractApi {
+            cache: Some(ResultCache::memory(16, 1024 * 1024)),
+            ..Default::default()
+        }),
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let first = tesseract_base.image_to_string("tests/test_img.png").await?;
+    let second = tesseract_base.image_to_string("tests/test_img.png").await?;
+    assert_eq!(first, second);
+    let stats = tesseract_base.cache.as_ref().unwrap().get_stats();
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hits, 1);
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+async fn start_test_server(max_body_bytes: usize) -> std::net::SocketAddr {
+    use rusttesserast::pool::{EngineConfig, EnginePool};
+    let pool = EnginePool::new(
+        EngineConfig {
+            datapath: Some(String::from("/usr/local/share/tessdata")),
+            ..Default::default()
+        },
+        1,
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(rusttesserast::server::serve(
+        listener,
+        std::sync::Arc::new(pool),
+        max_body_bytes,
+    ));
+    addr
+}
+
+#[cfg(feature = "server")]
+async fn send_http_request(addr: std::net::SocketAddr, request: &[u8]) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn test_server_health() -> () {
+    let addr = start_test_server(1024).await;
+    let response = send_http_request(
+        addr,
+        b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("\"status\":\"ok\""));
+    ()
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn test_server_rejects_invalid_requests() -> () {
+    let addr = start_test_server(16).await;
+    let response = send_http_request(
+        addr,
+        b"POST /ocr?format=pdfs HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1\r\nConnection: close\r\n\r\nx",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+    let response = send_http_request(
+        addr,
+        b"POST /ocr HTTP/1.1\r\nHost: localhost\r\nContent-Length: 32\r\nConnection: close\r\n\r\n0123456789abcdef0123456789abcdef",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 413"));
+    let response = send_http_request(
+        addr,
+        b"POST /ocr HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\nConnection: close\r\n\r\nnone",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+    ()
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn test_server_ocr_raw_body() -> () {
+    let addr = start_test_server(rusttesserast::server::DEFAULT_MAX_BODY_BYTES).await;
+    let image = std::fs::read("tests/test_img.png").unwrap();
+    let mut request = format!(
+        "POST /ocr?format=txt&psm=3 HTTP/1.1\r\nHost: localhost\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        image.len()
+    )
+    .into_bytes();
+    request.extend_from_slice(&image);
+    let response = send_http_request(addr, &request).await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("World!"));
+    ()
+}
+
+#[cfg(feature = "worker")]
+#[tokio::test]
+async fn test_worker_reports_errors_per_line() -> () {
+    use rusttesserast::pool::{EngineConfig, EnginePool};
+    let pool = EnginePool::new(
+        EngineConfig {
+            datapath: Some(String::from("/usr/local/share/tessdata")),
+            ..Default::default()
+        },
+        1,
+    );
+    let input = "{\"id\": 1, \"path\": \"tests/test_imgk.png\"}\n\
+                 not a json\n\
+                 \n\
+                 {\"id\": \"b\", \"path\": \"tests/test_img.png\", \"format\": \"tsvs\"}\n\
+                 {\"id\": 3, \"path\": \"tests/test_img.png\", \"format\": \"txt\", \"options\": {\"psm\": 3}}\n";
+    let mut output = Vec::new();
+    rusttesserast::worker::run_worker(&pool, input.as_bytes(), &mut output)
+        .await
+        .unwrap();
+    let responses: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["code"], "NoSuchFileException");
+    assert_eq!(responses[1]["code"], "InvalidRequest");
+    assert_eq!(responses[2]["id"], "b");
+    assert_eq!(responses[2]["ok"], false);
+    assert_eq!(responses[3]["ok"], true);
+    assert!(responses[3]["output"].as_str().unwrap().contains("World!"));
+    ()
+}
+
+#[cfg(feature = "cli")]
+fn run_cli(args: &[&str]) -> std::process::Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_rusttesserast"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_cli_exit_codes() -> () {
+    let output = run_cli(&["tests/test_img.png", "--format", "pdfs"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pdfs"));
+    let output = run_cli(&["tests/test_imgk.png"]);
+    assert_eq!(output.status.code(), Some(4));
+    let output = run_cli(&["tests/*.jpg"]);
+    assert_eq!(output.status.code(), Some(4));
+    ()
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_cli_inputs_and_summary() -> () {
+    let input_dir = std::env::temp_dir().join("rusttesserast_cli");
+    let _ = std::fs::remove_dir_all(&input_dir);
+    std::fs::create_dir_all(&input_dir).unwrap();
+    std::fs::copy("tests/test_img.png", input_dir.join("b.PNG")).unwrap();
+    std::fs::copy("tests/test_img.png", input_dir.join("a.png")).unwrap();
+    std::fs::write(input_dir.join("notes.txt"), "not an image").unwrap();
+    std::fs::write(input_dir.join("broken.png"), "not an image").unwrap();
+    let input_dir = input_dir.to_string_lossy().into_owned();
+
+    // A directory is expanded to its images sorted by name, other files are skipped.
+    let output = run_cli(&[
+        &input_dir,
+        "--tessdata",
+        "/usr/local/share/tessdata",
+        "--format",
+        "txt,tsv",
+        "--json",
+    ]);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["inputs"], 3);
+    let results = summary["results"].as_array().unwrap();
+    assert_eq!(results.len(), 6);
+    let inputs: Vec<&str> = results[..3]
+        .iter()
+        .map(|result| result["input"].as_str().unwrap())
+        .collect();
+    assert!(inputs[0].ends_with("a.png"));
+    assert!(inputs[1].ends_with("b.PNG"));
+    assert!(inputs[2].ends_with("broken.png"));
+    assert_eq!(results[3]["format"], "tsv");
+    // The unreadable image fails alone, the exit code is taken from its error.
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["failed"], 1);
+    assert_eq!(results[2]["status"], "error");
+    assert_eq!(output.status.code(), Some(8));
+
+    // A glob pattern is expanded to the matching files only.
+    let output = run_cli(&[
+        &format!("{}/[ab].*", input_dir),
+        "--tessdata",
+        "/usr/local/share/tessdata",
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["inputs"], 2);
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["failed"], 0);
+    ()
+}
+
+#[cfg(feature = "pdf")]
+#[tokio::test]
+async fn test_render_multi_page_pdf() -> Result<(), TesseractError> {
+    let mut tesseract_base = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let output_dir = std::env::temp_dir().join("rusttesserast_pdf");
+    let image_array = [
+        "tests/test_img.png",
+        "tests/test_img.png",
+        "tests/test_img.png",
+    ];
+    // Every input image is handed to the renderer, for both original and thresholded images.
+    let output = tesseract_base
+        .images_to_pdf(
+            &image_array,
+            output_dir.join("pages").to_str().unwrap(),
+            &PdfRendererOptions::default(),
+        )
+        .await?;
+    assert_eq!(output, output_dir.join("pages.pdf"));
+    let document = lopdf::Document::load(&output).unwrap();
+    assert_eq!(document.get_pages().len(), 3);
+    let options = PdfRendererOptions {
+        compression: PdfImageCompression::Bilevel,
+        ..Default::default()
+    };
+    let output = render_pdf(
+        &tesseract_base,
+        &image_array,
+        output_dir.join("bilevel.pdf").to_str().unwrap(),
+        &options,
+    )?;
+    let document = lopdf::Document::load(&output).unwrap();
+    assert_eq!(document.get_pages().len(), 3);
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_write_pdf_metadata() -> Result<(), TesseractError> {
+    let output = std::env::temp_dir().join("rusttesserast_pdf/pdfa.pdf");
+    std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+    std::fs::copy("tests/test_text.pdf", &output).unwrap();
+    let options = PdfRendererOptions {
+        pdfa: true,
+        title: Some(String::from("Caf\u{e9}")),
+        author: Some(String::from("Scanner")),
+        creation_date: Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(86400 * 365)),
+        ..Default::default()
+    };
+    write_metadata(&output, &options)?;
+    let pdf = String::from_utf8_lossy(&std::fs::read(&output).unwrap()).into_owned();
+    assert!(pdf.contains("(Hello World) Tj"));
+    assert!(pdf.contains("<FEFF00430061006600E9>"));
+    assert!(pdf.contains("(Scanner)"));
+    assert!(pdf.contains("(D:19710101000000+00'00')"));
+    assert!(pdf.contains("<pdfaid:part>2</pdfaid:part>"));
+    assert!(pdf.contains(
+        "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">Caf\u{e9}</rdf:li></rdf:Alt></dc:title>"
+    ));
+    assert!(pdf.contains("/GTS_PDFA1"));
+    assert!(pdf.contains("/OutputIntents"));
+    assert_eq!(
+        write_metadata(Path::new("tests/missing.pdf"), &options),
+        Err(TesseractError::PdfError)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_write_pdfa_output_intent() -> Result<(), TesseractError> {
+    let output = std::env::temp_dir().join("rusttesserast_pdf/pdfa_intent.pdf");
+    std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+    std::fs::copy("tests/test_text.pdf", &output).unwrap();
+    let options = PdfRendererOptions {
+        pdfa: true,
+        ..Default::default()
+    };
+    write_metadata(&output, &options)?;
+    let document = lopdf::Document::load(&output).unwrap();
+    let catalog = document.catalog().unwrap();
+    let stream = |object: &lopdf::Object| {
+        document
+            .get_object(object.as_reference().unwrap())
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .clone()
+    };
+    // The catalog has the PDF/A-2b identification in its XMP metadata.
+    let metadata = stream(catalog.get(b"Metadata").unwrap());
+    let xmp = String::from_utf8_lossy(&metadata.content).into_owned();
+    assert!(xmp.contains("<pdfaid:part>2</pdfaid:part>"));
+    assert!(xmp.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+    // One PDF/A output intent with the embedded RGB profile.
+    let intents = catalog.get(b"OutputIntents").unwrap().as_array().unwrap();
+    assert_eq!(intents.len(), 1);
+    let intent = intents[0].as_dict().unwrap();
+    assert_eq!(intent.get(b"S").unwrap().as_name_str().unwrap(), "GTS_PDFA1");
+    assert_eq!(
+        intent.get(b"OutputConditionIdentifier").unwrap().as_str().unwrap(),
+        b"Custom"
+    );
+    let profile = stream(intent.get(b"DestOutputProfile").unwrap());
+    assert_eq!(profile.dict.get(b"N").unwrap().as_i64().unwrap(), 3);
+    assert_eq!(&profile.content[36..40], b"acsp");
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() -> Result<(), TesseractError> {
+    let tsv = include_str!("data.txt");
+    let json = convert(tsv, &OutputFileFormat::TSV, &OutputFileFormat::JSON)?;
+    assert!(!json.contains("elapsed_ms"));
+    let pages = parse_json(&json)?;
+    assert_eq!(pages, get_pages(&parse_tsv(tsv)?));
+    assert_eq!(
+        parse_doc(
+            &serde_json::to_string(&pages[0]).unwrap(),
+            &OutputFileFormat::JSON
+        )?,
+        pages
+    );
+    assert_eq!(
+        convert(&json, &OutputFileFormat::JSON, &OutputFileFormat::TXT)?,
+        "Helto World!\nI use tesseract"
+    );
+    let doc = parse_json(&assemble_doc(
+        &OutputFileFormat::JSON,
+        &[json.clone(), json],
+    ))?;
+    assert_eq!(
+        doc.iter()
+            .map(|page| page.page_number)
+            .collect::<Vec<usize>>(),
+        vec![0, 1]
+    );
+    let mut page = pages[0].clone();
+    page.elapsed_ms = Some(120);
+    let page_json = serde_json::to_string(&page).unwrap();
+    assert!(page_json.contains("\"elapsed_ms\":120"));
+    assert_eq!(parse_json(&page_json)?, vec![page]);
+    assert_eq!(
+        OutputFileFormat::from_type("json"),
+        Some(OutputFileFormat::JSON)
+    );
+    let rows = parse_tsv(tsv)?;
+    assert_eq!(
+        serde_json::from_str::<Vec<TsvRow>>(&serde_json::to_string(&rows).unwrap()).unwrap(),
+        rows
+    );
+    let error: Result<String, TesseractError> = Err(TesseractError::ParseError);
+    assert_eq!(
+        serde_json::to_string(&error).unwrap(),
+        "{\"Err\":\"ParseError\"}"
+    );
+    assert_eq!(parse_json("{\"text\": 1}"), Err(TesseractError::ParseError));
+    Ok(())
+}
+
+fn get_layout_page() -> Vec<Page> {
+    let line = |bbox: &str, words: &[(&str, &str)]| {
+        let words: String = words
+            .iter()
+            .map(|(bbox, text)| {
+                format!(
+                    "<span class='ocrx_word' title='bbox {}'>{}</span>",
+                    bbox, text
+                )
+            })
+            .collect();
+        format!(
+            "<span class='ocr_line' title='bbox {}'>{}</span>",
+            bbox, words
+        )
+    };
+    let par = |lines: &[String]| format!("<p class='ocr_par'>{}</p>", lines.concat());
+    let hocr = [
+        "<div class='ocr_page' title='bbox 0 0 1000 1000'><div class='ocr_carea'>".to_string(),
+        par(&[line("100 100 400 140", &[("100 100 400 140", "Report")])]),
+        par(&[
+            line(
+                "100 200 500 220",
+                &[
+                    ("100 200 180 220", "Sales"),
+                    ("190 200 250 220", "grew"),
+                    ("260 200 500 220", "in-"),
+                ],
+            ),
+            line(
+                "100 230 300 250",
+                &[("100 230 200 250", "deed."), ("210 230 300 250", "Next:")],
+            ),
+        ]),
+        par(&[
+            line(
+                "100 300 400 320",
+                &[("100 300 110 320", "•"), ("120 300 400 320", "first")],
+            ),
+            line(
+                "100 330 400 350",
+                &[("100 330 130 350", "2."), ("140 330 400 350", "second")],
+            ),
+        ]),
+        "</div><div class='ocr_carea'>".to_string(),
+        par(&[
+            line(
+                "100 400 700 420",
+                &[("100 400 180 420", "Item"), ("600 400 700 420", "Price")],
+            ),
+            line(
+                "100 430 700 450",
+                &[("100 430 180 450", "Tea"), ("600 430 700 450", "3*4")],
+            ),
+        ]),
+        "</div></div>".to_string(),
+    ];
+    parse_hocr(&hocr.concat()).unwrap()
+}
+
+#[test]
+fn test_layout_text() -> () {
+    let text = write_layout_text(&get_layout_page());
+    let rows: Vec<&str> = text.lines().collect();
+    assert_eq!(rows[0], "Report");
+    assert_eq!(rows[1], "");
+    assert!(rows[2].starts_with("Sales grew"));
+    let item = rows.iter().find(|row| row.starts_with("Item")).unwrap();
+    let tea = rows.iter().find(|row| row.starts_with("Tea")).unwrap();
+    assert_eq!(item.find("Price"), tea.find("3*4"));
+    assert!(item.find("Price").unwrap() > 20);
+    assert_eq!(write_layout_text(&[Page::default()]), "");
+    ()
+}
+
+#[test]
+fn test_markdown() -> () {
+    let markdown = write_markdown(&get_layout_page());
+    assert_eq!(
+        markdown,
+        "# Report\n\nSales grew indeed. Next:\n\n- first\n\n2. second\n\n| Item | Price |\n| --- | --- |\n| Tea | 3\\*4 |"
+    );
+    let doc = convert(
+        &write_hocr(&get_layout_page()),
+        &OutputFileFormat::HOCR,
+        &OutputFileFormat::MARKDOWN,
+    )
+    .unwrap();
+    assert_eq!(doc, markdown);
+    assert_eq!(
+        OutputFileFormat::from_type("md"),
+        Some(OutputFileFormat::MARKDOWN)
+    );
+    ()
+}
+
+#[test]
+fn test_detect_tables() -> Result<(), TesseractError> {
+    let word = |bbox: &str, text: &str| {
+        format!(
+            "<span class='ocrx_word' title='bbox {}; x_wconf 90'>{}</span>",
+            bbox, text
+        )
+    };
+    let line = |bbox: &str, words: Vec<String>| {
+        format!(
+            "<span class='ocr_line' title='bbox {}'>{}</span>",
+            bbox,
+            words.concat()
+        )
+    };
+    let hocr = [
+        "<div class='ocr_page' title='bbox 0 0 1000 1000'>".to_string(),
+        line(
+            "100 50 600 70",
+            vec![
+                word("100 50 200 70", "Invoice"),
+                word("210 50 300 70", "no."),
+                word("310 50 400 70", "42"),
+            ],
+        ),
+        line(
+            "100 100 700 120",
+            vec![
+                word("100 100 200 120", "Item"),
+                word("400 100 480 120", "Qty"),
+                word("600 100 700 120", "Price"),
+            ],
+        ),
+        line(
+            "100 130 700 150",
+            vec![
+                word("100 130 180 150", "Green"),
+                word("190 130 250 150", "tea"),
+                word("400 130 420 150", "2"),
+                word("600 130 700 150", "3,50"),
+            ],
+        ),
+        line(
+            "100 160 700 180",
+            vec![
+                word("100 160 180 180", "Cup"),
+                word("610 160 690 180", "\"big\""),
+            ],
+        ),
+        line(
+            "100 600 300 620",
+            vec![word("100 600 150 620", "A1"), word("250 600 300 620", "B1")],
+        ),
+        line("100 650 300 670", vec![word("100 650 150 670", "A2")]),
+        "</div>".to_string(),
+    ];
+    let page = &parse_hocr(&hocr.concat())?[0];
+    let options = TableOptions {
+        rulings: Some(Rulings {
+            horizontal: vec![
+                BoundingBox::new(90, 590, 310, 592),
+                BoundingBox::new(90, 630, 310, 632),
+                BoundingBox::new(90, 680, 310, 682),
+            ],
+            vertical: vec![
+                BoundingBox::new(90, 590, 92, 682),
+                BoundingBox::new(200, 590, 202, 682),
+                BoundingBox::new(308, 590, 310, 682),
+            ],
+        }),
+        ..Default::default()
+    };
+    let tables = detect_tables(page, &options);
+    assert_eq!(tables.len(), 2);
+    let invoice = &tables[0];
+    assert!(!invoice.ruled);
+    assert_eq!(
+        (invoice.get_row_count(), invoice.get_column_count()),
+        (3, 3)
+    );
+    assert_eq!(invoice.get_cell(1, 0).unwrap().text, "Green tea");
+    assert_eq!(invoice.get_cell(1, 1).unwrap().confidence, Some(90.0));
+    assert_eq!(invoice.get_cell(2, 1).unwrap().text, "");
+    assert_eq!(
+        invoice.get_cell(2, 1).unwrap().bbox,
+        BoundingBox::new(400, 160, 480, 180)
+    );
+    assert_eq!(
+        write_csv(invoice, ','),
+        "Item,Qty,Price\nGreen tea,2,\"3,50\"\nCup,,\"\"\"big\"\"\"\n"
+    );
+    let ruled = &tables[1];
+    assert!(ruled.ruled);
+    assert_eq!(write_csv(ruled, ';'), "A1;B1\nA2;\n");
+    assert_eq!(
+        ruled.get_cell(1, 1).unwrap().bbox,
+        BoundingBox::new(201, 631, 309, 681)
+    );
+    assert!(detect_tables(
+        page,
+        &TableOptions {
+            min_rows: 4,
+            rulings: None,
+            ..Default::default()
+        }
+    )
+    .is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_form_template() -> Result<(), TesseractError> {
+    let anchor = |name: &str, text: &str, bbox: BoundingBox| FormAnchor {
+        name: name.to_string(),
+        text: text.to_string(),
+        bbox,
+    };
+    let template = FormTemplate {
+        name: String::from("invoice"),
+        width: 1000,
+        height: 1000,
+        anchors: vec![
+            anchor(
+                "total",
+                "Total amount",
+                BoundingBox::new(100, 800, 300, 820),
+            ),
+            anchor("date", "Date", BoundingBox::new(800, 100, 860, 120)),
+        ],
+        fields: vec![
+            FormField {
+                name: String::from("amount"),
+                anchor: Some(String::from("total")),
+                left: 250,
+                width: 200,
+                height: 20,
+                field_type: FieldType::Decimal,
+                pattern: Some(String::from(r"^\d")),
+                ..Default::default()
+            },
+            FormField {
+                name: String::from("number"),
+                corner: FormCorner::TopRight,
+                left: -300,
+                top: 50,
+                width: 250,
+                height: 30,
+                field_type: FieldType::Integer,
+                ..Default::default()
+            },
+        ],
+    };
+    template.validate()?;
+    // The scan is twice as large as the template and shifted by 10, 20 pixels.
+    let word = |bbox: &str, text: &str| {
+        format!(
+            "<span class='ocrx_word' title='bbox {}'>{}</span>",
+            bbox, text
+        )
+    };
+    let hocr = [
+        "<div class='ocr_page' title='bbox 0 0 2000 2000'><span class='ocr_line' title='bbox 210 1620 610 1660'>".to_string(),
+        word("210 1620 400 1660", "TOTAL"),
+        word("420 1620 610 1660", "amount:"),
+        "</span><span class='ocr_line' title='bbox 1610 220 1730 260'>".to_string(),
+        word("1610 220 1730 260", "Date"),
+        "</span></div>".to_string(),
+    ];
+    let page = &parse_hocr(&hocr.concat())?[0];
+    let anchors: Vec<FormAnchor> = template
+        .anchors
+        .iter()
+        .filter_map(|anchor| {
+            find_anchor(anchor, page).map(|bbox| FormAnchor {
+                bbox,
+                ..anchor.clone()
+            })
+        })
+        .collect();
+    assert_eq!(anchors[0].bbox, BoundingBox::new(210, 1620, 610, 1660));
+    let alignment = FormAlignment::new(&template, 2000, 2000, anchors);
+    assert_eq!((alignment.scale_x, alignment.offset_x), (2.0, 10.0));
+    assert_eq!((alignment.scale_y, alignment.offset_y), (2.0, 20.0));
+    assert_eq!(
+        alignment.get_region(&template, &template.fields[0]),
+        BoundingBox::new(710, 1620, 1110, 1660)
+    );
+    assert_eq!(
+        alignment.get_region(&template, &template.fields[1]),
+        BoundingBox::new(1400, 100, 1900, 160)
+    );
+    assert!(find_anchor(&anchor("total", "Total sum", BoundingBox::default()), page).is_none());
+
+    assert_eq!(
+        parse_value("1 234,50", &FieldType::Decimal),
+        Some(FieldValue::Decimal(1234.5))
+    );
+    assert_eq!(
+        parse_value("1,234.50", &FieldType::Decimal),
+        Some(FieldValue::Decimal(1234.5))
+    );
+    assert_eq!(
+        parse_value("1.234.567", &FieldType::Decimal),
+        Some(FieldValue::Decimal(1234567.0))
+    );
+    assert_eq!(
+        parse_value(" 42 ", &FieldType::Integer),
+        Some(FieldValue::Integer(42))
+    );
+    assert_eq!(parse_value("4x", &FieldType::Integer), None);
+    assert_eq!(
+        parse_value("29.02.2024", &FieldType::Date),
+        Some(FieldValue::Date {
+            year: 2024,
+            month: 2,
+            day: 29
+        })
+    );
+    assert_eq!(parse_value("2023-02-29", &FieldType::Date), None);
+    assert_eq!(parse_value("", &FieldType::Text), None);
+
+    let invalid = FormTemplate {
+        fields: vec![FormField {
+            anchor: Some(String::from("missing")),
+            ..Default::default()
+        }],
+        ..template.clone()
+    };
+    assert_eq!(invalid.validate(), Err(TesseractError::ParseError));
+    let invalid = FormTemplate {
+        fields: vec![FormField {
+            pattern: Some(String::from("(")),
+            ..Default::default()
+        }],
+        ..template.clone()
+    };
+    assert_eq!(invalid.validate(), Err(TesseractError::ParseError));
+    #[cfg(feature = "serde")]
+    {
+        let json = r#"{"name": "receipt", "width": 800, "height": 1200, "fields": [{"name": "total", "corner": "bottom_right", "left": -200, "top": -100, "width": 180, "height": 40, "field_type": "decimal"}]}"#;
+        let receipt = FormTemplate::from_json(json)?;
+        assert_eq!(receipt.fields[0].corner, FormCorner::BottomRight);
+        assert_eq!(receipt.fields[0].field_type, FieldType::Decimal);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_form_invalid_image() -> Result<(), TesseractError> {
+    let mut cube = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let template = FormTemplate {
+        width: 1000,
+        height: 1000,
+        ..Default::default()
+    };
+    assert_eq!(
+        cube.image_to_form("tests/data.txt", &template).await,
+        Err(TesseractError::InvalidImageError)
+    );
+    assert_eq!(
+        cube.image_to_form("tests/missing.png", &template).await,
+        Err(TesseractError::NoSuchFileException)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_images() -> Result<(), TesseractError> {
+    let mut cube = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let regex = SearchOptions {
+        mode: SearchMode::Regex,
+        ..Default::default()
+    };
+    // An invalid regex is reported before the missing image is opened.
+    assert_eq!(
+        cube.search_images(&["tests/missing.png"], "(", &regex)
+            .await,
+        Err(TesseractError::ParseError)
+    );
+    let found = cube
+        .search_images(
+            &["tests/test_img.png", "tests/test_img.png"],
+            "World!",
+            &SearchOptions::default(),
+        )
+        .await?;
+    assert_eq!(
+        found.iter().map(|found| found.image).collect::<Vec<_>>(),
+        vec![Some(0), Some(1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_pages() -> Result<(), TesseractError> {
+    let page = get_layout_page().remove(0);
+    let pages = vec![page.clone(), page];
+    let mode = |mode: SearchMode| SearchOptions {
+        mode,
+        ..Default::default()
+    };
+    let found = search_pages(&pages[..1], "grew  in- deed.", &SearchOptions::default())?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].text, "grew in- deed.");
+    assert_eq!(
+        found[0].line_boxes,
+        vec![
+            BoundingBox::new(190, 200, 500, 220),
+            BoundingBox::new(100, 230, 200, 250)
+        ]
+    );
+    assert_eq!(found[0].bbox, BoundingBox::new(100, 200, 500, 250));
+    assert!(search_pages(&pages, "report", &SearchOptions::default())?.is_empty());
+    let found = search_pages(&pages, "report", &mode(SearchMode::IgnoreCase))?;
+    assert_eq!(
+        found.iter().map(|found| found.page).collect::<Vec<usize>>(),
+        vec![0, 1]
+    );
+    assert!(found.iter().all(|found| found.image.is_none()));
+    assert_eq!(found[0].bbox, BoundingBox::new(100, 100, 400, 140));
+    assert_eq!(
+        search_pages(&pages[..1], "ric", &SearchOptions::default())?[0].text,
+        "Price"
+    );
+    assert_eq!(
+        search_pages(&pages[..1], r"\d\*\d", &mode(SearchMode::Regex))?[0].text,
+        "3*4"
+    );
+    let found = search_pages(&pages[..1], "sales grw", &mode(SearchMode::Fuzzy))?;
+    assert_eq!(
+        (found[0].text.as_str(), found[0].distance),
+        ("Sales grew", 1)
+    );
+    assert_eq!(
+        search_pages(&pages, "(", &mode(SearchMode::Regex)),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(get_distance("kitten", "sitting"), 3);
+    Ok(())
+}
+
+fn hocr_word(bbox: &str, text: &str) -> String {
+    format!(
+        "<span class='ocrx_word' title='bbox {}; x_wconf 90'>{}</span>",
+        bbox, text
+    )
+}
+
+fn hocr_line(bbox: &str, words: Vec<String>) -> String {
+    format!(
+        "<span class='ocr_line' title='bbox {}'>{}</span>",
+        bbox,
+        words.concat()
+    )
+}
+
+#[test]
+fn test_find_redactions() -> Result<(), TesseractError> {
+    let hocr = [
+        "<div class='ocr_page' title='bbox 0 0 1000 1000'>".to_string(),
+        hocr_line(
+            "100 100 600 120",
+            vec![
+                hocr_word("100 100 200 120", "Dear"),
+                hocr_word("210 100 300 120", "John"),
+                hocr_word("310 100 420 120", "Smith,"),
+            ],
+        ),
+        hocr_line(
+            "100 130 700 150",
+            vec![
+                hocr_word("100 130 200 150", "write"),
+                hocr_word("210 130 250 150", "to"),
+                hocr_word("260 130 500 150", "john.smith@mail.com"),
+            ],
+        ),
+        hocr_line(
+            "100 160 700 180",
+            vec![
+                hocr_word("100 160 200 180", "or"),
+                hocr_word("210 160 300 180", "call"),
+                hocr_word("310 160 400 180", "+49"),
+                hocr_word("410 160 500 180", "30 1234"),
+                hocr_word("510 160 600 180", "567."),
+            ],
+        ),
+        hocr_line(
+            "100 190 400 210",
+            vec![
+                hocr_word("100 190 200 210", "Johnson"),
+                hocr_word("210 190 300 210", "agrees."),
+            ],
+        ),
+        "</div>".to_string(),
+    ];
+    let pages = parse_hocr(&hocr.concat())?;
+    let options = RedactionOptions {
+        patterns: vec![
+            RedactionPattern::Words(vec![String::from("john  smith"), String::new()]),
+            RedactionPattern::Entity(RedactionEntity::Email),
+            RedactionPattern::Entity(RedactionEntity::Phone),
+        ],
+        ..Default::default()
+    };
+    let redactions = find_redactions(&pages, &options)?;
+    let texts: Vec<&str> = redactions.iter().map(|found| found.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec!["John Smith,", "john.smith@mail.com", "+49 30 1234 567."]
+    );
+    assert_eq!(
+        redactions[0].line_boxes,
+        vec![BoundingBox::new(210, 100, 420, 120)]
+    );
+    assert_eq!(redactions[2].bbox, BoundingBox::new(310, 160, 600, 180));
+    // Dates, amounts and ids are not phone numbers.
+    let lines: Vec<String> = [
+        "on 2023-01-15 at",
+        "on 15.01.2023 at",
+        "pay 1 234 567.89 EUR",
+        "pay 12.345.678,90 EUR",
+        "id INV-2023-000123 ok",
+        "id 1234567890123 ok",
+        "tel (030) 1234-5678 ok",
+        "tel +4930123456 ok",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(index, text)| {
+        let top = 100 + index * 30;
+        let words: Vec<String> = text
+            .split(' ')
+            .enumerate()
+            .map(|(column, word)| {
+                let left = 100 + column * 100;
+                hocr_word(
+                    &format!("{} {} {} {}", left, top, left + 90, top + 20),
+                    word,
+                )
+            })
+            .collect();
+        hocr_line(&format!("100 {} 900 {}", top, top + 20), words)
+    })
+    .collect();
+    let pages = parse_hocr(&format!(
+        "<div class='ocr_page' title='bbox 0 0 1000 1000'>{}</div>",
+        lines.concat()
+    ))?;
+    let phones = RedactionOptions {
+        patterns: vec![RedactionPattern::Entity(RedactionEntity::Phone)],
+        ..Default::default()
+    };
+    let texts: Vec<String> = find_redactions(&pages, &phones)?
+        .into_iter()
+        .map(|found| found.text)
+        .collect();
+    assert_eq!(texts, vec!["(030) 1234-5678", "+4930123456"]);
+    assert!(RedactionPattern::Words(Vec::new()).get_regex()?.is_none());
+    let invalid = RedactionOptions {
+        patterns: vec![RedactionPattern::Regex(String::from("[a-"))],
+        ..Default::default()
+    };
+    assert_eq!(
+        find_redactions(&pages, &invalid),
+        Err(TesseractError::ParseError)
+    );
+    assert_eq!(
+        TesseractError::RedactionError.to_string(),
+        "Redaction Error"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_redact_image_pixels() -> Result<(), TesseractError> {
+    use tesseract_plumbing::leptonica_sys::{
+        l_int32, pixConvertTo32, pixDestroy, pixGetPixel, pixRead, pixSetComponentArbitrary,
+        pixSetSpp, pixWrite, IFF_PNG, L_ALPHA_CHANNEL,
+    };
+    let output_dir = std::env::temp_dir().join("rusttesserast_redact");
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let to_cstring = |path: &Path| std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+    // An opaque RGBA copy of the test image.
+    let rgba = output_dir.join("rgba.png");
+    unsafe {
+        let mut pix = pixRead(to_cstring(Path::new("tests/test_img.png")).as_ptr());
+        let mut rgba_pix = pixConvertTo32(pix);
+        pixSetSpp(rgba_pix, 4);
+        pixSetComponentArbitrary(rgba_pix, L_ALPHA_CHANNEL as l_int32, 255);
+        assert_eq!(
+            pixWrite(to_cstring(&rgba).as_ptr(), rgba_pix, IFF_PNG as l_int32),
+            0
+        );
+        pixDestroy(&mut pix);
+        pixDestroy(&mut rgba_pix);
+    }
+    let mut tesseract_base = TesseractApi::new(
+        None,
+        Some(String::from("/usr/local/share/tessdata").as_str()),
+        Some(String::from("eng").as_str()),
+    )?;
+    let options = RedactionOptions {
+        patterns: vec![RedactionPattern::Words(vec![String::from("World")])],
+        ..Default::default()
+    };
+    let output = output_dir.join("redacted.png");
+    let redactions = tesseract_base
+        .redact_image(rgba.to_str().unwrap(), output.to_str().unwrap(), &options)
+        .await?;
+    assert_eq!(redactions.len(), 1);
+    let bbox = redactions[0].bbox;
+    let (x, y) = ((bbox.left + bbox.right) / 2, (bbox.top + bbox.bottom) / 2);
+    // The box is painted opaque black: R, G, B 0 and alpha 255.
+    unsafe {
+        let mut pix = pixRead(to_cstring(&output).as_ptr());
+        let mut value = 0;
+        assert_eq!(pixGetPixel(pix, x as l_int32, y as l_int32, &mut value), 0);
+        pixDestroy(&mut pix);
+        assert_eq!(value, 0xff);
+    }
+    #[cfg(feature = "pdf")]
+    {
+        use pdfium_render::prelude::{PdfRenderConfig, Pdfium};
+        let output = output_dir.join("redacted.pdf");
+        tesseract_base
+            .redact_image_to_pdf(
+                rgba.to_str().unwrap(),
+                output.to_str().unwrap(),
+                &options,
+                &PdfRendererOptions::default(),
+            )
+            .await?;
+        let pdfium = Pdfium::new(Pdfium::bind_to_system_library().unwrap());
+        let document = pdfium.load_pdf_from_file(&output, None).unwrap();
+        let page = document.pages().get(0).unwrap();
+        // The page is rendered at the resolution of the image, so the box has the same pixels.
+        let config = PdfRenderConfig::new().scale_page_by_factor(tesseract_base.dpi as f32 / 72.0);
+        let bitmap = page.render_with_config(&config).unwrap();
+        let rgba_bytes = bitmap.as_rgba_bytes();
+        let offset = (y as usize * bitmap.width() as usize + x as usize) * 4;
+        assert!(rgba_bytes[offset..offset + 3]
+            .iter()
+            .all(|value| *value < 64));
+        assert_eq!(rgba_bytes[offset + 3], 255);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_normalize_text() -> Result<(), TesseractError> {
+    let options = NormalizeOptions::default();
+    let text = "The \u{fb01}rst  \u{201c}quote\u{201d} \u{2014}  costs 1O0\u{2212}2 USD.\nHe1lo wor-\nld again\nC0DE 1st\n\n\n\nend Inter\u{ad}\nNational";
+    assert_eq!(
+        normalize_text(text, &options),
+        "The first \"quote\" \u{2014} costs 100-2 USD.\nHello world\nagain\nCODE 1st\n\nend Inter-\nNational"
+    );
+    let disabled = NormalizeOptions {
+        unicode: None,
+        dehyphenate: false,
+        collapse_whitespace: false,
+        quotes: false,
+        dashes: false,
+        confusions: false,
+    };
+    assert_eq!(normalize_text(text, &disabled), text);
+
+    let mut pages = get_layout_page();
+    normalize_pages(&mut pages, &options);
+    let lines: Vec<String> = pages[0].blocks[0].paragraphs[1]
+        .lines
+        .iter()
+        .map(|line| line.get_text())
+        .collect();
+    assert_eq!(lines, vec!["Sales grew indeed.", "Next:"]);
+    let hocr = write_hocr(&get_layout_page());
+    let normalized = parse_hocr(&normalize_output(&hocr, &OutputFileFormat::HOCR, &options)?)?;
+    assert_eq!(normalized, pages);
+    // Symbols are kept only for words whose text is not changed.
+    let hocr = "<div class='ocr_page' title='bbox 0 0 200 20'><span class='ocr_line' title='bbox 0 0 200 20'>\
+        <span class='ocrx_word' title='bbox 0 0 30 20; x_wconf 90'>\
+        <span class='ocrx_cinfo' title='x_bboxes 0 0 10 20; x_conf 95'>1</span>\
+        <span class='ocrx_cinfo' title='x_bboxes 10 0 20 20; x_conf 95'>O</span></span>\
+        <span class='ocrx_word' title='bbox 40 0 90 20; x_wconf 90'>\
+        <span class='ocrx_cinfo' title='x_bboxes 40 0 50 20; x_conf 95'>o</span>\
+        <span class='ocrx_cinfo' title='x_bboxes 50 0 60 20; x_conf 95'>k</span></span></span></div>";
+    let mut pages = parse_hocr(hocr)?;
+    normalize_pages(&mut pages, &options);
+    let words = &pages[0].blocks[0].paragraphs[0].lines[0].words;
+    assert_eq!(words[0].text, "10");
+    assert!(words[0].symbols.is_empty());
+    assert_eq!(words[1].text, "ok");
+    assert_eq!(words[1].symbols.len(), 2);
+    let api = TesseractApi::default();
+    let normalizing = TesseractApi {
+        normalize: Some(options),
+        ..Default::default()
+    };
+    assert_ne!(
+        api.get_cache_key(b"image", &OutputFileFormat::TXT),
+        normalizing.get_cache_key(b"image", &OutputFileFormat::TXT)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_spell_correction() -> Result<(), TesseractError> {
+    let aff = "SET UTF-8\nSFX D Y 2\nSFX D 0 ed [^e]\nSFX D 0 d e\nPFX U Y 1\nPFX U 0 un .\n";
+    let hunspell = Dictionary::parse_hunspell("eng", "2\nlock/DU\nquick\n", aff)?;
+    for word in ["lock", "locked", "unlock", "unlocked", "quick"] {
+        assert!(hunspell.contains(word));
+    }
+    assert_eq!(hunspell.words.len(), 5);
+
+    let hocr = "<div class='ocr_page' id='page_1' title='bbox 0 0 500 100'><span class='ocr_line' id='line_1_1' title='bbox 0 0 500 20'>\
+        <span class='ocrx_word' id='word_1_1' title='bbox 0 0 30 20; x_wconf 60'>\
+        <span class='ocrx_cinfo' title='x_bboxes 0 0 10 20; x_conf 95'>T</span>\
+        <span class='ocrx_cinfo' title='x_bboxes 10 0 20 20; x_conf 50'>b</span>\
+        <span class='ocrx_cinfo' id='lstm_choices_1_1_1'><span class='ocrx_cinfo' id='choice_1_1_0' title='x_confs 50'>b</span><span class='ocrx_cinfo' id='choice_1_1_1' title='x_confs 45'>h</span></span>\
+        <span class='ocrx_cinfo' title='x_bboxes 20 0 30 20; x_conf 95'>e</span></span>\
+        <span class='ocrx_word' id='word_1_2' title='bbox 40 0 90 20; x_wconf 40'>QUIK</span>\
+        <span class='ocrx_word' id='word_1_3' title='bbox 100 0 150 20; x_wconf 50'>warld,</span>\
+        <span class='ocrx_word' id='word_1_4' title='bbox 160 0 210 20; x_wconf 95'>Hause</span>\
+        <span class='ocrx_word' id='word_1_5' title='bbox 220 0 270 20; x_wconf 30'>l0ck</span></span></div>";
+    let mut pages = parse_hocr(hocr)?;
+    let word = &pages[0].blocks[0].paragraphs[0].lines[0].words[0];
+    assert_eq!(word.text, "Tbe");
+    assert_eq!(word.symbols.len(), 3);
+    assert_eq!(word.symbols[1].choices[1].text, "h");
+    assert_eq!(word.symbols[2].bbox, Some(BoundingBox::new(20, 0, 30, 20)));
+
+    let options = SpellOptions {
+        dictionaries: vec![hunspell, Dictionary::new("eng", ["the", "world", "house"])],
+        ..Default::default()
+    };
+    let corrections = correct_pages(&mut pages, &options);
+    assert_eq!(pages[0].get_text(), "The QUICK world, Hause l0ck");
+    let reasons: Vec<(&str, &str, CorrectionReason)> = corrections
+        .iter()
+        .map(|correction| {
+            (
+                correction.original.as_str(),
+                correction.corrected.as_str(),
+                correction.reason,
+            )
+        })
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            ("Tbe", "The", CorrectionReason::Choice { symbols: 1 }),
+            (
+                "QUIK",
+                "QUICK",
+                CorrectionReason::Dictionary { distance: 1 }
+            ),
+            (
+                "warld,",
+                "world,",
+                CorrectionReason::Dictionary { distance: 1 }
+            ),
+        ]
+    );
+    assert_eq!(corrections[2].word, 2);
+
+    pages[0].blocks[0].paragraphs[0].lines[0].words[1].text = String::from("Quick");
+    assert_eq!(revert_corrections(&mut pages, &corrections), 2);
+    assert_eq!(pages[0].get_text(), "Tbe Quick warld, Hause l0ck");
+    Ok(())
+}
+
+#[test]
+fn test_word_choices() -> Result<(), TesseractError> {
+    let hocr = "<div class='ocr_page' id='page_1' title='bbox 0 0 100 20'><span class='ocr_line' id='line_1_1' title='bbox 0 0 100 20'>\
+        <span class='ocrx_word' id='word_1_1' title='bbox 0 0 20 20; x_wconf 70'>\
+        <span class='ocrx_cinfo' title='x_bboxes 0 0 10 20; x_conf 60'>1</span>\
+        <span class='ocr_symbol' id='symbol_1_1_0'>\
+        <span class='ocrx_cinfo' id='timestep1_1_0'><span class='ocrx_cinfo' id='choice_1_1_0' title='x_confs 50'>l</span><span class='ocrx_cinfo' id='choice_1_1_1' title='x_confs 40'>1</span></span>\
+        <span class='ocrx_cinfo' id='timestep1_1_1'><span class='ocrx_cinfo' id='choice_1_1_2' title='x_confs 60'>1</span><span class='ocrx_cinfo' id='choice_1_1_3' title='x_confs 10'> </span></span></span>\
+        <span class='ocrx_cinfo' title='x_bboxes 10 0 20 20; x_conf 90'>7</span></span>\
+        <span class='ocrx_word' id='word_1_2' title='bbox 30 0 60 20; x_wconf 96'>plain</span></span></div>";
+    let words = get_word_choices(&parse_hocr(hocr)?, 2);
+    assert_eq!(words.len(), 2);
+    assert_eq!(words[0].text, "17");
+    assert_eq!(
+        words[0].symbols[0].choices,
+        vec![
+            Choice {
+                text: String::from("1"),
+                confidence: 60.0
+            },
+            Choice {
+                text: String::from("l"),
+                confidence: 50.0
+            }
+        ]
+    );
+    let alternatives: Vec<(&str, i32)> = words[0]
+        .alternatives
+        .iter()
+        .map(|choice| (choice.text.as_str(), choice.confidence.round() as i32))
+        .collect();
+    assert_eq!(alternatives, vec![("17", 54), ("l7", 45)]);
+    assert_eq!(
+        words[1].alternatives,
+        vec![Choice {
+            text: String::from("plain"),
+            confidence: 96.0
+        }]
+    );
+
+    let symbols: Vec<Symbol> = ["a", "b", "c"]
+        .iter()
+        .zip([80.0, 85.0, 90.0])
+        .map(|(text, confidence)| Symbol {
+            text: text.to_string(),
+            choices: vec![
+                Choice {
+                    text: text.to_string(),
+                    confidence,
+                },
+                Choice {
+                    text: text.to_uppercase(),
+                    confidence: 100.0 - confidence,
+                },
+            ],
+            ..Default::default()
+        })
+        .collect();
+    let texts: Vec<String> = get_alternatives(&symbols, 4)
+        .into_iter()
+        .map(|choice| choice.text)
+        .collect();
+    assert_eq!(texts, vec!["abc", "Abc", "aBc", "abC"]);
+    assert_eq!(
+        TesseractApi::default()
+            .get_attr("lstm_choice_mode")
+            .unwrap_right()
+            .unwrap(),
+        &TesseractDefaultConstants::DEFAULT_LSTM_CHOICE_MODE
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reading_order() -> Result<(), TesseractError> {
+    // A headline over two columns with aligned paragraph gaps, a footer, blocks in the order of layout analysis.
+    let blocks = [
+        ("R1", BoundingBox::new(520, 150, 900, 250)),
+        ("Headline", BoundingBox::new(100, 50, 900, 100)),
+        ("L2", BoundingBox::new(100, 260, 480, 500)),
+        ("Footer", BoundingBox::new(100, 650, 900, 700)),
+        ("L1", BoundingBox::new(100, 150, 480, 250)),
+        ("R2", BoundingBox::new(520, 260, 900, 600)),
+        ("L3", BoundingBox::new(100, 520, 480, 560)),
+    ];
+    let boxes: Vec<BoundingBox> = blocks.iter().map(|(_, bbox)| *bbox).collect();
+    let names = |order: Vec<usize>| {
+        order
+            .into_iter()
+            .map(|index| blocks[index].0)
+            .collect::<Vec<&str>>()
+    };
+    assert_eq!(
+        names(get_reading_order(&boxes, &ReadingOrderOptions::default())),
+        vec!["Headline", "L1", "L2", "L3", "R1", "R2", "Footer"]
+    );
+    let rtl = ReadingOrderOptions {
+        direction: Some(TextDirection::from_lang("heb+eng")),
+        ..Default::default()
+    };
+    assert_eq!(
+        names(get_reading_order(&boxes, &rtl)),
+        vec!["Headline", "R1", "R2", "L1", "L2", "L3", "Footer"]
+    );
+    let narrow = ReadingOrderOptions {
+        min_column_gap: 50,
+        ..Default::default()
+    };
+    assert_eq!(
+        names(get_reading_order(&boxes, &narrow)),
+        vec!["Headline", "L1", "R1", "L2", "R2", "L3", "Footer"]
+    );
+
+    let areas: String = blocks
+        .iter()
+        .map(|(text, bbox)| {
+            let bbox = format!("bbox {} {} {} {}", bbox.left, bbox.top, bbox.right, bbox.bottom);
+            format!("<div class='ocr_carea' title='{0}'><span class='ocr_line' title='{0}'><span class='ocrx_word' title='{0}'>{1}</span></span></div>", bbox, text)
+        })
+        .collect();
+    let mut pages = parse_hocr(&format!(
+        "<div class='ocr_page' title='bbox 0 0 1000 1000'>{}</div>",
+        areas
+    ))?;
+    order_page(&mut pages[0], &ReadingOrderOptions::default());
+    assert_eq!(
+        pages[0].get_text(),
+        "Headline\n\nL1\n\nL2\n\nL3\n\nR1\n\nR2\n\nFooter"
+    );
+    assert!(pages[0]
+        .blocks
+        .iter()
+        .enumerate()
+        .all(|(index, block)| block.order == Some(index)));
+    Ok(())
+}